@@ -0,0 +1,159 @@
+use std::io::{self, BufRead, Read, Write};
+
+/// Largest request body we are willing to read and discard.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The parts of an HTTP/1.1 request the local API cares about.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// The request target without its query string.
+    pub path: String,
+    pub authorization: Option<String>,
+}
+
+/// Reads a request line and headers, then consumes any body so the
+/// connection is left in a clean state.
+pub fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => {
+            let path = target.split_once('?').map_or(target, |(path, _)| path);
+            (method.to_string(), path.to_string())
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Malformed request line",
+            ))
+        }
+    };
+
+    let mut authorization = None;
+    let mut content_length = 0usize;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Request body too large",
+        ));
+    }
+    io::copy(&mut reader.take(content_length as u64), &mut io::sink())?;
+
+    Ok(Request {
+        method,
+        path,
+        authorization,
+    })
+}
+
+/// Checks an `Authorization: Bearer <token>` header value against the token.
+pub fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let provided = match authorization.and_then(|h| h.strip_prefix("Bearer ")) {
+        Some(provided) => provided.trim().as_bytes(),
+        None => return false,
+    };
+    let expected = token.as_bytes();
+
+    // Compare without short-circuiting so timing doesn't reveal the token
+    !expected.is_empty()
+        && provided.len() == expected.len()
+        && provided
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Returns the value of `name` in a request target's query string, if present.
+pub fn query_param<'a>(target: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = target.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Writes a JSON response and closes the exchange.
+pub fn write_json(writer: &mut impl Write, status: u16, body: &str) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Error",
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /start HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer abc\r\nContent-Length: 2\r\n\r\n{}";
+        let request = read_request(&mut io::Cursor::new(raw)).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/start");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer abc"));
+    }
+
+    #[test]
+    fn test_query_string_is_not_part_of_path() {
+        let raw = "GET /last?x=1 HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n";
+        let request = read_request(&mut io::Cursor::new(raw)).unwrap();
+        assert_eq!(request.path, "/last");
+    }
+
+    #[test]
+    fn test_malformed_request_line() {
+        assert!(read_request(&mut io::Cursor::new("garbage\r\n\r\n")).is_err());
+    }
+
+    #[test]
+    fn test_query_param() {
+        assert_eq!(query_param("/stream?token=abc&x=1", "token"), Some("abc"));
+        assert_eq!(query_param("/stream?x=1", "token"), None);
+        assert_eq!(query_param("/stream", "token"), None);
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(Some("Bearer secret"), "secret"));
+        assert!(!is_authorized(Some("Bearer wrong!"), "secret"));
+        assert!(!is_authorized(Some("secret"), "secret"));
+        assert!(!is_authorized(None, "secret"));
+        assert!(!is_authorized(Some("Bearer "), ""));
+    }
+}
@@ -0,0 +1,231 @@
+pub mod http;
+
+use crate::audio::levels::AudioLevels;
+use crate::state::{DictationState, SharedState};
+use crate::StreamSubscribers;
+use std::io::BufReader;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use tauri::Manager;
+use tungstenite::Message;
+
+/// How often a stream client checks for new frames and for shutdown.
+const STREAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Generates a random 128-bit token from the OS random number generator, hex-encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("Failed to read from the OS random number generator");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Starts the local API server on 127.0.0.1:`port`. The accept loop polls
+/// `stop` so the server can be shut down without a connection arriving.
+pub fn start(
+    app_handle: tauri::AppHandle,
+    port: u16,
+    token: String,
+    stop: Arc<AtomicBool>,
+) -> std::io::Result<std::thread::JoinHandle<()>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    listener.set_nonblocking(true)?;
+    log::info!("Local API listening on 127.0.0.1:{}", port);
+
+    Ok(std::thread::spawn(move || {
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let app_handle = app_handle.clone();
+                    let token = token.clone();
+                    let stop = Arc::clone(&stop);
+                    std::thread::spawn(move || {
+                        let result = match is_stream_request(&stream) {
+                            Ok(true) => handle_stream(&app_handle, stream, &token, &stop),
+                            Ok(false) => handle_connection(&app_handle, stream, &token),
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = result {
+                            log::warn!("Local API request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                Err(e) => {
+                    log::warn!("Local API accept failed: {}", e);
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+            }
+        }
+        log::info!("Local API stopped");
+    }))
+}
+
+/// Sends a frame to every connected stream client, dropping clients that have gone away.
+pub fn publish(app_handle: &tauri::AppHandle, frame: serde_json::Value) {
+    let subscribers = app_handle.state::<StreamSubscribers>();
+    let mut subscribers = subscribers.0.lock();
+    if subscribers.is_empty() {
+        return;
+    }
+    let frame = frame.to_string();
+    subscribers.retain(|tx| tx.send(frame.clone()).is_ok());
+}
+
+/// Peeks at the request line to tell WebSocket clients of `/stream` apart from
+/// plain HTTP requests without consuming the handshake.
+fn is_stream_request(stream: &TcpStream) -> std::io::Result<bool> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+    let mut buf = [0u8; 12];
+    let n = stream.peek(&mut buf)?;
+    Ok(buf[..n].starts_with(b"GET /stream"))
+}
+
+/// Upgrades the connection to a WebSocket and forwards published frames until the
+/// client goes away or the server is stopped.
+fn handle_stream(
+    app_handle: &tauri::AppHandle,
+    stream: TcpStream,
+    token: &str,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    let callback = |request: &tungstenite::handshake::server::Request,
+                    response: tungstenite::handshake::server::Response| {
+        let header = request
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        // Browsers can't set headers on a WebSocket, so also accept ?token=
+        let query = http::query_param(&request.uri().to_string(), "token")
+            .map(|token| format!("Bearer {}", token));
+        if http::is_authorized(header.or(query).as_deref(), token) {
+            Ok(response)
+        } else {
+            Err(tungstenite::http::Response::builder()
+                .status(401)
+                .body(Some("Unauthorized".to_string()))
+                .unwrap())
+        }
+    };
+    let mut socket = tungstenite::accept_hdr(stream, callback)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    let (tx, rx) = mpsc::channel::<String>();
+    app_handle.state::<StreamSubscribers>().0.lock().push(tx);
+
+    // Start the client off with the current state
+    let current = app_handle
+        .state::<SharedState>()
+        .lock()
+        .dictation_state
+        .clone();
+    let _ = socket.send(Message::text(state_frame(&current).to_string()));
+
+    while !stop.load(Ordering::SeqCst) {
+        match rx.recv_timeout(STREAM_POLL_INTERVAL) {
+            Ok(frame) => {
+                if socket.send(Message::text(frame)).is_err() {
+                    return Ok(());
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let _ = socket.close(None);
+    let _ = socket.flush();
+    Ok(())
+}
+
+/// A stream frame carrying the dictation state, in the same shape as the
+/// 'dictation-state' event the overlay receives.
+pub fn state_frame(dictation_state: &DictationState) -> serde_json::Value {
+    serde_json::json!({ "event": "dictation-state", "state": dictation_state })
+}
+
+/// A stream frame carrying the latest audio levels and loudness summary.
+pub fn levels_frame(levels: &AudioLevels) -> serde_json::Value {
+    serde_json::json!({
+        "event": "audio-levels",
+        "levels": levels.bars,
+        "peak": levels.peak,
+        "average": levels.average,
+        "too_quiet": levels.too_quiet,
+    })
+}
+
+fn handle_connection(
+    app_handle: &tauri::AppHandle,
+    stream: TcpStream,
+    token: &str,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = http::read_request(&mut reader)?;
+    let mut writer = stream;
+
+    if !http::is_authorized(request.authorization.as_deref(), token) {
+        return http::write_json(&mut writer, 401, r#"{"error":"Unauthorized"}"#);
+    }
+
+    let (status, body) = route(app_handle, &request.method, &request.path);
+    http::write_json(&mut writer, status, &body)
+}
+
+fn route(app_handle: &tauri::AppHandle, method: &str, path: &str) -> (u16, String) {
+    let (dictation_state, history_enabled) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let state = shared_state.lock();
+        (state.dictation_state.clone(), state.history_enabled)
+    };
+
+    match (method, path) {
+        ("POST", "/start") => {
+            if !matches!(dictation_state, DictationState::Idle) {
+                return conflict("Not idle");
+            }
+            crate::toggle_recording(app_handle);
+            ok()
+        }
+        ("POST", "/stop") => {
+            if !matches!(dictation_state, DictationState::Recording { .. }) {
+                return conflict("Not recording");
+            }
+            crate::toggle_recording(app_handle);
+            ok()
+        }
+        ("POST", "/cancel") => {
+            crate::cancel_recording(app_handle.clone());
+            ok()
+        }
+        ("GET", "/last") if !history_enabled => {
+            (404, r#"{"error":"History is disabled"}"#.to_string())
+        }
+        ("GET", "/last") => match crate::history::load_history().entries.first() {
+            Some(entry) => (
+                200,
+                serde_json::to_string(entry).unwrap_or_else(|_| "{}".to_string()),
+            ),
+            None => (404, r#"{"error":"No history"}"#.to_string()),
+        },
+        (_, "/start" | "/stop" | "/cancel" | "/last") => {
+            (405, r#"{"error":"Method not allowed"}"#.to_string())
+        }
+        _ => (404, r#"{"error":"Not found"}"#.to_string()),
+    }
+}
+
+fn ok() -> (u16, String) {
+    (200, r#"{"ok":true}"#.to_string())
+}
+
+fn conflict(message: &str) -> (u16, String) {
+    (409, serde_json::json!({ "error": message }).to_string())
+}
@@ -0,0 +1,52 @@
+/// Joins the next dictation onto the append-mode buffer, inserting a space
+/// between bursts unless the buffer already ends a line or the new text starts
+/// with closing punctuation.
+pub fn join_dictation(buffer: &str, next: &str) -> String {
+    let next = next.trim();
+    if buffer.is_empty() {
+        return next.to_string();
+    }
+    if next.is_empty() {
+        return buffer.to_string();
+    }
+
+    let needs_space = !buffer.ends_with(char::is_whitespace)
+        && !next.starts_with([',', '.', ';', ':', '!', '?', ')']);
+
+    let mut joined = String::with_capacity(buffer.len() + next.len() + 1);
+    joined.push_str(buffer);
+    if needs_space {
+        joined.push(' ');
+    }
+    joined.push_str(next);
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_burst_is_trimmed() {
+        assert_eq!(join_dictation("", "  Hello there. "), "Hello there.");
+    }
+
+    #[test]
+    fn test_bursts_are_space_separated() {
+        assert_eq!(
+            join_dictation("Hello there.", "How are you?"),
+            "Hello there. How are you?"
+        );
+    }
+
+    #[test]
+    fn test_no_space_before_punctuation_or_after_newline() {
+        assert_eq!(join_dictation("Hello", ", world"), "Hello, world");
+        assert_eq!(join_dictation("Title\n\n", "Body"), "Title\n\nBody");
+    }
+
+    #[test]
+    fn test_empty_burst_keeps_buffer() {
+        assert_eq!(join_dictation("Hello", "   "), "Hello");
+    }
+}
@@ -1,48 +1,259 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::Stream;
+use serde::Serialize;
 use std::sync::{Arc, Mutex};
 
 use super::resampler;
 
+/// Buffer capacity reserved up front when no recording limit is set.
+const DEFAULT_PREALLOC_SECS: u64 = 60;
+/// Upper bound on the up-front reservation, however long the limit is.
+const MAX_PREALLOC_SECS: u64 = 600;
+/// Minimum the buffer grows by once the reservation runs out, so the callback
+/// reallocates rarely while holding the lock.
+const GROW_SECS: u64 = 30;
+
+/// Seconds of audio to reserve for a recording capped at `max_recording_ms`
+/// (0 for no cap).
+fn prealloc_secs(max_recording_ms: u64) -> u64 {
+    if max_recording_ms == 0 {
+        DEFAULT_PREALLOC_SECS
+    } else {
+        max_recording_ms.div_ceil(1000).min(MAX_PREALLOC_SECS)
+    }
+}
+
+/// Appends `samples`, growing by at least `grow_by` samples when full.
+fn append_samples(buf: &mut Vec<f32>, samples: &[f32], grow_by: usize) {
+    if buf.capacity() - buf.len() < samples.len() {
+        buf.reserve(samples.len().max(grow_by));
+    }
+    buf.extend_from_slice(samples);
+}
+
+/// A channel needs at least this RMS level over the recording to count as live.
+const LIVE_CHANNEL_RMS: f32 = 0.003;
+/// A channel below this fraction of the loudest channel's RMS (-40 dB) is dead.
+const DEAD_CHANNEL_RATIO: f32 = 0.01;
+
+/// Running per-channel energy of a recording, kept before the mono mixdown so a
+/// dead channel can be spotted.
+#[derive(Debug, Clone, Default)]
+struct ChannelEnergy {
+    sum_squares: Vec<f64>,
+    frames: u64,
+}
+
+impl ChannelEnergy {
+    /// Adds interleaved `data` with `channels` channels, converting samples with `to_f32`.
+    fn add<T: Copy>(&mut self, data: &[T], channels: usize, to_f32: impl Fn(T) -> f32) {
+        if self.sum_squares.len() != channels {
+            *self = Self {
+                sum_squares: vec![0.0; channels],
+                frames: 0,
+            };
+        }
+        for frame in data.chunks_exact(channels) {
+            for (sum, &sample) in self.sum_squares.iter_mut().zip(frame) {
+                let sample = to_f32(sample) as f64;
+                *sum += sample * sample;
+            }
+            self.frames += 1;
+        }
+    }
+
+    fn rms(&self) -> Vec<f32> {
+        self.sum_squares
+            .iter()
+            .map(|sum| (sum / self.frames.max(1) as f64).sqrt() as f32)
+            .collect()
+    }
+}
+
+/// A recording where some input channels carried signal and others were silent,
+/// which makes the mono mix quieter than it should be. Emitted as
+/// 'channel-imbalance' when a recording stops.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelImbalance {
+    pub channels: usize,
+    /// 1-based numbers of the channels that had signal.
+    pub live_channels: Vec<usize>,
+}
+
+/// Checks per-channel RMS levels for a dead channel next to a live one.
+fn channel_imbalance(rms: &[f32]) -> Option<ChannelImbalance> {
+    let loudest = rms.iter().copied().fold(0.0, f32::max);
+    if rms.len() < 2 || loudest < LIVE_CHANNEL_RMS {
+        return None;
+    }
+    let dead_below = loudest * DEAD_CHANNEL_RATIO;
+    if rms.iter().all(|&level| level >= dead_below) {
+        return None;
+    }
+    Some(ChannelImbalance {
+        channels: rms.len(),
+        live_channels: rms
+            .iter()
+            .enumerate()
+            .filter(|(_, &level)| level >= dead_below)
+            .map(|(i, _)| i + 1)
+            .collect(),
+    })
+}
+
+/// The input device recordings will use and the format it delivers.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioInfo {
+    pub device_name: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String,
+}
+
+fn device_name(device: &cpal::Device) -> String {
+    device
+        .description()
+        .map(|description| description.name().to_string())
+        .unwrap_or_else(|_| "Unknown device".to_string())
+}
+
+/// Names of the available input devices, as accepted by `AudioCapture::with_device`.
+pub fn list_input_devices() -> Vec<String> {
+    cpal::default_host()
+        .input_devices()
+        .map(|devices| devices.map(|device| device_name(&device)).collect())
+        .unwrap_or_default()
+}
+
+/// The input device called `name`, or the default input when `name` is `None`.
+fn input_device(name: Option<&str>) -> anyhow::Result<cpal::Device> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .input_devices()?
+            .find(|device| device_name(device) == name)
+            .ok_or_else(|| anyhow::anyhow!("Input device not found: {}", name)),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| anyhow::anyhow!("No input device available")),
+    }
+}
+
+/// Reads the native config of the input device called `name` (the default input
+/// for `None`), the same one `AudioCapture` records with.
+pub fn input_info(name: Option<&str>) -> anyhow::Result<AudioInfo> {
+    let device = input_device(name)?;
+    let config = device.default_input_config()?;
+
+    Ok(AudioInfo {
+        device_name: device_name(&device),
+        sample_rate: config.sample_rate(),
+        channels: config.channels(),
+        sample_format: config.sample_format().to_string(),
+    })
+}
+
+/// Mono samples at the rate they were captured.
+#[derive(Debug, Clone, Default)]
+pub struct RawAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl RawAudio {
+    /// The audio resampled to 16kHz, the rate whisper needs.
+    pub fn resampled_16k(&self) -> Vec<f32> {
+        resampler::resample(&self.samples, self.sample_rate, 16000)
+    }
+}
+
 pub struct AudioCapture {
     stream: Option<Stream>,
     buffer: Arc<Mutex<Vec<f32>>>,
+    channel_energy: Arc<Mutex<ChannelEnergy>>,
     device_sample_rate: u32,
+    /// The input device the stream was opened on, to detect route changes.
+    device_id: Option<cpal::DeviceId>,
+    /// The device chosen by name; `None` follows the system default input.
+    device_name: Option<String>,
 }
 
 impl AudioCapture {
+    /// Records from the system default input.
     pub fn new() -> anyhow::Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow::anyhow!("No input device available"))?;
+        Self::open(None)
+    }
+
+    /// Records from the input device called `name` (see `list_input_devices`).
+    pub fn with_device(name: &str) -> anyhow::Result<Self> {
+        Self::open(Some(name))
+    }
+
+    fn open(name: Option<&str>) -> anyhow::Result<Self> {
+        let device = input_device(name)?;
         let config = device.default_input_config()?;
         let device_sample_rate = config.sample_rate();
 
         Ok(Self {
             stream: None,
             buffer: Arc::new(Mutex::new(Vec::new())),
+            channel_energy: Arc::new(Mutex::new(ChannelEnergy::default())),
             device_sample_rate,
+            device_id: None,
+            device_name: name.map(str::to_string),
         })
     }
 
-    pub fn start_recording(&mut self) -> anyhow::Result<()> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow::anyhow!("No input device available"))?;
-        let config = device.default_input_config()?;
-        let channels = config.channels() as usize;
-        let sample_format = config.sample_format();
-        self.device_sample_rate = config.sample_rate();
-
+    /// Starts recording on the capture's input device. The buffer is sized up front for
+    /// `max_recording_ms` (0 for no limit) so the audio callback rarely reallocates.
+    pub fn start_recording(&mut self, max_recording_ms: u64) -> anyhow::Result<()> {
         // Clear buffer before starting
         {
             let mut buf = self.buffer.lock().unwrap();
             buf.clear();
+            buf.reserve(
+                prealloc_secs(max_recording_ms) as usize * self.device_sample_rate as usize,
+            );
+        }
+        *self.channel_energy.lock().unwrap() = ChannelEnergy::default();
+
+        self.open_stream()
+    }
+
+    /// Returns true if the system default input is no longer the device being
+    /// recorded from, e.g. after headphones with a mic were connected. Always
+    /// false for a capture on a device chosen by name.
+    pub fn default_input_changed(&self) -> bool {
+        if self.device_name.is_some() {
+            return false;
         }
+        let current = cpal::default_host()
+            .default_input_device()
+            .and_then(|device| device.id().ok());
+        self.device_id.is_some() && current != self.device_id
+    }
+
+    /// Moves recording to the current default input device, keeping
+    /// everything recorded so far. If the new device can't be opened, the
+    /// error is returned and the current stream keeps recording.
+    pub fn switch_to_default_input(&mut self) -> anyhow::Result<()> {
+        self.device_name = None;
+        self.open_stream()
+    }
+
+    /// Opens and starts a stream on the capture's input device, appending to
+    /// the existing buffer. Any stream already running is only replaced once
+    /// the new one has been built.
+    fn open_stream(&mut self) -> anyhow::Result<()> {
+        let device = input_device(self.device_name.as_deref())?;
+        let config = device.default_input_config()?;
+        let channels = config.channels() as usize;
+        let sample_format = config.sample_format();
+        let sample_rate = config.sample_rate();
 
         let buffer = Arc::clone(&self.buffer);
+        let channel_energy = Arc::clone(&self.channel_energy);
+        let grow_by = GROW_SECS as usize * sample_rate as usize;
 
         let stream = match sample_format {
             cpal::SampleFormat::F32 => device.build_input_stream(
@@ -52,8 +263,9 @@ impl AudioCapture {
                         .chunks(channels)
                         .map(|frame| frame.iter().sum::<f32>() / channels as f32)
                         .collect();
+                    channel_energy.lock().unwrap().add(data, channels, |s| s);
                     let mut buf = buffer.lock().unwrap();
-                    buf.extend_from_slice(&mono);
+                    append_samples(&mut buf, &mono, grow_by);
                 },
                 |err| eprintln!("Audio stream error: {}", err),
                 None,
@@ -71,8 +283,12 @@ impl AudioCapture {
                                 sum / channels as f32
                             })
                             .collect();
+                        channel_energy
+                            .lock()
+                            .unwrap()
+                            .add(data, channels, |s| s as f32 / i16::MAX as f32);
                         let mut buf = buffer.lock().unwrap();
-                        buf.extend_from_slice(&mono);
+                        append_samples(&mut buf, &mono, grow_by);
                     },
                     |err| eprintln!("Audio stream error: {}", err),
                     None,
@@ -86,21 +302,46 @@ impl AudioCapture {
             }
         };
 
+        // Stop the old stream before touching the buffer it writes to
+        self.stream = None;
+
+        // Samples from a previous device must match the new device's rate
+        if sample_rate != self.device_sample_rate {
+            let mut buf = self.buffer.lock().unwrap();
+            *buf = resampler::resample(&buf, self.device_sample_rate, sample_rate);
+        }
+        self.device_sample_rate = sample_rate;
+        self.device_id = device.id().ok();
+
         stream.play()?;
         self.stream = Some(stream);
         Ok(())
     }
 
-    /// Clones the current audio buffer and resamples to 16kHz for transcription.
+    /// Clones the last `window_secs` seconds of the audio buffer and resamples to
+    /// 16kHz for partial transcription. The window start is aligned so it falls
+    /// exactly on a resampled sample.
     /// This does NOT stop recording — the stream continues appending samples.
-    pub fn clone_buffer_resampled(&self) -> Vec<f32> {
+    pub fn clone_tail_resampled(&self, window_secs: f32) -> Vec<f32> {
         let buffer = {
             let buf = self.buffer.lock().unwrap();
-            buf.clone()
+            let start = resampler::aligned_tail_start(
+                buf.len(),
+                window_secs,
+                self.device_sample_rate,
+                16000,
+            );
+            buf[start..].to_vec()
         };
         resampler::resample(&buffer, self.device_sample_rate, 16000)
     }
 
+    /// Whether the recording so far had signal on some input channels but not
+    /// others. Only the device in use at the time is considered.
+    pub fn channel_imbalance(&self) -> Option<ChannelImbalance> {
+        channel_imbalance(&self.channel_energy.lock().unwrap().rms())
+    }
+
     pub fn sample_rate(&self) -> u32 {
         self.device_sample_rate
     }
@@ -109,15 +350,67 @@ impl AudioCapture {
         &self.buffer
     }
 
-    pub fn stop_recording(&mut self) -> Vec<f32> {
+    /// Stops recording and returns the audio at the device's native rate, for
+    /// keeping or saving at full quality. Use `stop_recording` for transcription.
+    pub fn stop_recording_raw(&mut self) -> RawAudio {
         // Drop the stream to stop recording
         self.stream = None;
 
-        let buffer = {
-            let mut buf = self.buffer.lock().unwrap();
-            std::mem::take(&mut *buf)
-        };
+        let samples = std::mem::take(&mut *self.buffer.lock().unwrap());
+        RawAudio {
+            samples,
+            sample_rate: self.device_sample_rate,
+        }
+    }
 
-        resampler::resample(&buffer, self.device_sample_rate, 16000)
+    /// Stops recording and returns the audio resampled to 16kHz for whisper.
+    pub fn stop_recording(&mut self) -> Vec<f32> {
+        self.stop_recording_raw().resampled_16k()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prealloc_is_bounded() {
+        assert_eq!(prealloc_secs(0), DEFAULT_PREALLOC_SECS);
+        assert_eq!(prealloc_secs(90_500), 91);
+        assert_eq!(prealloc_secs(24 * 60 * 60 * 1000), MAX_PREALLOC_SECS);
+    }
+
+    #[test]
+    fn test_detects_dead_channel() {
+        let mut energy = ChannelEnergy::default();
+        let frames: Vec<f32> = (0..1000)
+            .flat_map(|i| [(i as f32 * 0.1).sin() * 0.3, 0.0])
+            .collect();
+        energy.add(&frames, 2, |s| s);
+        assert_eq!(
+            channel_imbalance(&energy.rms()),
+            Some(ChannelImbalance {
+                channels: 2,
+                live_channels: vec![1],
+            })
+        );
+    }
+
+    #[test]
+    fn test_balanced_or_silent_channels_pass() {
+        assert_eq!(channel_imbalance(&[0.1, 0.08]), None);
+        assert_eq!(channel_imbalance(&[0.0, 0.0]), None);
+        assert_eq!(channel_imbalance(&[0.1]), None);
+    }
+
+    #[test]
+    fn test_append_grows_in_chunks() {
+        let mut buf = Vec::new();
+        append_samples(&mut buf, &[0.5; 4], 100);
+        assert!(buf.capacity() >= 100);
+        let capacity = buf.capacity();
+        append_samples(&mut buf, &[0.5; 4], 100);
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf.len(), 8);
     }
 }
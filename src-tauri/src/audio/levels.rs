@@ -1,3 +1,35 @@
+use serde::Serialize;
+
+/// Level bars plus overall loudness, emitted as 'audio-levels' while recording.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioLevels {
+    pub bars: Vec<f32>,
+    /// Loudest bar.
+    pub peak: f32,
+    /// Mean of the bars.
+    pub average: f32,
+    /// Even the loudest bar is under the configured threshold, so the overlay
+    /// can ask the user to speak up.
+    pub too_quiet: bool,
+}
+
+/// Summarizes `bars` from `compute_levels`. A `too_quiet_threshold` of 0 never
+/// reports the audio as too quiet.
+pub fn summarize_levels(bars: Vec<f32>, too_quiet_threshold: f32) -> AudioLevels {
+    let peak = bars.iter().copied().fold(0.0, f32::max);
+    let average = if bars.is_empty() {
+        0.0
+    } else {
+        bars.iter().sum::<f32>() / bars.len() as f32
+    };
+    AudioLevels {
+        bars,
+        peak,
+        average,
+        too_quiet: too_quiet_threshold > 0.0 && peak < too_quiet_threshold,
+    }
+}
+
 /// Computes RMS amplitude levels from the tail of an audio buffer.
 /// Returns `num_bars` values, each representing ~33ms of audio.
 pub fn compute_levels(buffer: &[f32], sample_rate: u32, num_bars: usize) -> Vec<f32> {
@@ -25,3 +57,146 @@ pub fn compute_levels(buffer: &[f32], sample_rate: u32, num_bars: usize) -> Vec<
     }
     levels
 }
+
+/// How long at the start of a recording is used to measure the room's noise floor.
+pub const NOISE_CALIBRATION_MS: u64 = 500;
+/// A level counts as speech once it is this many times the noise floor.
+const SPEECH_TO_FLOOR_RATIO: f32 = 3.0;
+/// Lowest speech threshold, so a digitally silent floor doesn't make any hiss speech.
+const MIN_SPEECH_LEVEL: f32 = 0.005;
+
+/// Detects the end of speech for hands-free auto-stop. The first
+/// `NOISE_CALIBRATION_MS` of levels set the noise floor, and speech is anything
+/// well above it, so the same settings work in quiet and noisy rooms. Nothing
+/// is reported until speech has been heard at least once.
+#[derive(Debug, Clone)]
+pub struct SilenceDetector {
+    timeout_ms: u64,
+    calibration: Vec<f32>,
+    speech_level: Option<f32>,
+    heard_speech: bool,
+    silent_since_ms: Option<u64>,
+}
+
+impl SilenceDetector {
+    pub fn new(timeout_ms: u64) -> Self {
+        Self {
+            timeout_ms,
+            calibration: Vec::new(),
+            speech_level: None,
+            heard_speech: false,
+            silent_since_ms: None,
+        }
+    }
+
+    /// Feeds the latest level, `elapsed_ms` into the recording. Returns true once
+    /// the levels have stayed below the speech threshold for `timeout_ms` after
+    /// speech.
+    pub fn update(&mut self, level: f32, elapsed_ms: u64) -> bool {
+        let Some(speech_level) = self.speech_level else {
+            self.calibration.push(level);
+            if elapsed_ms >= NOISE_CALIBRATION_MS {
+                // The median ignores a word spoken before calibration ends
+                self.calibration.sort_by(f32::total_cmp);
+                let floor = self.calibration[self.calibration.len() / 2];
+                self.speech_level = Some((floor * SPEECH_TO_FLOOR_RATIO).max(MIN_SPEECH_LEVEL));
+            }
+            return false;
+        };
+
+        if level >= speech_level {
+            self.heard_speech = true;
+            self.silent_since_ms = None;
+            return false;
+        }
+        if !self.heard_speech {
+            return false;
+        }
+        let silent_since_ms = *self.silent_since_ms.get_or_insert(elapsed_ms);
+        elapsed_ms - silent_since_ms >= self.timeout_ms
+    }
+}
+
+/// Largest resolution `compute_waveform` will produce, to keep payloads small.
+pub const MAX_WAVEFORM_RESOLUTION: usize = 4096;
+
+/// Computes an RMS envelope over the whole buffer, split into `resolution`
+/// equal buckets (capped at `MAX_WAVEFORM_RESOLUTION` and the buffer length).
+/// Returns an empty vec for an empty buffer.
+pub fn compute_waveform(buffer: &[f32], resolution: usize) -> Vec<f32> {
+    let resolution = resolution.min(MAX_WAVEFORM_RESOLUTION).min(buffer.len());
+    (0..resolution)
+        .map(|i| {
+            let start = i * buffer.len() / resolution;
+            let end = (i + 1) * buffer.len() / resolution;
+            let chunk = &buffer[start..end];
+            (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_flags_quiet_audio() {
+        let levels = summarize_levels(vec![0.01, 0.03, 0.02], 0.05);
+        assert_eq!(levels.peak, 0.03);
+        assert!((levels.average - 0.02).abs() < 1e-6);
+        assert!(levels.too_quiet);
+        assert!(!summarize_levels(vec![0.01, 0.1], 0.05).too_quiet);
+        assert!(!summarize_levels(vec![0.0; 4], 0.0).too_quiet);
+    }
+
+    /// Feeds `level` every 33ms from `from_ms` until `to_ms`, returning when the
+    /// detector first fired.
+    fn feed(detector: &mut SilenceDetector, level: f32, from_ms: u64, to_ms: u64) -> Option<u64> {
+        (from_ms..to_ms)
+            .step_by(33)
+            .find(|&ms| detector.update(level, ms))
+    }
+
+    #[test]
+    fn test_silence_detector_stops_after_trailing_silence() {
+        let mut detector = SilenceDetector::new(1000);
+        assert_eq!(feed(&mut detector, 0.001, 0, 600), None);
+        assert_eq!(feed(&mut detector, 0.2, 600, 2000), None);
+        let fired = feed(&mut detector, 0.001, 2000, 4000).unwrap();
+        assert!((3000..3100).contains(&fired));
+    }
+
+    #[test]
+    fn test_silence_detector_waits_for_speech() {
+        let mut detector = SilenceDetector::new(500);
+        assert_eq!(feed(&mut detector, 0.001, 0, 5000), None);
+    }
+
+    #[test]
+    fn test_silence_detector_adapts_to_noise_floor() {
+        // Steady room noise at 0.05 is neither speech nor silence-breaking
+        let mut detector = SilenceDetector::new(500);
+        assert_eq!(feed(&mut detector, 0.05, 0, 600), None);
+        assert_eq!(feed(&mut detector, 0.1, 600, 1200), None);
+        assert_eq!(feed(&mut detector, 0.3, 1200, 2000), None);
+        assert!(feed(&mut detector, 0.05, 2000, 3000).is_some());
+    }
+
+    #[test]
+    fn test_waveform_covers_whole_buffer() {
+        let mut buffer = vec![0.0; 100];
+        buffer.extend(vec![0.5; 100]);
+        assert_eq!(compute_waveform(&buffer, 4), vec![0.0, 0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_waveform_resolution_is_capped() {
+        assert!(compute_waveform(&[], 100).is_empty());
+        assert_eq!(compute_waveform(&[0.5; 3], 100).len(), 3);
+        let long = vec![0.1; MAX_WAVEFORM_RESOLUTION * 2];
+        assert_eq!(
+            compute_waveform(&long, usize::MAX).len(),
+            MAX_WAVEFORM_RESOLUTION
+        );
+    }
+}
@@ -1,3 +1,6 @@
 pub mod capture;
+pub mod decode;
 pub mod levels;
+pub mod permission;
 pub mod resampler;
+pub mod silence;
@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+/// Microphone authorization as reported by the OS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MicrophonePermission {
+    /// The user hasn't been asked yet; macOS prompts on the first recording.
+    NotDetermined,
+    /// Blocked by a system policy (e.g. parental controls or MDM).
+    Restricted,
+    /// The user refused access; it can only be granted in System Settings.
+    Denied,
+    Granted,
+}
+
+impl MicrophonePermission {
+    /// Whether recording is blocked until the user changes System Settings.
+    pub fn is_blocked(self) -> bool {
+        matches!(self, Self::Restricted | Self::Denied)
+    }
+}
+
+/// Reads the microphone authorization status from AVFoundation.
+#[cfg(target_os = "macos")]
+pub fn check_microphone_permission() -> MicrophonePermission {
+    use cocoa::base::id;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: id;
+    }
+
+    // AVAuthorizationStatus
+    let status: isize = unsafe {
+        msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+    };
+    match status {
+        0 => MicrophonePermission::NotDetermined,
+        1 => MicrophonePermission::Restricted,
+        2 => MicrophonePermission::Denied,
+        _ => MicrophonePermission::Granted,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check_microphone_permission() -> MicrophonePermission {
+    MicrophonePermission::Granted
+}
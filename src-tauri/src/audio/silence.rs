@@ -0,0 +1,111 @@
+/// RMS level at or above which a frame always counts as speech.
+const SILENCE_THRESHOLD: f32 = 0.01;
+/// Fraction of the loudest frame's RMS that also counts as speech (about -20 dB),
+/// so a quiet mic or a soft speaker is measured against its own level.
+const RELATIVE_THRESHOLD: f32 = 0.1;
+/// Length of each analysis frame.
+const FRAME_MS: u32 = 20;
+/// Audio kept on either side of the detected speech so onsets aren't clipped.
+const PADDING_MS: u32 = 200;
+
+/// Drops leading and trailing silence from a buffer, keeping a small padding
+/// margin around the first and last frames above the energy threshold. The
+/// threshold drops below `SILENCE_THRESHOLD` for recordings that never get that
+/// loud, and a buffer with no signal at all is returned untrimmed, so trimming
+/// never throws away a recording that could have been transcribed.
+pub fn trim_silence(buffer: &[f32], sample_rate: u32) -> Vec<f32> {
+    let frame_len = ((sample_rate * FRAME_MS / 1000) as usize).max(1);
+    let padding = (sample_rate * PADDING_MS / 1000) as usize;
+
+    let frame_rms =
+        |frame: &[f32]| (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+    let peak = buffer.chunks(frame_len).map(frame_rms).fold(0.0, f32::max);
+    let threshold = (peak * RELATIVE_THRESHOLD).min(SILENCE_THRESHOLD);
+    let is_loud = |frame: &[f32]| {
+        let rms = frame_rms(frame);
+        rms > 0.0 && rms >= threshold
+    };
+
+    let first = buffer.chunks(frame_len).position(is_loud);
+    let last = buffer.chunks(frame_len).rposition(is_loud);
+
+    match (first, last) {
+        (Some(first), Some(last)) => {
+            let start = (first * frame_len).saturating_sub(padding);
+            let end = ((last + 1) * frame_len + padding).min(buffer.len());
+            buffer[start..end].to_vec()
+        }
+        _ => buffer.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(len: usize) -> Vec<f32> {
+        tone_at(len, 0.5)
+    }
+
+    fn tone_at(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 * 0.1).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn test_trims_leading_and_trailing_silence() {
+        let silence = vec![0.0; RATE as usize];
+        let speech = tone(RATE as usize / 2);
+        let buffer = [silence.clone(), speech.clone(), silence].concat();
+
+        let trimmed = trim_silence(&buffer, RATE);
+        let padding = (RATE * PADDING_MS / 1000) as usize;
+
+        assert_eq!(trimmed.len(), speech.len() + 2 * padding);
+        assert!(trimmed[..padding].iter().all(|&s| s == 0.0));
+        assert!(trimmed[trimmed.len() - padding..].iter().all(|&s| s == 0.0));
+        assert_eq!(&trimmed[padding..padding + speech.len()], &speech[..]);
+    }
+
+    #[test]
+    fn test_padding_clamped_to_buffer() {
+        let speech = tone(RATE as usize / 4);
+        assert_eq!(trim_silence(&speech, RATE), speech);
+    }
+
+    #[test]
+    fn test_quiet_speech_is_kept() {
+        // Peaks well under SILENCE_THRESHOLD, like a soft speaker on a quiet mic
+        let silence = vec![0.0; RATE as usize];
+        let speech = tone_at(RATE as usize / 2, 0.004);
+        let buffer = [silence.clone(), speech.clone(), silence].concat();
+
+        let trimmed = trim_silence(&buffer, RATE);
+        let padding = (RATE * PADDING_MS / 1000) as usize;
+
+        assert_eq!(trimmed.len(), speech.len() + 2 * padding);
+        assert_eq!(&trimmed[padding..padding + speech.len()], &speech[..]);
+    }
+
+    #[test]
+    fn test_quiet_background_still_trimmed_in_loud_recording() {
+        let hiss = tone_at(RATE as usize, 0.002);
+        let speech = tone(RATE as usize / 2);
+        let buffer = [hiss.clone(), speech.clone(), hiss].concat();
+
+        let padding = (RATE * PADDING_MS / 1000) as usize;
+        assert_eq!(
+            trim_silence(&buffer, RATE).len(),
+            speech.len() + 2 * padding
+        );
+    }
+
+    #[test]
+    fn test_all_silence_is_left_untrimmed() {
+        let silence = vec![0.0; RATE as usize];
+        assert_eq!(trim_silence(&silence, RATE), silence);
+    }
+}
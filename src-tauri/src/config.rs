@@ -1,12 +1,35 @@
+use crate::formatting::{FormattingProfile, FormattingRule};
+use crate::history::DEFAULT_TIME_FORMAT;
+use crate::quiet_hours::QuietHours;
+use crate::state::{CorrectionStage, HotkeyDuringProcessing};
+use crate::transcription::whisper::{
+    WhisperSampling, DEFAULT_ENTROPY_THRESHOLD, DEFAULT_NO_SPEECH_THRESHOLD, DEFAULT_TEMPERATURE,
+};
+use crate::translation::engine::DEFAULT_MAX_INPUT_TOKENS;
 use crate::translation::model_manager::DEFAULT_TRANSLATION_MODEL;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 const DEFAULT_HOTKEY: &str = "alt+space";
 const DEFAULT_MODEL: &str = "base.en";
 const DEFAULT_LANGUAGE: &str = "en";
 const DEFAULT_TRANSLATION_TARGET_LANG: &str = "en";
+const DEFAULT_MIN_RECORDING_MS: u64 = 400;
+const DEFAULT_MIN_WORD_CHARS: usize = 2;
+/// Recordings stop on their own after 5 minutes unless configured otherwise, so a
+/// forgotten recording can't grow without bound.
+const DEFAULT_MAX_RECORDING_MS: u64 = 5 * 60 * 1000;
+const DEFAULT_MAX_RECORDING_WARNING_MS: u64 = 5000;
+const DEFAULT_TOO_QUIET_THRESHOLD: f32 = 0.02;
+const DEFAULT_SILENCE_TIMEOUT_MS: u64 = 1500;
+const DEFAULT_PARTIAL_WINDOW_SECS: f32 = 15.0;
+const DEFAULT_LOCAL_API_PORT: u16 = 47821;
+const DEFAULT_PROCESSING_TIMEOUT_MS: u64 = 70_000;
+const DEFAULT_MODEL_BASE_URL: &str = "https://huggingface.co";
+/// Environment variable that overrides `model_base_url`, e.g. for a corporate mirror.
+const MODEL_BASE_URL_ENV: &str = "WREN_MODEL_BASE_URL";
 
 fn default_model() -> String {
     DEFAULT_MODEL.to_string()
@@ -24,6 +47,78 @@ fn default_translation_model() -> String {
     DEFAULT_TRANSLATION_MODEL.to_string()
 }
 
+fn default_translation_max_input_tokens() -> usize {
+    DEFAULT_MAX_INPUT_TOKENS
+}
+
+fn default_min_recording_ms() -> u64 {
+    DEFAULT_MIN_RECORDING_MS
+}
+
+fn default_min_word_chars() -> usize {
+    DEFAULT_MIN_WORD_CHARS
+}
+
+fn default_max_recording_ms() -> u64 {
+    DEFAULT_MAX_RECORDING_MS
+}
+
+fn default_max_recording_warning_ms() -> u64 {
+    DEFAULT_MAX_RECORDING_WARNING_MS
+}
+
+fn default_too_quiet_threshold() -> f32 {
+    DEFAULT_TOO_QUIET_THRESHOLD
+}
+
+fn default_silence_timeout_ms() -> u64 {
+    DEFAULT_SILENCE_TIMEOUT_MS
+}
+
+fn default_partial_window_secs() -> f32 {
+    DEFAULT_PARTIAL_WINDOW_SECS
+}
+
+fn default_local_api_port() -> u16 {
+    DEFAULT_LOCAL_API_PORT
+}
+
+fn default_model_base_url() -> String {
+    DEFAULT_MODEL_BASE_URL.to_string()
+}
+
+fn default_processing_timeout_ms() -> u64 {
+    DEFAULT_PROCESSING_TIMEOUT_MS
+}
+
+fn default_intercepted_preview_keys() -> Vec<String> {
+    vec!["enter".to_string(), "escape".to_string()]
+}
+
+fn default_history_time_format() -> String {
+    DEFAULT_TIME_FORMAT.to_string()
+}
+
+fn default_best_of() -> u32 {
+    WhisperSampling::default().best_of
+}
+
+fn default_temperature_fallbacks() -> u32 {
+    WhisperSampling::default().temperature_fallbacks
+}
+
+fn default_no_speech_threshold() -> f32 {
+    DEFAULT_NO_SPEECH_THRESHOLD
+}
+
+fn default_temperature() -> f32 {
+    DEFAULT_TEMPERATURE
+}
+
+fn default_entropy_threshold() -> f32 {
+    DEFAULT_ENTROPY_THRESHOLD
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub hotkey: String,
@@ -32,11 +127,23 @@ pub struct AppConfig {
     #[serde(default = "default_true")]
     pub smart_paste: bool,
     #[serde(default)]
+    pub target_app: Option<String>,
+    #[serde(default)]
     pub overlay_x: Option<f64>,
     #[serde(default)]
     pub overlay_y: Option<f64>,
+    #[serde(default)]
+    pub overlay_width: Option<f64>,
+    #[serde(default)]
+    pub overlay_height: Option<f64>,
     #[serde(default = "default_language")]
     pub language: String,
+    /// Initial prompt given to Whisper when `language_prompts` has none for the language.
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    /// Initial prompt per language code (e.g. "es"), replacing `initial_prompt`.
+    #[serde(default)]
+    pub language_prompts: HashMap<String, String>,
     #[serde(default = "default_true")]
     pub vocab_enabled: bool,
     #[serde(default)]
@@ -45,6 +152,118 @@ pub struct AppConfig {
     pub translation_target_lang: String,
     #[serde(default = "default_translation_model")]
     pub translation_model: String,
+    #[serde(default = "default_true")]
+    pub show_overlay: bool,
+    /// How long a recording runs before the overlay appears; 0 shows it immediately.
+    #[serde(default)]
+    pub overlay_show_delay_ms: u64,
+    #[serde(default = "default_min_recording_ms")]
+    pub min_recording_ms: u64,
+    #[serde(default = "default_min_word_chars")]
+    pub min_word_chars: usize,
+    #[serde(default = "default_no_speech_threshold")]
+    pub no_speech_threshold: f32,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f32,
+    #[serde(default = "default_true")]
+    pub trim_silence: bool,
+    #[serde(default)]
+    pub input_device: Option<String>,
+    #[serde(default = "default_partial_window_secs")]
+    pub partial_window_secs: f32,
+    #[serde(default)]
+    pub partial_model: Option<String>,
+    #[serde(default)]
+    pub paragraph_on_pause: bool,
+    #[serde(default)]
+    pub enable_local_api: bool,
+    #[serde(default = "default_local_api_port")]
+    pub local_api_port: u16,
+    #[serde(default)]
+    pub local_api_token: Option<String>,
+    #[serde(default)]
+    pub custom_model_path: Option<String>,
+    #[serde(default)]
+    pub notify_on_complete: bool,
+    #[serde(default)]
+    pub append_mode: bool,
+    #[serde(default)]
+    pub error_auto_dismiss_ms: u64,
+    #[serde(default)]
+    pub auto_accept_corrections: bool,
+    #[serde(default)]
+    pub auto_accept_translation: bool,
+    #[serde(default = "default_history_time_format")]
+    pub history_time_format: String,
+    #[serde(default)]
+    pub metrics_logging: bool,
+    #[serde(default = "default_model_base_url")]
+    pub model_base_url: String,
+    #[serde(default)]
+    pub offline_mode: bool,
+    #[serde(default = "default_intercepted_preview_keys")]
+    pub intercepted_preview_keys: Vec<String>,
+    #[serde(default)]
+    pub guard_enter_while_recording: bool,
+    #[serde(default = "default_processing_timeout_ms")]
+    pub processing_timeout_ms: u64,
+    #[serde(default)]
+    pub translation_target_variant: Option<String>,
+    #[serde(default)]
+    pub auto_append_file: Option<String>,
+    #[serde(default = "default_true")]
+    pub history_enabled: bool,
+    #[serde(default)]
+    pub encrypt_data: bool,
+    #[serde(default)]
+    pub encryption_salt: Option<String>,
+    #[serde(default)]
+    pub encryption_verifier: Option<String>,
+    #[serde(default = "default_best_of")]
+    pub best_of: u32,
+    #[serde(default = "default_temperature_fallbacks")]
+    pub temperature_fallbacks: u32,
+    #[serde(default)]
+    pub overlay_url: Option<String>,
+    #[serde(default)]
+    pub double_tap_cancel: bool,
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    #[serde(default = "default_true")]
+    pub strip_nonspeech_tags: bool,
+    /// Threads whisper decodes with; 0 uses every available CPU.
+    #[serde(default)]
+    pub whisper_threads: usize,
+    /// Run whisper on the GPU (Metal), falling back to the CPU when unavailable.
+    #[serde(default = "default_true")]
+    pub use_gpu: bool,
+    #[serde(default)]
+    pub correct_partials: bool,
+    #[serde(default = "default_max_recording_ms")]
+    pub max_recording_ms: u64,
+    #[serde(default = "default_max_recording_warning_ms")]
+    pub max_recording_warning_ms: u64,
+    #[serde(default = "default_too_quiet_threshold")]
+    pub too_quiet_threshold: f32,
+    /// Stop recording automatically once speech is followed by `silence_timeout_ms` of silence.
+    #[serde(default)]
+    pub vad_enabled: bool,
+    #[serde(default = "default_silence_timeout_ms")]
+    pub silence_timeout_ms: u64,
+    #[serde(default)]
+    pub max_paste_chars: usize,
+    #[serde(default = "default_translation_max_input_tokens")]
+    pub translation_max_input_tokens: usize,
+    #[serde(default)]
+    pub hotkey_during_processing: HotkeyDuringProcessing,
+    #[serde(default)]
+    pub correction_stage: CorrectionStage,
+    #[serde(default)]
+    pub formatting_profile: FormattingProfile,
+    #[serde(default)]
+    pub formatting_rules: Vec<FormattingRule>,
 }
 
 fn default_true() -> bool {
@@ -57,13 +276,72 @@ impl Default for AppConfig {
             hotkey: DEFAULT_HOTKEY.to_string(),
             selected_model: default_model(),
             smart_paste: true,
+            target_app: None,
             overlay_x: None,
             overlay_y: None,
+            overlay_width: None,
+            overlay_height: None,
             language: default_language(),
+            initial_prompt: None,
+            language_prompts: HashMap::new(),
             vocab_enabled: true,
             translation_enabled: false,
             translation_target_lang: default_translation_target_lang(),
             translation_model: default_translation_model(),
+            show_overlay: true,
+            overlay_show_delay_ms: 0,
+            min_recording_ms: default_min_recording_ms(),
+            min_word_chars: default_min_word_chars(),
+            no_speech_threshold: default_no_speech_threshold(),
+            temperature: default_temperature(),
+            entropy_threshold: default_entropy_threshold(),
+            trim_silence: true,
+            input_device: None,
+            partial_window_secs: default_partial_window_secs(),
+            partial_model: None,
+            paragraph_on_pause: false,
+            enable_local_api: false,
+            local_api_port: default_local_api_port(),
+            local_api_token: None,
+            custom_model_path: None,
+            notify_on_complete: false,
+            append_mode: false,
+            error_auto_dismiss_ms: 0,
+            auto_accept_corrections: false,
+            auto_accept_translation: false,
+            history_time_format: default_history_time_format(),
+            metrics_logging: false,
+            model_base_url: default_model_base_url(),
+            offline_mode: false,
+            intercepted_preview_keys: default_intercepted_preview_keys(),
+            guard_enter_while_recording: false,
+            processing_timeout_ms: DEFAULT_PROCESSING_TIMEOUT_MS,
+            translation_target_variant: None,
+            auto_append_file: None,
+            history_enabled: true,
+            encrypt_data: false,
+            encryption_salt: None,
+            encryption_verifier: None,
+            best_of: default_best_of(),
+            temperature_fallbacks: default_temperature_fallbacks(),
+            overlay_url: None,
+            double_tap_cancel: false,
+            quiet_hours: QuietHours::default(),
+            strip_nonspeech_tags: true,
+            whisper_threads: 0,
+            use_gpu: true,
+            correct_partials: false,
+            max_recording_ms: default_max_recording_ms(),
+            max_recording_warning_ms: default_max_recording_warning_ms(),
+            too_quiet_threshold: default_too_quiet_threshold(),
+            vad_enabled: false,
+            silence_timeout_ms: default_silence_timeout_ms(),
+            max_paste_chars: 0,
+            translation_max_input_tokens: default_translation_max_input_tokens(),
+            hotkey_during_processing: HotkeyDuringProcessing::Ignore,
+            correction_stage: CorrectionStage::BeforeTranslation,
+            formatting_profile: FormattingProfile::Off,
+            formatting_rules: Vec::new(),
         }
     }
 }
@@ -96,3 +374,28 @@ pub fn save_config(config: &AppConfig) -> Result<()> {
     std::fs::write(&path, contents)?;
     Ok(())
 }
+
+/// Returns the base URL models are downloaded from. `WREN_MODEL_BASE_URL` takes
+/// precedence over `model_base_url`; an empty or invalid value falls back to Hugging Face.
+pub fn model_base_url() -> String {
+    let configured = std::env::var(MODEL_BASE_URL_ENV)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| load_config().model_base_url);
+    match normalize_base_url(&configured) {
+        Some(url) => url,
+        None => {
+            if !configured.trim().is_empty() {
+                log::warn!("Ignoring invalid model base URL: {}", configured);
+            }
+            DEFAULT_MODEL_BASE_URL.to_string()
+        }
+    }
+}
+
+/// Trims whitespace and trailing slashes, accepting only http(s) URLs.
+fn normalize_base_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(url).ok()?;
+    matches!(parsed.scheme(), "http" | "https").then(|| url.to_string())
+}
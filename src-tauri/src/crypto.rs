@@ -0,0 +1,174 @@
+//! Optional at-rest encryption for history.json and vocabulary.json.
+//!
+//! The key is derived from the user's passphrase with Argon2 and only ever
+//! kept in memory. While encryption is on but no passphrase has been entered
+//! yet, encrypted files read as errors and writes are refused, so a locked
+//! session can never overwrite encrypted data with plaintext.
+
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Prefix identifying an encrypted file, followed by the nonce and ciphertext.
+const MAGIC: &[u8] = b"WRENENC1";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+/// Known plaintext encrypted at setup so a passphrase can be checked on unlock.
+const VERIFIER_PLAINTEXT: &[u8] = b"wren";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SESSION_KEY: parking_lot::Mutex<Option<[u8; 32]>> = parking_lot::Mutex::new(None);
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+pub fn is_unlocked() -> bool {
+    SESSION_KEY.lock().is_some()
+}
+
+/// Marks encryption as on without a key, e.g. at startup before the passphrase prompt.
+pub fn set_locked() {
+    *SESSION_KEY.lock() = None;
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Turns encryption off and forgets the session key.
+pub fn disable() {
+    *SESSION_KEY.lock() = None;
+    ENABLED.store(false, Ordering::SeqCst);
+}
+
+/// Turns encryption on with a fresh salt for `passphrase`. Returns the salt and
+/// passphrase verifier (both hex) to persist in config.
+pub fn setup(passphrase: &str) -> Result<(String, String)> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let verifier = encrypt(&key, VERIFIER_PLAINTEXT)?;
+
+    *SESSION_KEY.lock() = Some(key);
+    ENABLED.store(true, Ordering::SeqCst);
+    Ok((to_hex(&salt), to_hex(&verifier)))
+}
+
+/// Derives the key for `passphrase` and keeps it for the session if it
+/// matches the stored verifier.
+pub fn unlock(passphrase: &str, salt_hex: &str, verifier_hex: &str) -> Result<()> {
+    let salt = from_hex(salt_hex).ok_or_else(|| anyhow!("Corrupt encryption salt"))?;
+    let verifier = from_hex(verifier_hex).ok_or_else(|| anyhow!("Corrupt encryption verifier"))?;
+    let key = derive_key(passphrase, &salt)?;
+    match decrypt(&key, &verifier) {
+        Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => {
+            *SESSION_KEY.lock() = Some(key);
+            ENABLED.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        _ => Err(anyhow!("Wrong passphrase")),
+    }
+}
+
+/// Reads a data file, decrypting it if it was written encrypted. Plaintext
+/// files are returned as-is so existing data keeps loading after encryption is
+/// turned on; they are encrypted on their next save.
+pub fn read_file(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    if !bytes.starts_with(MAGIC) {
+        return Ok(String::from_utf8(bytes)?);
+    }
+    let key = *SESSION_KEY.lock();
+    let key = key.ok_or_else(|| anyhow!("Data is locked — enter your passphrase"))?;
+    let plaintext = decrypt(&key, &bytes[MAGIC.len()..])?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+/// Writes a data file, encrypting it when encryption is on. Refuses to write
+/// while locked rather than falling back to plaintext.
+pub fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if !is_enabled() {
+        std::fs::write(path, contents)?;
+        return Ok(());
+    }
+    let key = *SESSION_KEY.lock();
+    let key = key.ok_or_else(|| anyhow!("Data is locked — enter your passphrase"))?;
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(encrypt(&key, contents.as_bytes())?);
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypts to `nonce || ciphertext`.
+fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < NONCE_LEN {
+        return Err(anyhow!("Encrypted data is truncated"));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Could not decrypt data — wrong passphrase or corrupt file"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = derive_key("correct horse", b"0123456789abcdef").unwrap();
+        let encrypted = encrypt(&key, b"hello").unwrap();
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let key = derive_key("correct horse", b"0123456789abcdef").unwrap();
+        let other = derive_key("battery staple", b"0123456789abcdef").unwrap();
+        let encrypted = encrypt(&key, b"hello").unwrap();
+        assert!(decrypt(&other, &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = [0u8, 1, 0xab, 0xff];
+        assert_eq!(from_hex(&to_hex(&bytes)).unwrap(), bytes);
+        assert!(from_hex("abc").is_none());
+        assert!(from_hex("zz").is_none());
+    }
+}
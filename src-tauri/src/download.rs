@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A model download fails if no data arrives for this long.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a download waiting on the network re-checks its cancel flag, so a
+/// stalled read never holds up shutdown.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// HTTP client for model downloads.
+pub fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .read_timeout(READ_TIMEOUT)
+        .build()?)
+}
+
+/// Streams the body of `response` into `tmp`, calling `on_chunk` with the size of
+/// each chunk written. If the download is cancelled, stalls, or fails, `tmp` is
+/// removed so no half-written file is left behind.
+pub async fn write_body<F>(
+    response: reqwest::Response,
+    tmp: &Path,
+    cancel: &AtomicBool,
+    mut on_chunk: F,
+) -> Result<()>
+where
+    F: FnMut(u64),
+{
+    let result = copy_body(response, tmp, cancel, &mut on_chunk).await;
+    if result.is_err() {
+        let _ = std::fs::remove_file(tmp);
+    }
+    result
+}
+
+async fn copy_body<F>(
+    response: reqwest::Response,
+    tmp: &Path,
+    cancel: &AtomicBool,
+    on_chunk: &mut F,
+) -> Result<()>
+where
+    F: FnMut(u64),
+{
+    let mut file = std::fs::File::create(tmp)?;
+    let mut stream = response.bytes_stream();
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(anyhow!("Download cancelled"));
+        }
+        let chunk = match tokio::time::timeout(CANCEL_POLL_INTERVAL, stream.next()).await {
+            Ok(Some(chunk)) => chunk?,
+            Ok(None) => return Ok(()),
+            // Still waiting on the network
+            Err(_) => continue,
+        };
+        file.write_all(&chunk)?;
+        on_chunk(chunk.len() as u64);
+    }
+}
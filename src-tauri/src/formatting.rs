@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+/// Which set of spoken formatting commands is applied to transcripts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FormattingProfile {
+    /// Transcripts are left as whisper wrote them.
+    #[default]
+    Off,
+    /// Spoken line and paragraph breaks only.
+    Prose,
+    /// Line breaks plus brackets, operators, and other symbols for dictating code.
+    Code,
+}
+
+/// A spoken phrase and the literal text it is replaced with. The output is
+/// inserted as-is with no spaces added around it, so spacing is part of the
+/// output (" = " vs "(").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormattingRule {
+    pub phrase: String,
+    pub output: String,
+}
+
+const PROSE_RULES: &[(&str, &str)] = &[("new line", "\n"), ("new paragraph", "\n\n")];
+
+const CODE_RULES: &[(&str, &str)] = &[
+    ("tab", "\t"),
+    ("open paren", "("),
+    ("close paren", ")"),
+    ("open bracket", "["),
+    ("close bracket", "]"),
+    ("open brace", "{"),
+    ("close brace", "}"),
+    ("less than", " < "),
+    ("greater than", " > "),
+    ("equals", " = "),
+    ("double equals", " == "),
+    ("not equals", " != "),
+    ("plus", " + "),
+    ("plus equals", " += "),
+    ("minus", " - "),
+    ("arrow", " -> "),
+    ("fat arrow", " => "),
+    ("comma", ", "),
+    ("semicolon", "; "),
+    ("colon", ": "),
+    ("double colon", "::"),
+    ("dot", "."),
+    ("underscore", "_"),
+    ("dash", "-"),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("star", "*"),
+    ("asterisk", "*"),
+    ("ampersand", "&"),
+    ("pipe", "|"),
+    ("hash", "#"),
+    ("at sign", "@"),
+    ("dollar sign", "$"),
+    ("percent", "%"),
+    ("bang", "!"),
+    ("question mark", "?"),
+    ("quote", "\""),
+    ("single quote", "'"),
+    ("backtick", "`"),
+    ("tilde", "~"),
+    ("caret", "^"),
+];
+
+/// A rule split into lowercase phrase words for matching.
+struct CompiledRule {
+    words: Vec<String>,
+    output: String,
+}
+
+/// Lowercases a transcript word and drops the punctuation whisper tends to add
+/// around spoken commands ("Paren," / "line.").
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':' | '!' | '?'))
+        .to_lowercase()
+}
+
+/// The built-in rules for `profile` with `custom` rules added on top; a custom
+/// rule replaces a built-in one with the same phrase. Longer phrases come first
+/// so "double equals" wins over "equals".
+fn compile(profile: FormattingProfile, custom: &[FormattingRule]) -> Vec<CompiledRule> {
+    let built_in: &[&[(&str, &str)]] = match profile {
+        FormattingProfile::Off => &[],
+        FormattingProfile::Prose => &[PROSE_RULES],
+        FormattingProfile::Code => &[PROSE_RULES, CODE_RULES],
+    };
+
+    let mut rules: Vec<CompiledRule> = Vec::new();
+    let pairs = built_in
+        .iter()
+        .flat_map(|rules| rules.iter().map(|(p, o)| (p.to_string(), o.to_string())))
+        .chain(custom.iter().map(|r| (r.phrase.clone(), r.output.clone())));
+    for (phrase, output) in pairs {
+        let words: Vec<String> = phrase.split_whitespace().map(normalize).collect();
+        if words.is_empty() {
+            continue;
+        }
+        match rules.iter_mut().find(|r| r.words == words) {
+            Some(existing) => existing.output = output,
+            None => rules.push(CompiledRule { words, output }),
+        }
+    }
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.words.len()));
+    rules
+}
+
+fn format_line(line: &str, rules: &[CompiledRule]) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let normalized: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let mut out = String::with_capacity(line.len());
+    // Words are space-separated, but never from a symbol; its output carries its own spacing
+    let mut after_symbol = true;
+    let mut i = 0;
+    while i < words.len() {
+        let matched = rules
+            .iter()
+            .find(|rule| normalized[i..].starts_with(&rule.words));
+        match matched {
+            Some(rule) => {
+                // Whisper often punctuates the word before a spoken symbol ("b, close paren")
+                if !after_symbol && rule.output.contains(|c: char| !c.is_whitespace()) {
+                    let kept = out.trim_end_matches([',', '.', ';', ':']).len();
+                    out.truncate(kept);
+                }
+                out.push_str(&rule.output);
+                after_symbol = true;
+                i += rule.words.len();
+            }
+            None => {
+                if !after_symbol {
+                    out.push(' ');
+                }
+                out.push_str(words[i]);
+                after_symbol = false;
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Collapses runs of spaces left where spaced symbols meet, and strips spaces
+/// at the ends of lines.
+fn tidy(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let mut tidied = String::with_capacity(line.len());
+            for c in line.trim_matches(' ').chars() {
+                if !(c == ' ' && tidied.ends_with(' ')) {
+                    tidied.push(c);
+                }
+            }
+            tidied
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replaces spoken formatting commands in `text` with the symbols and
+/// whitespace they stand for, using the rules of `profile` plus `custom`.
+pub fn apply_formatting(
+    text: &str,
+    profile: FormattingProfile,
+    custom: &[FormattingRule],
+) -> String {
+    if profile == FormattingProfile::Off {
+        return text.to_string();
+    }
+    let rules = compile(profile, custom);
+    let formatted = text
+        .split('\n')
+        .map(|line| format_line(line, &rules))
+        .collect::<Vec<_>>()
+        .join("\n");
+    tidy(&formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(text: &str) -> String {
+        apply_formatting(text, FormattingProfile::Code, &[])
+    }
+
+    #[test]
+    fn test_off_leaves_text_alone() {
+        let text = "print open paren x close paren";
+        assert_eq!(apply_formatting(text, FormattingProfile::Off, &[]), text);
+    }
+
+    #[test]
+    fn test_prose_line_breaks() {
+        assert_eq!(
+            apply_formatting(
+                "First line. New line. Second line. New paragraph. Third.",
+                FormattingProfile::Prose,
+                &[]
+            ),
+            "First line.\nSecond line.\n\nThird."
+        );
+        // Prose doesn't touch code words
+        assert_eq!(
+            apply_formatting("a comma b", FormattingProfile::Prose, &[]),
+            "a comma b"
+        );
+    }
+
+    #[test]
+    fn test_code_brackets_and_operators() {
+        assert_eq!(code("print open paren x close paren"), "print(x)");
+        assert_eq!(code("Let x equals 5 semicolon."), "Let x = 5;");
+        assert_eq!(code("if a double equals b"), "if a == b");
+        assert_eq!(code("Open paren, a, comma b. Close paren."), "(a, b)");
+        assert_eq!(
+            code("items open bracket i close bracket dot len"),
+            "items[i].len"
+        );
+        assert_eq!(
+            code("fn main open paren close paren open brace new line tab run close brace"),
+            "fn main(){\n\trun}"
+        );
+    }
+
+    #[test]
+    fn test_custom_rules_override_built_ins() {
+        let custom = vec![
+            FormattingRule {
+                phrase: "arrow".to_string(),
+                output: "->".to_string(),
+            },
+            FormattingRule {
+                phrase: "self dot".to_string(),
+                output: "self.".to_string(),
+            },
+        ];
+        assert_eq!(
+            apply_formatting("a arrow b", FormattingProfile::Code, &custom),
+            "a->b"
+        );
+        assert_eq!(
+            apply_formatting("Self dot name", FormattingProfile::Prose, &custom),
+            "self.name"
+        );
+    }
+}
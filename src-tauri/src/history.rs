@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 const MAX_HISTORY_ENTRIES: usize = 50;
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -10,6 +11,26 @@ pub struct HistoryEntry {
     pub text: String,
     pub timestamp_ms: u64,
     pub duration_ms: u64,
+    /// The transcript before translation, kept when `text` is an accepted translation.
+    #[serde(default)]
+    pub source_text: Option<String>,
+    /// Language whisper detected when the language was set to auto-detect.
+    #[serde(default)]
+    pub detected_language: Option<String>,
+}
+
+impl HistoryEntry {
+    /// The translated text, if this entry was translated.
+    pub fn translated_text(&self) -> Option<&str> {
+        self.source_text.as_ref().map(|_| self.text.as_str())
+    }
+
+    /// Source and translation together as "source / translation", if translated.
+    pub fn bilingual_text(&self) -> Option<String> {
+        self.source_text
+            .as_ref()
+            .map(|source| format!("{} / {}", source, self.text))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -17,6 +38,14 @@ pub struct TranscriptionHistory {
     pub entries: Vec<HistoryEntry>,
 }
 
+/// What `compact_history` removed from the history file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CompactStats {
+    pub kept: usize,
+    pub malformed_removed: usize,
+    pub over_cap_removed: usize,
+}
+
 fn history_path() -> PathBuf {
     let data_dir = dirs::data_dir().expect("Failed to get data directory");
     data_dir.join("com.wren.app").join("history.json")
@@ -27,7 +56,7 @@ pub fn load_history() -> TranscriptionHistory {
     if !path.exists() {
         return TranscriptionHistory::default();
     }
-    match std::fs::read_to_string(&path) {
+    match crate::crypto::read_file(&path) {
         Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
         Err(_) => TranscriptionHistory::default(),
     }
@@ -39,7 +68,7 @@ pub fn save_history(history: &TranscriptionHistory) -> Result<()> {
         std::fs::create_dir_all(parent)?;
     }
     let contents = serde_json::to_string_pretty(history)?;
-    std::fs::write(&path, contents)?;
+    crate::crypto::write_file(&path, &contents)?;
     Ok(())
 }
 
@@ -62,6 +91,47 @@ pub fn clear_history() -> Result<()> {
     save_history(&TranscriptionHistory::default())
 }
 
+/// Rewrites the history file keeping only entries that still parse, newest
+/// first and cut to the usual cap. Unlike `load_history`, one bad entry does
+/// not discard the whole file. Entries have no pinned flag, so the cap drops
+/// the oldest entries whatever they are.
+pub fn compact_history() -> Result<CompactStats> {
+    let path = history_path();
+    if !path.exists() {
+        return Ok(CompactStats::default());
+    }
+    let contents = crate::crypto::read_file(&path)?;
+    let (history, stats) = compact_contents(&contents)?;
+    save_history(&history)?;
+    Ok(stats)
+}
+
+fn compact_contents(contents: &str) -> Result<(TranscriptionHistory, CompactStats)> {
+    let raw: serde_json::Value = serde_json::from_str(contents)?;
+    let raw_entries = match raw.get("entries") {
+        Some(serde_json::Value::Array(entries)) => entries.clone(),
+        _ => anyhow::bail!("History file has no entries list"),
+    };
+
+    let total = raw_entries.len();
+    let mut entries: Vec<HistoryEntry> = raw_entries
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect();
+    let malformed_removed = total - entries.len();
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp_ms));
+    let over_cap_removed = entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+    entries.truncate(MAX_HISTORY_ENTRIES);
+
+    let stats = CompactStats {
+        kept: entries.len(),
+        malformed_removed,
+        over_cap_removed,
+    };
+    Ok((TranscriptionHistory { entries }, stats))
+}
+
 pub fn update_most_recent_text(new_text: String) -> Result<()> {
     let mut history = load_history();
     if let Some(entry) = history.entries.first_mut() {
@@ -69,3 +139,97 @@ pub fn update_most_recent_text(new_text: String) -> Result<()> {
     }
     save_history(&history)
 }
+
+/// Replaces the most recent entry's text with its accepted translation and keeps
+/// the transcript it was translated from.
+pub fn set_most_recent_translation(source_text: String, translated_text: String) -> Result<()> {
+    let mut history = load_history();
+    if let Some(entry) = history.entries.first_mut() {
+        entry.text = translated_text;
+        entry.source_text = Some(source_text);
+    }
+    save_history(&history)
+}
+
+pub fn find_entry(id: u64) -> Option<HistoryEntry> {
+    load_history().entries.into_iter().find(|e| e.id == id)
+}
+
+/// Formats epoch milliseconds as local time with a chrono/strftime `format`.
+/// An invalid format falls back to `DEFAULT_TIME_FORMAT` instead of failing.
+pub fn format_timestamp(timestamp_ms: u64, format: &str) -> String {
+    let Some(time) = chrono::DateTime::from_timestamp_millis(timestamp_ms as i64) else {
+        return String::new();
+    };
+    let time = time.with_timezone(&chrono::Local);
+
+    let valid = !chrono::format::StrftimeItems::new(format)
+        .any(|item| matches!(item, chrono::format::Item::Error));
+    let format = if valid { format } else { DEFAULT_TIME_FORMAT };
+    time.format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        let ms = 1_700_000_000_000;
+        let expected = chrono::DateTime::from_timestamp_millis(ms as i64)
+            .unwrap()
+            .with_timezone(&chrono::Local)
+            .format("%H:%M")
+            .to_string();
+        assert_eq!(format_timestamp(ms, "%H:%M"), expected);
+    }
+
+    #[test]
+    fn test_translated_entry_texts() {
+        let mut entry = HistoryEntry {
+            id: 1,
+            text: "Hello".to_string(),
+            timestamp_ms: 1,
+            duration_ms: 1000,
+            source_text: None,
+            detected_language: None,
+        };
+        assert_eq!(entry.translated_text(), None);
+        assert_eq!(entry.bilingual_text(), None);
+
+        entry.source_text = Some("Hola".to_string());
+        assert_eq!(entry.translated_text(), Some("Hello"));
+        assert_eq!(entry.bilingual_text().as_deref(), Some("Hola / Hello"));
+    }
+
+    #[test]
+    fn test_compact_drops_malformed_and_sorts() {
+        let contents = r#"{"entries": [
+            {"id": 1, "text": "older", "timestamp_ms": 100, "duration_ms": 500},
+            {"id": 2, "text": 42, "timestamp_ms": 150},
+            {"id": 3, "text": "newer", "timestamp_ms": 200, "duration_ms": 700}
+        ]}"#;
+        let (history, stats) = compact_contents(contents).unwrap();
+        let ids: Vec<u64> = history.entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(
+            stats,
+            CompactStats {
+                kept: 2,
+                malformed_removed: 1,
+                over_cap_removed: 0,
+            }
+        );
+
+        assert!(compact_contents("not json").is_err());
+    }
+
+    #[test]
+    fn test_invalid_format_falls_back_to_default() {
+        let ms = 1_700_000_000_000;
+        assert_eq!(
+            format_timestamp(ms, "%Q broken"),
+            format_timestamp(ms, DEFAULT_TIME_FORMAT)
+        );
+    }
+}
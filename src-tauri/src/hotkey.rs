@@ -0,0 +1,37 @@
+use tauri_plugin_global_shortcut::Shortcut;
+
+/// Shortcuts macOS (or Wren itself) already claims. Registering one of these either
+/// fails silently or never fires, so they're rejected up front.
+const RESERVED_SHORTCUTS: [(&str, &str); 12] = [
+    ("cmd+space", "Spotlight"),
+    ("cmd+alt+space", "Finder search"),
+    ("ctrl+space", "input source switching"),
+    ("ctrl+alt+space", "input source switching"),
+    ("cmd+tab", "the app switcher"),
+    ("cmd+shift+3", "screenshots"),
+    ("cmd+shift+4", "screenshots"),
+    ("cmd+shift+5", "screenshots"),
+    ("cmd+q", "quitting apps"),
+    ("ctrl+up", "Mission Control"),
+    ("cmd+alt+,", "Wren's Settings shortcut"),
+    ("cmd+alt+h", "Wren's History shortcut"),
+];
+
+/// Checks that `shortcut` parses as an accelerator and isn't a known reserved combo.
+pub fn validate(shortcut: &str) -> Result<Shortcut, String> {
+    let parsed: Shortcut = shortcut
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid hotkey '{}': {}", shortcut, e))?;
+
+    for (reserved, owner) in RESERVED_SHORTCUTS {
+        if reserved.parse::<Shortcut>().ok() == Some(parsed) {
+            return Err(format!(
+                "'{}' is reserved for {} and won't reach Wren — pick another hotkey",
+                shortcut, owner
+            ));
+        }
+    }
+
+    Ok(parsed)
+}
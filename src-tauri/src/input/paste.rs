@@ -4,10 +4,11 @@ use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 use std::thread;
 use std::time::Duration;
 
-/// Checks if the currently focused UI element is a text input field
-/// using the macOS Accessibility API.
+/// Reads string attributes (e.g. "AXRole", "AXSubrole") of the currently focused
+/// UI element using the macOS Accessibility API. Each entry is `None` if the
+/// attribute is missing or nothing is focused.
 #[cfg(target_os = "macos")]
-fn is_text_field_focused() -> bool {
+fn focused_element_attributes<const N: usize>(names: [&str; N]) -> [Option<String>; N] {
     use std::ffi::{c_char, c_void, CString};
     use std::ptr;
 
@@ -78,7 +79,7 @@ fn is_text_field_focused() -> bool {
     unsafe {
         let system_wide = AXUIElementCreateSystemWide();
         if system_wide.is_null() {
-            return false;
+            return std::array::from_fn(|_| None);
         }
 
         let focused_attr = make_cfstring("AXFocusedUIElement");
@@ -88,42 +89,224 @@ fn is_text_field_focused() -> bool {
         CFRelease(focused_attr as CFTypeRef);
 
         if err != K_AX_ERROR_SUCCESS || focused.is_null() {
-            return false;
+            return std::array::from_fn(|_| None);
         }
 
-        let role_attr = make_cfstring("AXRole");
-        let mut role: CFTypeRef = ptr::null();
-        let err = AXUIElementCopyAttributeValue(focused as AXUIElementRef, role_attr, &mut role);
+        let values = names.map(|name| {
+            let attr = make_cfstring(name);
+            let mut value: CFTypeRef = ptr::null();
+            let err = AXUIElementCopyAttributeValue(focused as AXUIElementRef, attr, &mut value);
+            CFRelease(attr as CFTypeRef);
+
+            if err != K_AX_ERROR_SUCCESS || value.is_null() {
+                return None;
+            }
+            let value_string = cfstring_to_string(value as CFStringRef);
+            CFRelease(value);
+            value_string
+        });
         CFRelease(focused);
-        CFRelease(role_attr as CFTypeRef);
 
-        if err != K_AX_ERROR_SUCCESS || role.is_null() {
-            return false;
+        values
+    }
+}
+
+/// Whether an accessibility role accepts typed text.
+#[cfg(target_os = "macos")]
+fn is_text_input_role(role: &str) -> bool {
+    matches!(
+        role,
+        "AXTextField" | "AXTextArea" | "AXComboBox" | "AXSearchField" | "AXWebArea"
+    )
+}
+
+/// Whether a role/subrole pair describes a password field. Native secure fields
+/// report role "AXTextField" with subrole "AXSecureTextField"; some toolkits put
+/// it in the role itself.
+#[cfg(target_os = "macos")]
+fn is_secure_text_role(role: Option<&str>, subrole: Option<&str>) -> bool {
+    role == Some("AXSecureTextField") || subrole == Some("AXSecureTextField")
+}
+
+/// Checks if the currently focused UI element is a text input field
+/// using the macOS Accessibility API.
+#[cfg(target_os = "macos")]
+fn is_text_field_focused() -> bool {
+    let [role] = focused_element_attributes(["AXRole"]);
+    role.as_deref().is_some_and(is_text_input_role)
+}
+
+/// Checks if the currently focused UI element is a password field.
+#[cfg(target_os = "macos")]
+fn is_secure_field_focused() -> bool {
+    let [role, subrole] = focused_element_attributes(["AXRole", "AXSubrole"]);
+    is_secure_text_role(role.as_deref(), subrole.as_deref())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_text_field_focused() -> bool {
+    true
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_secure_field_focused() -> bool {
+    false
+}
+
+/// Time given to a target app to come forward and take focus before pasting.
+const ACTIVATE_SETTLE_MS: u64 = 150;
+
+/// Converts an `NSString` to a Rust string; `None` for nil.
+#[cfg(target_os = "macos")]
+unsafe fn nsstring_to_string(string: cocoa::base::id) -> Option<String> {
+    use objc::{msg_send, sel, sel_impl};
+
+    if string == cocoa::base::nil {
+        return None;
+    }
+    let ptr: *const std::ffi::c_char = msg_send![string, UTF8String];
+    (!ptr.is_null()).then(|| std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+/// Bundle identifier of the frontmost application (e.g. "com.apple.Notes"),
+/// read from `NSWorkspace`.
+#[cfg(target_os = "macos")]
+pub fn frontmost_app_bundle_id() -> Option<String> {
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: id = msg_send![workspace, frontmostApplication];
+        if app == nil {
+            return None;
         }
+        let bundle_id: id = msg_send![app, bundleIdentifier];
+        nsstring_to_string(bundle_id)
+    }
+}
 
-        let role_string = cfstring_to_string(role as CFStringRef);
-        CFRelease(role);
+#[cfg(not(target_os = "macos"))]
+pub fn frontmost_app_bundle_id() -> Option<String> {
+    None
+}
 
-        matches!(
-            role_string.as_deref(),
-            Some("AXTextField" | "AXTextArea" | "AXComboBox" | "AXSearchField" | "AXWebArea")
-        )
+/// Brings the running app with `bundle_id` to the front. Returns false if no
+/// such app is running or it refused to activate.
+#[cfg(target_os = "macos")]
+fn activate_app(bundle_id: &str) -> bool {
+    use cocoa::base::{id, nil, BOOL, YES};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    // NSApplicationActivateIgnoringOtherApps
+    const ACTIVATE_IGNORING_OTHER_APPS: usize = 1 << 1;
+
+    unsafe {
+        let bundle_id = NSString::alloc(nil).init_str(bundle_id);
+        let apps: id = msg_send![
+            class!(NSRunningApplication),
+            runningApplicationsWithBundleIdentifier: bundle_id
+        ];
+        let _: () = msg_send![bundle_id, release];
+        let app: id = msg_send![apps, firstObject];
+        if app == nil {
+            return false;
+        }
+        let activated: BOOL = msg_send![app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+        activated == YES
     }
 }
 
 #[cfg(not(target_os = "macos"))]
-fn is_text_field_focused() -> bool {
+fn activate_app(_bundle_id: &str) -> bool {
+    false
+}
+
+/// Hides Wren if it is the active app (e.g. a paste was started from its
+/// settings window), so the app the user was in before takes focus back and
+/// receives the paste. Returns whether Wren was hidden; call `unhide_wren`
+/// afterwards to bring its windows back without taking focus.
+#[cfg(target_os = "macos")]
+pub fn yield_focus() -> bool {
+    use cocoa::base::{id, nil, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let current: id = msg_send![class!(NSRunningApplication), currentApplication];
+        let active: BOOL = msg_send![current, isActive];
+        if active != YES {
+            return false;
+        }
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, hide: nil];
+    }
+    thread::sleep(Duration::from_millis(ACTIVATE_SETTLE_MS));
     true
 }
 
+#[cfg(not(target_os = "macos"))]
+pub fn yield_focus() -> bool {
+    false
+}
+
+/// Shows Wren's windows again after `yield_focus`, leaving focus where it is.
+#[cfg(target_os = "macos")]
+pub fn unhide_wren() {
+    use cocoa::base::id;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, unhideWithoutActivation];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn unhide_wren() {}
+
+/// macOS virtual keycode for the physical V key (kVK_ANSI_V). Sending the raw keycode
+/// keeps Cmd+V working on layouts where the 'v' character lives on a different key.
+#[cfg(target_os = "macos")]
+const PASTE_KEYCODE: u16 = 9;
+
+/// Presses the paste key while the Command modifier is held.
+#[cfg(target_os = "macos")]
+fn click_paste_key(enigo: &mut Enigo) -> Result<()> {
+    enigo
+        .raw(PASTE_KEYCODE, Direction::Click)
+        .map_err(|e| anyhow::anyhow!("Failed to click paste key: {}", e))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn click_paste_key(enigo: &mut Enigo) -> Result<()> {
+    enigo
+        .key(Key::Unicode('v'), Direction::Click)
+        .map_err(|e| anyhow::anyhow!("Failed to click 'v' key: {}", e))
+}
+
 /// Pastes transcribed text. When smart_paste is true, checks if a text field
-/// is focused first — auto-pastes if so, otherwise saves to clipboard.
+/// is focused first — auto-pastes if so, otherwise saves to clipboard. Password
+/// fields never receive an auto-paste in smart mode.
 /// When smart_paste is false, always attempts immediate paste.
-pub fn paste_text(text: &str, smart_paste: bool) -> Result<()> {
+/// With a `target_app` bundle id, that app is brought to the front first so the
+/// text lands there even if focus moved; if it isn't running, the focused app is used.
+pub fn paste_text(text: &str, smart_paste: bool, target_app: Option<&str>) -> Result<()> {
     let mut clipboard =
         Clipboard::new().map_err(|e| anyhow::anyhow!("Failed to access clipboard: {}", e))?;
 
-    let should_auto_paste = !smart_paste || is_text_field_focused();
+    if let Some(bundle_id) = target_app {
+        if activate_app(bundle_id) {
+            thread::sleep(Duration::from_millis(ACTIVATE_SETTLE_MS));
+        } else {
+            log::warn!(
+                "Target app {} isn't running; pasting into the focused app",
+                bundle_id
+            );
+        }
+    }
+
+    let should_auto_paste = !smart_paste || (is_text_field_focused() && !is_secure_field_focused());
 
     if should_auto_paste {
         // Text field is focused — auto-paste and restore clipboard
@@ -142,9 +325,7 @@ pub fn paste_text(text: &str, smart_paste: bool) -> Result<()> {
         enigo
             .key(Key::Meta, Direction::Press)
             .map_err(|e| anyhow::anyhow!("Failed to press Meta key: {}", e))?;
-        enigo
-            .key(Key::Unicode('v'), Direction::Click)
-            .map_err(|e| anyhow::anyhow!("Failed to click 'v' key: {}", e))?;
+        click_paste_key(&mut enigo)?;
         enigo
             .key(Key::Meta, Direction::Release)
             .map_err(|e| anyhow::anyhow!("Failed to release Meta key: {}", e))?;
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Expands a path template: strftime tokens such as `%Y-%m-%d` become the date
+/// of `now`, and a leading `~/` becomes the home directory. A template with an
+/// invalid token is used literally.
+pub fn expand_path(template: &str, now: DateTime<Local>) -> PathBuf {
+    let valid = !chrono::format::StrftimeItems::new(template)
+        .any(|item| matches!(item, chrono::format::Item::Error));
+    let expanded = if valid {
+        now.format(template).to_string()
+    } else {
+        template.to_string()
+    };
+
+    match (expanded.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(expanded),
+    }
+}
+
+/// Formats one transcription as a Markdown section with a time heading.
+fn format_entry(text: &str, now: DateTime<Local>) -> String {
+    format!("## {}\n\n{}\n\n", now.format("%H:%M:%S"), text.trim())
+}
+
+/// Appends `text` to the file the template expands to right now, creating the
+/// file and its parent directories if needed.
+pub fn append_entry(template: &str, text: &str) -> Result<PathBuf> {
+    let now = Local::now();
+    let path = expand_path(template, now);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(format_entry(text, now).as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 9, 12, 5, 30).unwrap()
+    }
+
+    #[test]
+    fn test_expand_date_tokens() {
+        assert_eq!(
+            expand_path("/notes/%Y-%m-%d.md", noon()),
+            PathBuf::from("/notes/2024-03-09.md")
+        );
+    }
+
+    #[test]
+    fn test_invalid_template_used_literally() {
+        assert_eq!(
+            expand_path("/notes/%Q.md", noon()),
+            PathBuf::from("/notes/%Q.md")
+        );
+    }
+
+    #[test]
+    fn test_format_entry_has_time_heading() {
+        assert_eq!(
+            format_entry("  hello world \n", noon()),
+            "## 12:05:30\n\nhello world\n\n"
+        );
+    }
+}
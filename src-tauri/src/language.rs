@@ -0,0 +1,91 @@
+/// Normalizes a language code to BCP-47 casing, e.g. "pt-br" to "pt-BR" or
+/// "zh_hant_tw" to "zh-Hant-TW". Accepts a 2–3 letter language with an optional
+/// 4-letter script and an optional 2-letter (or 3-digit) region. "auto" is kept
+/// as is; anything else returns `None`.
+pub fn normalize(code: &str) -> Option<String> {
+    let code = code.trim();
+    if code.eq_ignore_ascii_case("auto") {
+        return Some("auto".to_string());
+    }
+
+    let mut subtags = code.split(['-', '_']);
+    let language = subtags.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut normalized = language.to_ascii_lowercase();
+
+    let mut rest = subtags.peekable();
+    if let Some(script) = rest.next_if(|s| s.len() == 4) {
+        if !script.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        normalized.push('-');
+        normalized.push_str(&script[..1].to_ascii_uppercase());
+        normalized.push_str(&script[1..].to_ascii_lowercase());
+    }
+    if let Some(region) = rest.next() {
+        let letters = region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic());
+        let digits = region.len() == 3 && region.chars().all(|c| c.is_ascii_digit());
+        if !letters && !digits {
+            return None;
+        }
+        normalized.push('-');
+        normalized.push_str(&region.to_ascii_uppercase());
+    }
+    if rest.next().is_some() {
+        return None;
+    }
+    Some(normalized)
+}
+
+/// The language without script or region, e.g. "pt" for "pt-BR".
+pub fn base(code: &str) -> &str {
+    code.split('-').next().unwrap_or(code)
+}
+
+/// Whether the code carries the given script or region subtag, e.g. "TW" in "zh-TW".
+pub fn has_subtag(code: &str, subtag: &str) -> bool {
+    code.split('-').skip(1).any(|part| part == subtag)
+}
+
+/// The code whisper expects: the base language, or `None` to auto-detect.
+pub fn whisper_language(code: &str) -> Option<String> {
+    if code == "auto" {
+        None
+    } else {
+        Some(base(code).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_case_and_separators() {
+        assert_eq!(normalize("pt-br").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize("zh_hant_tw").as_deref(), Some("zh-Hant-TW"));
+        assert_eq!(normalize("EN").as_deref(), Some("en"));
+        assert_eq!(normalize("es-419").as_deref(), Some("es-419"));
+        assert_eq!(normalize("Auto").as_deref(), Some("auto"));
+    }
+
+    #[test]
+    fn test_rejects_malformed_codes() {
+        assert_eq!(normalize(""), None);
+        assert_eq!(normalize("english"), None);
+        assert_eq!(normalize("pt-BRA"), None);
+        assert_eq!(normalize("pt-BR-x"), None);
+        assert_eq!(normalize("p1"), None);
+    }
+
+    #[test]
+    fn test_whisper_gets_base_language() {
+        assert_eq!(whisper_language("pt-BR").as_deref(), Some("pt"));
+        assert_eq!(whisper_language("auto"), None);
+        assert_eq!(base("zh-Hant-TW"), "zh");
+        assert!(has_subtag("zh-Hant-TW", "TW"));
+        assert!(!has_subtag("zh", "zh"));
+    }
+}
@@ -1,21 +1,36 @@
+mod api;
+mod append;
 mod audio;
 mod config;
+mod crypto;
+mod download;
+mod formatting;
 mod history;
+mod hotkey;
 mod input;
+mod journal;
+mod language;
+mod metrics;
+mod overlay;
+mod quiet_hours;
 mod state;
 mod transcription;
 mod translation;
 mod tray;
+mod truncate;
 mod vocabulary;
 
 use serde::Serialize;
-use state::{DictationState, SharedState, StatePayload};
+use state::{CorrectionStage, DictationState, HotkeyDuringProcessing, SharedState, StatePayload};
 use std::ffi::c_void;
-use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
-use transcription::whisper::{TranscriptionRequest, TranscriptionResponse};
+use tauri_plugin_notification::NotificationExt;
+use transcription::whisper::{
+    TranscriptionRequest, TranscriptionResponse, WhisperSampling, WhisperThresholds,
+};
 use translation::engine::{TranslationJob, TranslationRequest, TranslationResponse};
 
 /// Makes the overlay window non-activating so it doesn't steal focus from the current app.
@@ -57,6 +72,25 @@ static PREVIEW_TAP: parking_lot::Mutex<Option<TapState>> = parking_lot::Mutex::n
 #[cfg(target_os = "macos")]
 static TAP_PORT: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
 
+/// Keys the preview tap can intercept: (macOS virtual keycode, name emitted to the frontend).
+/// The number keys pick among a correction's candidates.
+const PREVIEW_KEYS: [(u16, &str); 6] = [
+    (36, "enter"),
+    (53, "escape"),
+    (18, "1"),
+    (19, "2"),
+    (20, "3"),
+    (21, "4"),
+];
+
+/// Most correction candidates reachable with the number keys.
+#[cfg(target_os = "macos")]
+const CANDIDATE_KEYS: usize = 4;
+
+/// Bitmask over `PREVIEW_KEYS` of the keys to swallow, set when the tap is installed.
+#[cfg(target_os = "macos")]
+static INTERCEPTED_KEYS: AtomicU8 = AtomicU8::new(0);
+
 #[cfg(target_os = "macos")]
 extern "C" {
     fn CGEventTapCreate(
@@ -81,7 +115,7 @@ extern "C" {
     static kCFRunLoopCommonModes: *const c_void;
 }
 
-/// CGEventTap callback: suppresses Enter/Escape and emits Tauri events.
+/// CGEventTap callback: suppresses the configured preview keys and emits Tauri events.
 #[cfg(target_os = "macos")]
 extern "C" fn preview_event_tap_callback(
     _proxy: *mut c_void,
@@ -107,25 +141,37 @@ extern "C" fn preview_event_tap_callback(
 
     // kCGKeyboardEventKeycode = 9
     let key_code = unsafe { CGEventGetIntegerValueField(event, 9) } as u16;
-    match key_code {
-        36 | 53 => {
+    let intercepted = INTERCEPTED_KEYS.load(Ordering::Relaxed);
+    match PREVIEW_KEYS.iter().position(|&(code, _)| code == key_code) {
+        Some(index) if intercepted & (1 << index) != 0 => {
             let app_handle = unsafe { &*(user_info as *const tauri::AppHandle) };
-            let key = if key_code == 36 { "enter" } else { "escape" };
-            let _ = app_handle.emit("preview-key-pressed", key);
+            let _ = app_handle.emit("preview-key-pressed", PREVIEW_KEYS[index].1);
             std::ptr::null_mut() // Suppress the event
         }
         _ => event,
     }
 }
 
-/// Installs a CGEventTap that intercepts Enter/Escape while a preview is showing.
+/// Installs a CGEventTap that intercepts Enter/Escape (and the candidate number
+/// keys) while a preview is showing.
 /// Unlike the previous NSEvent global monitor, a CGEventTap suppresses the key
 /// events so they don't reach the underlying application (preventing unwanted
 /// newlines, form submissions, or focus loss).
+/// Only the keys in `mask` (a bitmask over `PREVIEW_KEYS`) are swallowed; with
+/// none, no tap is installed.
 #[cfg(target_os = "macos")]
-fn install_preview_key_monitor(app_handle: tauri::AppHandle) {
+fn install_preview_key_monitor(app_handle: tauri::AppHandle, mask: u8) {
+    // Recording re-emits its state every tick; keep a tap that already does the job
+    if PREVIEW_TAP.lock().is_some() && INTERCEPTED_KEYS.load(Ordering::Relaxed) == mask {
+        return;
+    }
     remove_preview_key_monitor();
 
+    if mask == 0 {
+        return;
+    }
+    INTERCEPTED_KEYS.store(mask, Ordering::Relaxed);
+
     let boxed = Box::new(app_handle);
     let user_info = Box::into_raw(boxed) as *mut c_void;
 
@@ -192,6 +238,31 @@ fn remove_preview_key_monitor() {
     }
 }
 
+/// Converts preview key names into a bitmask over `PREVIEW_KEYS`.
+#[cfg(target_os = "macos")]
+fn preview_key_mask(keys: &[String]) -> u8 {
+    PREVIEW_KEYS
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, name))| keys.iter().any(|key| key == name))
+        .fold(0, |mask, (index, _)| mask | (1 << index))
+}
+
+/// Bitmask over `PREVIEW_KEYS` of the number keys that choose among the
+/// candidates of the first correction offering any.
+#[cfg(target_os = "macos")]
+fn candidate_key_mask(corrections: &[vocabulary::CorrectionApplied]) -> u8 {
+    let count = corrections
+        .iter()
+        .map(|c| c.candidates.len())
+        .find(|&count| count > 1)
+        .unwrap_or(0);
+    let keys: Vec<String> = (1..=count.min(CANDIDATE_KEYS))
+        .map(|n| n.to_string())
+        .collect();
+    preview_key_mask(&keys)
+}
+
 /// Wrapper to store the transcription channel sender as managed state.
 pub struct TranscriptionSender(pub std::sync::Mutex<std::sync::mpsc::Sender<TranscriptionRequest>>);
 
@@ -200,9 +271,16 @@ pub struct TranscriptionReceiver(
     pub std::sync::Mutex<std::sync::mpsc::Receiver<TranscriptionResponse>>,
 );
 
+/// Id of the dictation whose final transcription should stop; shared with the transcription thread.
+pub struct TranscriptionAbort(pub Arc<AtomicU64>);
+
 /// Wrapper to store an active AudioCapture instance during recording.
 pub struct ActiveCapture(pub std::sync::Mutex<Option<audio::capture::AudioCapture>>);
 
+/// The last finished recording at the device's native rate, kept so it can be drawn
+/// (or saved at full quality) after it stops.
+pub struct LastRecording(pub std::sync::Mutex<audio::capture::RawAudio>);
+
 /// Signals the streaming partial transcription loop to stop.
 pub struct StreamingActive(pub Arc<AtomicBool>);
 
@@ -220,23 +298,470 @@ pub struct TranslationReceiver(
 /// Wrapper for the partial translation results channel.
 pub struct PartialTranslationReceiver(pub std::sync::Mutex<std::sync::mpsc::Receiver<String>>);
 
+/// Signals in-flight model downloads to stop and clean up their partial files.
+pub struct DownloadCancel(pub Arc<AtomicBool>);
+
+/// Bumped on every state change so a pending processing watchdog can tell it is stale.
+pub struct ProcessingWatchdog(pub AtomicU64);
+
+/// Join handles for the transcription and translation worker threads.
+pub struct WorkerThreads(pub std::sync::Mutex<Vec<std::thread::JoinHandle<()>>>);
+
+/// Stop flag and thread handle for the running local API server, if any.
+pub struct LocalApiServer(
+    pub std::sync::Mutex<Option<(Arc<AtomicBool>, std::thread::JoinHandle<()>)>>,
+);
+
+/// Frame senders for each connected local API stream client.
+pub struct StreamSubscribers(pub parking_lot::Mutex<Vec<std::sync::mpsc::Sender<String>>>);
+
 /// Emits the current dictation state to the frontend via a 'dictation-state' event.
-/// Also manages the global key monitor for preview states.
+/// Also manages the global key monitor for preview states (and, with
+/// `guard_enter_while_recording`, for recording) and the processing watchdog.
 fn emit_state(app_handle: &tauri::AppHandle, dictation_state: &DictationState) {
     #[cfg(target_os = "macos")]
-    match dictation_state {
-        DictationState::CorrectionPreview { .. } | DictationState::TranslationPreview { .. } => {
-            install_preview_key_monitor(app_handle.clone());
-        }
-        _ => {
+    {
+        let mask = {
+            let shared_state = app_handle.state::<SharedState>();
+            let state = shared_state.lock();
+            match dictation_state {
+                DictationState::CorrectionPreview { corrections, .. } => {
+                    preview_key_mask(&state.intercepted_preview_keys)
+                        | candidate_key_mask(corrections)
+                }
+                DictationState::TranslationPreview { .. } => {
+                    preview_key_mask(&state.intercepted_preview_keys)
+                }
+                // Keep Enter from submitting a chat message before the dictation is pasted
+                DictationState::Recording { .. }
+                | DictationState::Processing
+                | DictationState::Translating
+                    if state.guard_enter_while_recording =>
+                {
+                    preview_key_mask(&["enter".to_string()])
+                }
+                _ => 0,
+            }
+        };
+        if mask == 0 {
             remove_preview_key_monitor();
+        } else {
+            install_preview_key_monitor(app_handle.clone(), mask);
         }
     }
 
+    // Any transition cancels the pending watchdog; entering a busy state arms a new one
+    let generation = app_handle
+        .state::<ProcessingWatchdog>()
+        .0
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    if matches!(
+        dictation_state,
+        DictationState::Processing | DictationState::Translating
+    ) {
+        spawn_processing_watchdog(app_handle.clone(), generation);
+    }
+
     let payload = StatePayload {
         state: dictation_state.clone(),
     };
     let _ = app_handle.emit("dictation-state", payload);
+    api::publish(app_handle, api::state_frame(dictation_state));
+}
+
+/// Forces an Error state if Processing/Translating is still showing after
+/// `processing_timeout_ms`, so a missed branch can't leave the overlay spinning forever.
+fn spawn_processing_watchdog(app_handle: tauri::AppHandle, generation: u64) {
+    std::thread::spawn(move || {
+        let timeout_ms = app_handle
+            .state::<SharedState>()
+            .lock()
+            .processing_timeout_ms;
+        if timeout_ms == 0 {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(timeout_ms));
+
+        let stalled = app_handle
+            .state::<ProcessingWatchdog>()
+            .0
+            .load(Ordering::SeqCst)
+            == generation
+            && matches!(
+                app_handle.state::<SharedState>().lock().dictation_state,
+                DictationState::Processing | DictationState::Translating
+            );
+        if stalled {
+            log::error!("Processing stalled for {}ms; giving up", timeout_ms);
+            set_error_state(&app_handle, "Processing stalled — try again".to_string());
+        }
+    });
+}
+
+/// Resolves the `overlay_url` setting to a page the overlay can load: an existing
+/// local file (plain path or `file://` URL) or an https URL. Anything else,
+/// including plain http, returns `None` so the bundled overlay is kept.
+fn custom_overlay_url(setting: &str) -> Option<tauri::Url> {
+    let setting = setting.trim();
+    if let Ok(url) = tauri::Url::parse(setting) {
+        match url.scheme() {
+            "https" => return Some(url),
+            // Anyone on the network path could rewrite the page and its event access
+            "http" => return None,
+            "file" => {
+                let is_file = url.to_file_path().is_ok_and(|path| path.is_file());
+                return is_file.then_some(url);
+            }
+            // Not a URL we can load; it may still be a Windows drive path
+            _ => {}
+        }
+    }
+
+    let path = std::path::Path::new(setting).canonicalize().ok()?;
+    if !path.is_file() {
+        return None;
+    }
+    tauri::Url::from_file_path(path).ok()
+}
+
+/// Capability letting a custom overlay page listen to Wren's events and nothing
+/// else. Pages outside the bundle get no IPC access unless their URL is in a
+/// capability's remote scope: here any page on the configured https origin, or
+/// exactly the configured local file.
+fn custom_overlay_capability(url: &tauri::Url) -> tauri::ipc::CapabilityBuilder {
+    let pattern = match url.scheme() {
+        "file" => url.to_string(),
+        _ => format!("{}/*", url.origin().ascii_serialization()),
+    };
+    tauri::ipc::CapabilityBuilder::new("custom-overlay")
+        .remote(pattern)
+        .window("overlay")
+        .permission("core:event:allow-listen")
+        .permission("core:event:allow-unlisten")
+}
+
+/// Shows the overlay window without focus, unless the overlay is turned off in settings.
+fn show_overlay(app_handle: &tauri::AppHandle) {
+    let enabled = app_handle.state::<SharedState>().lock().show_overlay;
+    if !enabled {
+        return;
+    }
+    if let Some(window) = app_handle.get_webview_window("overlay") {
+        let _ = window.show();
+    }
+}
+
+/// Hides the overlay window. Safe to call when the overlay is turned off.
+fn hide_overlay(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("overlay") {
+        let _ = window.hide();
+    }
+}
+
+/// Moves the state machine to Error and notifies the frontend.
+/// If `error_auto_dismiss_ms` is set, the error is dismissed after that long
+/// unless it has already been replaced by another state.
+fn set_error_state(app_handle: &tauri::AppHandle, message: String) {
+    let error_state = DictationState::Error {
+        message: message.clone(),
+    };
+    let (generation, auto_dismiss_ms, show_overlay) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.dictation_state = error_state.clone();
+        state.error_generation += 1;
+        // The dictation is over; a late result or queued recording shouldn't follow the error
+        state.active_dictation = None;
+        state.queued_recording = false;
+        (
+            state.error_generation,
+            state.error_auto_dismiss_ms,
+            state.show_overlay,
+        )
+    };
+    emit_state(app_handle, &error_state);
+
+    // Without the overlay the Error state has nowhere to show, so surface it as a notification
+    if !show_overlay {
+        show_notification(app_handle, "Wren error", &message);
+    }
+
+    if auto_dismiss_ms > 0 {
+        let app_handle = app_handle.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(auto_dismiss_ms));
+            // Leave it alone if the user already dismissed it, started a new
+            // recording, or a newer error replaced it
+            let still_showing = {
+                let shared_state = app_handle.state::<SharedState>();
+                let state = shared_state.lock();
+                matches!(state.dictation_state, DictationState::Error { .. })
+                    && state.error_generation == generation
+            };
+            if still_showing {
+                reset_to_idle(&app_handle);
+            }
+        });
+    }
+}
+
+/// Returns to Idle and hides the overlay. Also ends any `transcribe_to_result` session.
+fn reset_to_idle(app_handle: &tauri::AppHandle) {
+    let (had_session_language, queued_recording) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.dictation_state = DictationState::Idle;
+        state.result_only = false;
+        state.active_dictation = None;
+        let had_quick_english = restore_quick_english(&mut state);
+        (
+            state.session_language.take().is_some() || had_quick_english,
+            std::mem::take(&mut state.queued_recording),
+        )
+    };
+    emit_state(app_handle, &DictationState::Idle);
+    hide_overlay(app_handle);
+
+    // A one-off session language (or quick English) only lasts for a single recording
+    if had_session_language {
+        sync_whisper_language(app_handle);
+        sync_translation_languages(app_handle);
+    }
+
+    if queued_recording {
+        log::info!("Starting queued recording");
+        toggle_recording(app_handle);
+    }
+}
+
+/// Whether `dictation_id` is still the dictation whose result is wanted.
+fn dictation_is_active(app_handle: &tauri::AppHandle, dictation_id: u64) -> bool {
+    app_handle.state::<SharedState>().lock().active_dictation == Some(dictation_id)
+}
+
+/// Longest transcript excerpt shown in a completion notification.
+const NOTIFICATION_PREVIEW_CHARS: usize = 60;
+
+/// Posts a system notification when `notify_on_complete` is on and none of Wren's
+/// windows has focus, since the non-activating overlay is easy to miss.
+fn notify_if_backgrounded(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    if !app_handle.state::<SharedState>().lock().notify_on_complete {
+        return;
+    }
+    let focused = app_handle
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    if focused {
+        return;
+    }
+    show_notification(app_handle, title, body);
+}
+
+/// Posts a system notification, shortening `body` to a preview.
+fn show_notification(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    let mut preview: String = body.chars().take(NOTIFICATION_PREVIEW_CHARS).collect();
+    if body.chars().count() > NOTIFICATION_PREVIEW_CHARS {
+        preview.push('…');
+    }
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(preview)
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// Appends a metrics line when `metrics_logging` is on. The write happens on its
+/// own thread so a slow disk never delays the paste.
+fn record_metrics(app_handle: &tauri::AppHandle, record: metrics::MetricsRecord) {
+    if !app_handle.state::<SharedState>().lock().metrics_logging {
+        return;
+    }
+    std::thread::spawn(move || {
+        if let Err(e) = metrics::append(&record) {
+            log::warn!("Failed to write metrics: {}", e);
+        }
+    });
+}
+
+/// Saves a new history entry unless history is turned off.
+fn record_history(app_handle: &tauri::AppHandle, entry: history::HistoryEntry) {
+    if !app_handle.state::<SharedState>().lock().history_enabled {
+        return;
+    }
+    if let Err(e) = history::add_entry(entry) {
+        log::error!("Failed to save history entry: {}", e);
+    }
+    let _ = app_handle.emit("history-updated", ());
+}
+
+/// Replaces the most recent history entry's text (after an undo or accepted
+/// translation) unless history is turned off.
+fn update_history_text(app_handle: &tauri::AppHandle, text: String) {
+    if !app_handle.state::<SharedState>().lock().history_enabled {
+        return;
+    }
+    if let Err(e) = history::update_most_recent_text(text) {
+        log::error!("Failed to update history entry: {}", e);
+    }
+    let _ = app_handle.emit("history-updated", ());
+}
+
+/// Records an accepted translation on the newest history entry, keeping its source.
+fn update_history_translation(
+    app_handle: &tauri::AppHandle,
+    source_text: String,
+    translated_text: String,
+) {
+    if !app_handle.state::<SharedState>().lock().history_enabled {
+        return;
+    }
+    if let Err(e) = history::set_most_recent_translation(source_text, translated_text) {
+        log::error!("Failed to update history entry: {}", e);
+    }
+    let _ = app_handle.emit("history-updated", ());
+}
+
+/// Appends a transcription to the `auto_append_file`, if one is set. Runs on its
+/// own thread; failures are logged and never affect the paste.
+fn append_to_journal(app_handle: &tauri::AppHandle, text: &str) {
+    let Some(template) = app_handle
+        .state::<SharedState>()
+        .lock()
+        .auto_append_file
+        .clone()
+    else {
+        return;
+    };
+    let text = text.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = journal::append_entry(&template, &text) {
+            log::error!("Failed to append transcription to {}: {}", template, e);
+        }
+    });
+}
+
+/// Pastes `text` on the main thread, then returns to Idle.
+/// A failed paste leaves the state machine in Error instead.
+/// In append mode the text is joined onto the append buffer and only the
+/// clipboard is updated; the joined text is pasted on flush.
+fn paste_and_reset(
+    app_handle: &tauri::AppHandle,
+    text: String,
+    smart_paste: bool,
+) -> tauri::Result<()> {
+    let (appended, max_paste_chars, target_app) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let mut state = shared_state.lock();
+        let appended = if state.append_mode {
+            state.append_buffer = append::join_dictation(&state.append_buffer, &text);
+            Some(state.append_buffer.clone())
+        } else {
+            None
+        };
+        (appended, state.max_paste_chars, state.target_app.clone())
+    };
+
+    let app_for_paste = app_handle.clone();
+    if let Some(joined) = appended {
+        let joined = limit_paste_length(app_handle, joined, max_paste_chars);
+        return app_handle.run_on_main_thread(move || {
+            if let Err(e) = copy_to_clipboard(joined) {
+                log::error!("{}", e);
+                set_error_state(&app_for_paste, e);
+                return;
+            }
+            reset_to_idle(&app_for_paste);
+        });
+    }
+
+    let text = limit_paste_length(app_handle, text, max_paste_chars);
+    app_handle.run_on_main_thread(move || {
+        // Remembered before pasting so a paste that fails can still be retried
+        *app_for_paste.state::<LastPaste>().0.lock().unwrap() = Some(text.clone());
+        if let Err(e) = input::paste::paste_text(&text, smart_paste, target_app.as_deref()) {
+            log::error!("Failed to paste text: {}", e);
+            set_error_state(&app_for_paste, format!("Failed to paste: {}", e));
+            return;
+        }
+        reset_to_idle(&app_for_paste);
+    })
+}
+
+/// Emitted as 'paste-truncated' when text was cut to `max_paste_chars` before pasting.
+#[derive(Clone, Serialize)]
+struct PasteTruncated {
+    original_chars: usize,
+    max_chars: usize,
+}
+
+/// Cuts `text` to `max_chars` at a word boundary (0 means no limit), telling the
+/// frontend when anything was dropped. History keeps the full text.
+fn limit_paste_length(app_handle: &tauri::AppHandle, text: String, max_chars: usize) -> String {
+    let truncated = truncate::truncate_at_word_boundary(&text, max_chars);
+    if truncated.len() < text.len() {
+        let original_chars = text.chars().count();
+        log::info!(
+            "Truncated {} characters to {} before pasting",
+            original_chars,
+            max_chars
+        );
+        let _ = app_handle.emit(
+            "paste-truncated",
+            PasteTruncated {
+                original_chars,
+                max_chars,
+            },
+        );
+    }
+    truncated
+}
+
+/// The most recently transcribed text sent to paste, kept even if that paste
+/// failed, for `repeat_last_paste`.
+pub struct LastPaste(pub std::sync::Mutex<Option<String>>);
+
+/// Pastes the previous result again, e.g. when focus moved at the wrong moment
+/// and the first paste didn't land.
+#[tauri::command]
+fn repeat_last_paste(
+    app: tauri::AppHandle,
+    shared_state: tauri::State<'_, SharedState>,
+    last_paste: tauri::State<'_, LastPaste>,
+) -> Result<(), String> {
+    let (smart_paste, target_app) = {
+        let state = shared_state.lock();
+        if !matches!(state.dictation_state, DictationState::Idle) {
+            return Err("Can't paste while dictation is in progress".to_string());
+        }
+        (state.smart_paste, state.target_app.clone())
+    };
+    let text = last_paste
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "Nothing to paste yet".to_string())?;
+
+    let app_for_paste = app.clone();
+    app.run_on_main_thread(move || {
+        if let Err(e) = input::paste::paste_text(&text, smart_paste, target_app.as_deref()) {
+            log::error!("Failed to paste text: {}", e);
+            set_error_state(&app_for_paste, format!("Failed to paste: {}", e));
+        }
+    })
+    .map_err(|e| format!("Failed to run on main thread: {}", e))
+}
+
+/// Validates a language code from the frontend, normalizing region-qualified
+/// codes like "pt-br" to "pt-BR".
+fn normalize_language(code: &str) -> Result<String, String> {
+    language::normalize(code).ok_or_else(|| format!("Unknown language code '{}'", code))
 }
 
 fn source_language_for_translation(language: &str) -> String {
@@ -251,11 +776,12 @@ fn sync_translation_languages(app: &tauri::AppHandle) {
     let (source, target) = {
         let shared_state = app.state::<SharedState>();
         let state = shared_state.lock();
+        let language = state.active_language();
         (
-            if state.language == "auto" {
+            if language == "auto" {
                 None
             } else {
-                Some(state.language.clone())
+                Some(language.to_string())
             },
             state.translation_target_lang.clone(),
         )
@@ -266,384 +792,489 @@ fn sync_translation_languages(app: &tauri::AppHandle) {
     let _ = tx.send(TranslationRequest::SetLanguages { source, target });
 }
 
-/// Registers fallback shortcuts for opening windows when the tray icon is hidden by macOS.
-fn register_fallback_shortcuts(app: &tauri::AppHandle) {
-    let gs = app.global_shortcut();
+#[tauri::command]
+fn get_translation_max_input_tokens(shared_state: tauri::State<'_, SharedState>) -> usize {
+    shared_state.lock().translation_max_input_tokens
+}
 
-    if let Err(e) = gs.on_shortcut("cmd+alt+,", |app, _shortcut, event| {
-        if event.state == ShortcutState::Pressed {
-            tray::show_settings_window(app);
-        }
-    }) {
-        log::warn!("Failed to register fallback settings shortcut: {}", e);
+#[tauri::command]
+fn set_translation_max_input_tokens(
+    app: tauri::AppHandle,
+    max_tokens: usize,
+) -> Result<(), String> {
+    if max_tokens == 0 {
+        return Err("The token limit must be at least 1".to_string());
     }
 
-    if let Err(e) = gs.on_shortcut("cmd+alt+h", |app, _shortcut, event| {
-        if event.state == ShortcutState::Pressed {
-            tray::show_history_window(app);
-        }
-    }) {
-        log::warn!("Failed to register fallback history shortcut: {}", e);
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.translation_max_input_tokens = max_tokens;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.translation_max_input_tokens = max_tokens;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Send to translation thread
+    {
+        let tx = app.state::<TranslationSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranslationRequest::SetMaxInputTokens(max_tokens));
     }
+
+    Ok(())
 }
 
-/// Toggles recording based on the current dictation state.
-fn toggle_recording(app_handle: &tauri::AppHandle) {
-    let shared_state = app_handle.state::<SharedState>();
-    let current_state = {
+/// Sends the active language (session override or persisted setting) to the
+/// transcription thread.
+fn sync_whisper_language(app: &tauri::AppHandle) {
+    let language = {
+        let shared_state = app.state::<SharedState>();
         let state = shared_state.lock();
-        state.dictation_state.clone()
+        language::whisper_language(state.active_language())
     };
 
-    match current_state {
-        DictationState::Idle => {
-            // Re-check accessibility permission before starting recording
-            if !input::paste::check_accessibility_permission() {
-                let error_state = DictationState::Error {
-                    message: "Accessibility access needed — check System Settings > Privacy > Accessibility".to_string(),
-                };
-                {
-                    let mut state = shared_state.lock();
-                    state.dictation_state = error_state.clone();
-                }
-                emit_state(app_handle, &error_state);
-                if let Some(window) = app_handle.get_webview_window("overlay") {
-                    let _ = window.show();
-                }
-                return;
-            }
-
-            // Start recording
-            match audio::capture::AudioCapture::new() {
-                Ok(mut capture) => match capture.start_recording() {
-                    Ok(()) => {
-                        // Store the active capture
-                        let active_capture = app_handle.state::<ActiveCapture>();
-                        {
-                            let mut ac = active_capture.0.lock().unwrap();
-                            *ac = Some(capture);
-                        }
-
-                        // Update state to Recording
-                        let initial_recording_state = {
-                            let mut state = shared_state.lock();
-                            let source_lang = source_language_for_translation(&state.language);
-                            let target_lang = state.translation_target_lang.clone();
-                            state.dictation_state = DictationState::Recording {
-                                duration_ms: 0,
-                                partial_text: None,
-                                partial_translation: None,
-                                source_lang,
-                                target_lang,
-                            };
-                            state.dictation_state.clone()
-                        };
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetLanguage(language));
+    }
+    sync_whisper_prompt(app);
+}
 
-                        emit_state(app_handle, &initial_recording_state);
+/// Sends the initial prompt for the active language (its preset, or the global
+/// prompt) to the transcription thread.
+fn sync_whisper_prompt(app: &tauri::AppHandle) {
+    let prompt = app.state::<SharedState>().lock().whisper_prompt();
 
-                        // Show overlay window without focus
-                        if let Some(window) = app_handle.get_webview_window("overlay") {
-                            let _ = window.show();
-                        }
+    let tx = app.state::<TranscriptionSender>();
+    let tx = tx.0.lock().unwrap();
+    let _ = tx.send(TranscriptionRequest::SetInitialPrompt(prompt));
+}
 
-                        // Start the streaming partial transcription loop
-                        let streaming_flag = app_handle.state::<StreamingActive>();
-                        streaming_flag.0.store(true, Ordering::SeqCst);
-                        let flag = Arc::clone(&streaming_flag.0);
-                        let app_stream = app_handle.clone();
+/// Loads the configured `partial_model` into the transcription thread. Partials
+/// fall back to the main model when it is unset, is the main model, isn't
+/// downloaded, or is English-only while another language is selected.
+fn sync_partial_model(app: &tauri::AppHandle) -> Result<(), String> {
+    let (partial_model, selected_model, language) = {
+        let shared_state = app.state::<SharedState>();
+        let state = shared_state.lock();
+        (
+            state.partial_model.clone(),
+            state.selected_model.clone(),
+            state.language.clone(),
+        )
+    };
 
-                        // Spawn audio level emitter (~30fps) + duration tracker
-                        let flag_levels = Arc::clone(&streaming_flag.0);
-                        let app_levels = app_handle.clone();
-                        let recording_start = std::time::Instant::now();
-                        std::thread::spawn(move || {
-                            while flag_levels.load(Ordering::SeqCst) {
-                                let levels = {
-                                    let active_capture = app_levels.state::<ActiveCapture>();
-                                    let ac = active_capture.0.lock().unwrap();
-                                    match ac.as_ref() {
-                                        Some(capture) => {
-                                            let buf = capture.buffer().lock().unwrap();
-                                            audio::levels::compute_levels(
-                                                &buf,
-                                                capture.sample_rate(),
-                                                48,
-                                            )
-                                        }
-                                        None => break,
-                                    }
-                                };
+    let path = partial_model
+        .filter(|name| *name != selected_model)
+        .and_then(|name| {
+            if transcription::model_manager::is_english_only(&name)
+                && language::base(&language) != "en"
+            {
+                log::info!(
+                    "Partial model '{}' is English-only; using the main model for partials",
+                    name
+                );
+                return None;
+            }
+            if !transcription::model_manager::model_exists(&name) {
+                log::warn!(
+                    "Partial model '{}' isn't downloaded; using the main model for partials",
+                    name
+                );
+                return None;
+            }
+            transcription::model_manager::model_path(&name)
+        })
+        .map(|path| path.to_string_lossy().to_string());
 
-                                let _ = app_levels.emit("audio-levels", &levels);
-
-                                // Update duration_ms in shared state
-                                let elapsed_ms = recording_start.elapsed().as_millis() as u64;
-                                let shared_state = app_levels.state::<SharedState>();
-                                let new_state = {
-                                    let mut state = shared_state.lock();
-                                    if let DictationState::Recording {
-                                        partial_text,
-                                        partial_translation,
-                                        source_lang,
-                                        target_lang,
-                                        ..
-                                    } = &state.dictation_state
-                                    {
-                                        state.dictation_state = DictationState::Recording {
-                                            duration_ms: elapsed_ms,
-                                            partial_text: partial_text.clone(),
-                                            partial_translation: partial_translation.clone(),
-                                            source_lang: source_lang.clone(),
-                                            target_lang: target_lang.clone(),
-                                        };
-                                        Some(state.dictation_state.clone())
-                                    } else {
-                                        None
-                                    }
-                                };
-                                if let Some(new_state) = new_state {
-                                    emit_state(&app_levels, &new_state);
-                                }
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        tx.send(TranscriptionRequest::LoadPartialModel(path, reply_tx))
+            .map_err(|_| "Transcription thread is not running".to_string())?;
+    }
+    reply_rx
+        .recv_timeout(std::time::Duration::from_secs(30))
+        .map_err(|_| "Partial model loading timed out".to_string())?
+}
 
-                                std::thread::sleep(std::time::Duration::from_millis(33));
-                            }
-                        });
+/// Stops the local API server, if running, and waits for it to release its port.
+fn stop_local_api(app_handle: &tauri::AppHandle) {
+    let server = app_handle.state::<LocalApiServer>();
+    let running = server.0.lock().unwrap().take();
+    if let Some((stop, handle)) = running {
+        stop.store(true, Ordering::SeqCst);
+        let _ = handle.join();
+    }
+}
 
-                        std::thread::spawn(move || {
-                            // Wait for initial audio to accumulate
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-
-                            while flag.load(Ordering::SeqCst) {
-                                let tick_start = std::time::Instant::now();
-
-                                // Clone the audio buffer
-                                let audio_data = {
-                                    let active_capture = app_stream.state::<ActiveCapture>();
-                                    let ac = active_capture.0.lock().unwrap();
-                                    match ac.as_ref() {
-                                        Some(capture) => capture.clone_buffer_resampled(),
-                                        None => break,
-                                    }
-                                };
+/// (Re)starts the local API server according to config. Creates the access token
+/// the first time the API is enabled.
+fn restart_local_api(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    stop_local_api(app_handle);
 
-                                if audio_data.is_empty() {
-                                    std::thread::sleep(std::time::Duration::from_millis(500));
-                                    continue;
-                                }
+    let mut cfg = config::load_config();
+    if !cfg.enable_local_api {
+        return Ok(());
+    }
 
-                                // Send partial transcription request
-                                {
-                                    let tx = app_stream.state::<TranscriptionSender>();
-                                    let tx = tx.0.lock().unwrap();
-                                    let _ = tx
-                                        .send(TranscriptionRequest::TranscribePartial(audio_data));
-                                }
+    let token = match &cfg.local_api_token {
+        Some(token) => token.clone(),
+        None => {
+            let token = api::generate_token();
+            cfg.local_api_token = Some(token.clone());
+            config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+            token
+        }
+    };
 
-                                // Wait for partial result on the dedicated channel
-                                let resp = {
-                                    let rx = app_stream.state::<PartialTranscriptionReceiver>();
-                                    let rx = rx.0.lock().unwrap();
-                                    rx.recv_timeout(std::time::Duration::from_millis(5000))
-                                };
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = api::start(
+        app_handle.clone(),
+        cfg.local_api_port,
+        token,
+        Arc::clone(&stop),
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to start local API on port {}: {}",
+            cfg.local_api_port, e
+        )
+    })?;
+    *app_handle.state::<LocalApiServer>().0.lock().unwrap() = Some((stop, handle));
 
-                                if !flag.load(Ordering::SeqCst) {
-                                    break;
-                                }
+    Ok(())
+}
 
-                                if let Ok(text) = resp {
-                                    let partial_text = text.trim().to_string();
-                                    let partial = if partial_text.is_empty() {
-                                        None
-                                    } else {
-                                        Some(partial_text.clone())
-                                    };
-
-                                    let (
-                                        recording_duration_ms,
-                                        source_lang,
-                                        target_lang,
-                                        translation_enabled,
-                                    ) = {
-                                        let shared_state = app_stream.state::<SharedState>();
-                                        let state = shared_state.lock();
-                                        if let DictationState::Recording {
-                                            duration_ms,
-                                            source_lang,
-                                            target_lang,
-                                            ..
-                                        } = &state.dictation_state
-                                        {
-                                            (
-                                                Some(*duration_ms),
-                                                source_lang.clone(),
-                                                target_lang.clone(),
-                                                state.translation_enabled,
-                                            )
-                                        } else {
-                                            (None, String::new(), String::new(), false)
-                                        }
-                                    };
-
-                                    let partial_translation = if translation_enabled
-                                        && partial.is_some()
-                                        && recording_duration_ms.is_some()
-                                    {
-                                        {
-                                            let tx = app_stream.state::<TranslationSender>();
-                                            let tx = tx.0.lock().unwrap();
-                                            let _ = tx.send(TranslationRequest::TranslatePartial(
-                                                TranslationJob {
-                                                    text: partial_text.clone(),
-                                                    source_lang: source_lang.clone(),
-                                                    target_lang: target_lang.clone(),
-                                                },
-                                            ));
-                                        }
-
-                                        let resp = {
-                                            let rx =
-                                                app_stream.state::<PartialTranslationReceiver>();
-                                            let rx = rx.0.lock().unwrap();
-                                            rx.recv_timeout(std::time::Duration::from_millis(1500))
-                                        };
-
-                                        resp.ok().and_then(|t| {
-                                            let trimmed = t.trim().to_string();
-                                            if trimmed.is_empty() {
-                                                None
-                                            } else {
-                                                Some(trimmed)
-                                            }
-                                        })
-                                    } else {
-                                        None
-                                    };
-
-                                    let shared_state = app_stream.state::<SharedState>();
-                                    let new_state = {
-                                        let mut state = shared_state.lock();
-                                        if let DictationState::Recording {
-                                            duration_ms: d,
-                                            source_lang,
-                                            target_lang,
-                                            ..
-                                        } = state.dictation_state.clone()
-                                        {
-                                            let partial = if text.trim().is_empty() {
-                                                None
-                                            } else {
-                                                Some(text.trim().to_string())
-                                            };
-                                            state.dictation_state = DictationState::Recording {
-                                                duration_ms: d,
-                                                partial_text: partial,
-                                                partial_translation,
-                                                source_lang,
-                                                target_lang,
-                                            };
-                                            Some(state.dictation_state.clone())
-                                        } else {
-                                            None
-                                        }
-                                    };
-
-                                    if let Some(new_state) = new_state {
-                                        emit_state(&app_stream, &new_state);
-                                    }
-                                }
+/// How long to wait for worker threads to finish their current job on quit.
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Stops background work before the process exits: ends the streaming loops and any
+/// active capture, cancels downloads, and asks both worker threads to shut down.
+fn shutdown(app_handle: &tauri::AppHandle) {
+    log::info!("Shutting down");
+
+    stop_local_api(app_handle);
+
+    app_handle
+        .state::<StreamingActive>()
+        .0
+        .store(false, Ordering::SeqCst);
+    app_handle
+        .state::<DownloadCancel>()
+        .0
+        .store(true, Ordering::SeqCst);
+
+    {
+        let active_capture = app_handle.state::<ActiveCapture>();
+        let mut ac = active_capture.0.lock().unwrap();
+        if let Some(mut capture) = ac.take() {
+            let _ = capture.stop_recording();
+        }
+    }
+
+    {
+        let tx = app_handle.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::Shutdown);
+    }
+    {
+        let tx = app_handle.state::<TranslationSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranslationRequest::Shutdown);
+    }
+
+    // A worker may be mid-inference; give it a moment, but don't hang the quit on it.
+    let handles = std::mem::take(&mut *app_handle.state::<WorkerThreads>().0.lock().unwrap());
+    let deadline = std::time::Instant::now() + SHUTDOWN_TIMEOUT;
+    while handles.iter().any(|h| !h.is_finished()) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    for handle in handles {
+        if handle.is_finished() {
+            let _ = handle.join();
+        } else {
+            log::warn!("Worker thread still busy at shutdown");
+        }
+    }
+}
+
+/// Registers fallback shortcuts for opening windows when the tray icon is hidden by macOS.
+fn register_fallback_shortcuts(app: &tauri::AppHandle) {
+    let gs = app.global_shortcut();
+
+    if let Err(e) = gs.on_shortcut("cmd+alt+,", |app, _shortcut, event| {
+        if event.state == ShortcutState::Pressed {
+            tray::show_settings_window(app);
+        }
+    }) {
+        log::warn!("Failed to register fallback settings shortcut: {}", e);
+    }
+
+    if let Err(e) = gs.on_shortcut("cmd+alt+h", |app, _shortcut, event| {
+        if event.state == ShortcutState::Pressed {
+            tray::show_history_window(app);
+        }
+    }) {
+        log::warn!("Failed to register fallback history shortcut: {}", e);
+    }
+}
+
+/// A recording at least this long that produced no samples at all points to a broken
+/// input device rather than silence.
+const NO_AUDIO_ERROR_AFTER_MS: u64 = 2000;
+
+/// Toggles recording based on the current dictation state.
+fn toggle_recording(app_handle: &tauri::AppHandle) {
+    let shared_state = app_handle.state::<SharedState>();
+    let current_state = {
+        let state = shared_state.lock();
+        state.dictation_state.clone()
+    };
+
+    match current_state {
+        DictationState::Idle => {
+            // Quiet hours only block starting; a recording in progress can still be stopped
+            if shared_state.lock().quiet_hours_active() {
+                log::info!("Ignoring recording request during quiet hours");
+                let _ = app_handle.emit("quiet-hours-blocked", ());
+                tray::flash_quiet_hours(app_handle);
+                return;
+            }
+
+            // Re-check accessibility permission before starting recording
+            if !input::paste::check_accessibility_permission() {
+                set_error_state(
+                    app_handle,
+                    "Accessibility access needed — check System Settings > Privacy > Accessibility"
+                        .to_string(),
+                );
+                show_overlay(app_handle);
+                return;
+            }
+
+            // Apply a one-off session language before any audio reaches whisper
+            if shared_state.lock().session_language.is_some() {
+                sync_whisper_language(app_handle);
+                sync_translation_languages(app_handle);
+            }
+
+            // Start recording
+            let (max_recording_ms, input_device) = {
+                let state = shared_state.lock();
+                (state.max_recording_ms, state.input_device.clone())
+            };
+            let capture = match input_device {
+                Some(name) => audio::capture::AudioCapture::with_device(&name).or_else(|e| {
+                    // A missing device shouldn't stop the dictation; use the default mic
+                    log::warn!("{}; recording from the default input instead", e);
+                    let _ = app_handle.emit(
+                        "input-device-changed",
+                        format!("{} isn't connected — recording from the default mic", name),
+                    );
+                    audio::capture::AudioCapture::new()
+                }),
+                None => audio::capture::AudioCapture::new(),
+            };
+            match capture {
+                Ok(mut capture) => match capture.start_recording(max_recording_ms) {
+                    Ok(()) => {
+                        // Store the active capture
+                        let active_capture = app_handle.state::<ActiveCapture>();
+                        {
+                            let mut ac = active_capture.0.lock().unwrap();
+                            *ac = Some(capture);
+                        }
+
+                        // Update state to Recording
+                        let (initial_recording_state, recording_generation) = {
+                            let mut state = shared_state.lock();
+                            state.recording_generation += 1;
+                            let source_lang =
+                                source_language_for_translation(state.active_language());
+                            let target_lang = state.translation_target_lang.clone();
+                            state.dictation_state = DictationState::Recording {
+                                duration_ms: 0,
+                                partial_text: None,
+                                partial_translation: None,
+                                source_lang,
+                                target_lang,
+                            };
+                            (state.dictation_state.clone(), state.recording_generation)
+                        };
+
+                        emit_state(app_handle, &initial_recording_state);
 
-                                // Sleep remaining time to hit ~1s interval
-                                let elapsed = tick_start.elapsed();
-                                if elapsed < std::time::Duration::from_millis(1000) {
-                                    std::thread::sleep(
-                                        std::time::Duration::from_millis(1000) - elapsed,
-                                    );
+                        // Show overlay window without focus, after the grace period if set
+                        let show_delay_ms = shared_state.lock().overlay_show_delay_ms;
+                        if show_delay_ms == 0 {
+                            show_overlay(app_handle);
+                        } else {
+                            let app_handle = app_handle.clone();
+                            std::thread::spawn(move || {
+                                std::thread::sleep(std::time::Duration::from_millis(show_delay_ms));
+                                // A recording that already stopped never shows it, even if
+                                // another one started during the delay
+                                let still_recording = {
+                                    let shared_state = app_handle.state::<SharedState>();
+                                    let state = shared_state.lock();
+                                    matches!(
+                                        state.dictation_state,
+                                        DictationState::Recording { .. }
+                                    ) && state.recording_generation == recording_generation
+                                };
+                                if still_recording {
+                                    show_overlay(&app_handle);
                                 }
-                            }
+                            });
+                        }
+
+                        // Start the streaming partial transcription loop
+                        let streaming_flag = app_handle.state::<StreamingActive>();
+                        streaming_flag.0.store(true, Ordering::SeqCst);
+
+                        // Spawn audio level emitter (~30fps) + duration tracker
+                        let flag = Arc::clone(&streaming_flag.0);
+                        let app_levels = app_handle.clone();
+                        let recording_start = std::time::Instant::now();
+                        std::thread::spawn(move || {
+                            emit_levels_loop(app_levels, flag, recording_start);
+                        });
+
+                        let flag = Arc::clone(&streaming_flag.0);
+                        let app_stream = app_handle.clone();
+                        std::thread::spawn(move || {
+                            stream_partials_loop(app_stream, flag);
                         });
                     }
                     Err(e) => {
                         log::error!("Failed to start recording: {}", e);
-                        let error_state = DictationState::Error {
-                            message: "Microphone access needed — check System Settings > Privacy > Microphone".to_string(),
-                        };
-                        {
-                            let mut state = shared_state.lock();
-                            state.dictation_state = error_state.clone();
-                        }
-                        emit_state(app_handle, &error_state);
-                        if let Some(window) = app_handle.get_webview_window("overlay") {
-                            let _ = window.show();
-                        }
+                        set_error_state(
+                            app_handle,
+                            "Microphone access needed — check System Settings > Privacy > Microphone".to_string(),
+                        );
+                        show_overlay(app_handle);
                     }
                 },
                 Err(e) => {
                     log::error!("Failed to create audio capture: {}", e);
-                    let error_state = DictationState::Error {
-                        message: "Microphone access needed — check System Settings > Privacy > Microphone".to_string(),
-                    };
-                    {
-                        let mut state = shared_state.lock();
-                        state.dictation_state = error_state.clone();
-                    }
-                    emit_state(app_handle, &error_state);
-                    if let Some(window) = app_handle.get_webview_window("overlay") {
-                        let _ = window.show();
-                    }
+                    set_error_state(
+                        app_handle,
+                        "Microphone access needed — check System Settings > Privacy > Microphone"
+                            .to_string(),
+                    );
+                    show_overlay(app_handle);
                 }
             }
         }
         DictationState::Recording { .. } => {
-            // Stop the streaming loop
+            // Stop the streaming loop, and drop its queued partial translations
+            // so they don't hold up the final one
             let streaming_flag = app_handle.state::<StreamingActive>();
             streaming_flag.0.store(false, Ordering::SeqCst);
+            translation::engine::cancel_partials();
 
             // Stop recording and begin transcription
-            let audio_data = {
+            let (raw_audio, channel_imbalance) = {
                 let active_capture = app_handle.state::<ActiveCapture>();
                 let mut ac = active_capture.0.lock().unwrap();
-                if let Some(mut capture) = ac.take() {
-                    capture.stop_recording()
-                } else {
-                    Vec::new()
-                }
+                ac.take()
+                    .map(|mut capture| {
+                        let imbalance = capture.channel_imbalance();
+                        (capture.stop_recording_raw(), imbalance)
+                    })
+                    .unwrap_or_default()
             };
-
-            // If no audio data, just go back to Idle
-            if audio_data.is_empty() {
-                {
-                    let mut state = shared_state.lock();
-                    state.dictation_state = DictationState::Idle;
-                }
-                emit_state(app_handle, &DictationState::Idle);
-                if let Some(window) = app_handle.get_webview_window("overlay") {
-                    let _ = window.hide();
-                }
-                return;
+            if let Some(imbalance) = channel_imbalance {
+                // Channels are averaged into mono, so a dead one makes the recording quieter
+                log::warn!(
+                    "Only input channel(s) {:?} of {} had signal",
+                    imbalance.live_channels,
+                    imbalance.channels
+                );
+                let _ = app_handle.emit("channel-imbalance", imbalance);
             }
+            // Whisper always gets 16kHz; the kept copy stays at the native rate
+            let audio_data = raw_audio.resampled_16k();
+            *app_handle.state::<LastRecording>().0.lock().unwrap() = raw_audio;
 
             // Capture recording duration before transitioning to Processing
-            let recording_duration_ms = {
+            let (recording_duration_ms, min_recording_ms, trim_silence) = {
                 let state = shared_state.lock();
-                if let DictationState::Recording { duration_ms, .. } = &state.dictation_state {
-                    *duration_ms
+                let duration_ms =
+                    if let DictationState::Recording { duration_ms, .. } = &state.dictation_state {
+                        *duration_ms
+                    } else {
+                        0
+                    };
+                (duration_ms, state.min_recording_ms, state.trim_silence)
+            };
+
+            if audio_data.is_empty() {
+                // Some virtual devices never deliver samples; don't mistake that for silence
+                if recording_duration_ms >= NO_AUDIO_ERROR_AFTER_MS {
+                    log::error!(
+                        "No samples captured during {}ms recording",
+                        recording_duration_ms
+                    );
+                    set_error_state(
+                        app_handle,
+                        "No audio received from input device — check your mic".to_string(),
+                    );
+                    show_overlay(app_handle);
                 } else {
-                    0
+                    reset_to_idle(app_handle);
                 }
+                return;
+            }
+
+            // Very short recordings are usually accidental taps, and whisper tends to
+            // hallucinate words from them — discard instead of transcribing
+            if recording_duration_ms < min_recording_ms {
+                log::info!(
+                    "Discarding {}ms recording (minimum is {}ms)",
+                    recording_duration_ms,
+                    min_recording_ms
+                );
+                reset_to_idle(app_handle);
+                return;
+            }
+
+            // Leading/trailing silence wastes whisper time and invites hallucinations
+            let audio_data = if trim_silence {
+                audio::silence::trim_silence(&audio_data, 16000)
+            } else {
+                audio_data
             };
 
             // Set state to Processing
-            {
+            let dictation_id = {
                 let mut state = shared_state.lock();
                 state.dictation_state = DictationState::Processing;
-            }
+                state.last_dictation_id += 1;
+                state.active_dictation = Some(state.last_dictation_id);
+                state.last_dictation_id
+            };
             emit_state(app_handle, &DictationState::Processing);
 
+            // The previous result is no longer the one to repeat
+            *app_handle.state::<LastPaste>().0.lock().unwrap() = None;
+
             // Send audio to transcription thread
+            let transcription_start = std::time::Instant::now();
             {
                 let tx = app_handle.state::<TranscriptionSender>();
                 let tx = tx.0.lock().unwrap();
-                let _ = tx.send(TranscriptionRequest::Transcribe(audio_data));
+                let _ = tx.send(TranscriptionRequest::Transcribe(
+                    audio_data,
+                    Some(dictation_id),
+                ));
             }
 
             // Spawn a thread to wait for the transcription result (with timeout)
@@ -655,260 +1286,52 @@ fn toggle_recording(app_handle: &tauri::AppHandle) {
                     rx.recv_timeout(std::time::Duration::from_secs(60))
                         .map_err(|_| ())
                 };
+                let transcription_ms = transcription_start.elapsed().as_millis() as u64;
+
+                // Cancelled from the hotkey (or given up on); nobody wants this result
+                if !dictation_is_active(&app_handle_clone, dictation_id) {
+                    log::info!("Discarding result of cancelled dictation");
+                    return;
+                }
 
                 match resp {
-                    Ok(TranscriptionResponse::TranscriptionComplete(Ok(text))) => {
-                        let trimmed = text.trim().to_string();
+                    Ok(TranscriptionResponse::TranscriptionComplete(Ok(transcript))) => {
+                        let trimmed = transcript.text.trim().to_string();
+                        let min_word_chars = app_handle_clone
+                            .state::<SharedState>()
+                            .lock()
+                            .min_word_chars;
                         if trimmed.is_empty() {
                             // Silent audio — go back to Idle without pasting
-                            let shared_state = app_handle_clone.state::<SharedState>();
-                            {
-                                let mut state = shared_state.lock();
-                                state.dictation_state = DictationState::Idle;
-                            }
-                            emit_state(&app_handle_clone, &DictationState::Idle);
-                            if let Some(window) = app_handle_clone.get_webview_window("overlay") {
-                                let _ = window.hide();
-                            }
+                            reset_to_idle(&app_handle_clone);
+                        } else if !transcription::segments::has_meaningful_content(
+                            &trimmed,
+                            min_word_chars,
+                        ) {
+                            // Just "." or a stray letter from marginal audio — treat as silence
+                            log::info!("Discarding transcript without clear speech: {:?}", trimmed);
+                            let _ = app_handle_clone.emit("no-clear-speech", &trimmed);
+                            reset_to_idle(&app_handle_clone);
                         } else {
-                            let (
-                                vocab_enabled,
-                                translation_enabled,
-                                source_lang,
-                                target_lang,
-                                smart_paste,
-                            ) = {
-                                let shared_state = app_handle_clone.state::<SharedState>();
-                                let state = shared_state.lock();
-                                (
-                                    state.vocab_enabled,
-                                    state.translation_enabled,
-                                    source_language_for_translation(&state.language),
-                                    state.translation_target_lang.clone(),
-                                    state.smart_paste,
-                                )
-                            };
-
-                            let correction_result = if vocab_enabled {
-                                let vocab = vocabulary::load_vocabulary();
-                                let result = vocabulary::apply_corrections(&trimmed, &vocab);
-                                if result.corrections.is_empty() {
-                                    None
-                                } else {
-                                    Some(result)
-                                }
-                            } else {
-                                None
-                            };
-
-                            let source_text = correction_result
-                                .as_ref()
-                                .map_or_else(|| trimmed.clone(), |r| r.text.clone());
-
-                            let timestamp_ms = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_millis() as u64;
-                            let entry = history::HistoryEntry {
-                                id: timestamp_ms,
-                                text: source_text.clone(),
-                                timestamp_ms,
-                                duration_ms: recording_duration_ms,
-                            };
-                            if let Err(e) = history::add_entry(entry) {
-                                log::error!("Failed to save history entry: {}", e);
-                            }
-                            let _ = app_handle_clone.emit("history-updated", ());
-
-                            if translation_enabled {
-                                {
-                                    let shared_state = app_handle_clone.state::<SharedState>();
-                                    let mut state = shared_state.lock();
-                                    state.dictation_state = DictationState::Translating;
-                                }
-                                emit_state(&app_handle_clone, &DictationState::Translating);
-
-                                {
-                                    let tx = app_handle_clone.state::<TranslationSender>();
-                                    let tx = tx.0.lock().unwrap();
-                                    let _ =
-                                        tx.send(TranslationRequest::Translate(TranslationJob {
-                                            text: source_text.clone(),
-                                            source_lang: source_lang.clone(),
-                                            target_lang: target_lang.clone(),
-                                        }));
-                                }
-
-                                let translation_resp = {
-                                    let rx = app_handle_clone.state::<TranslationReceiver>();
-                                    let rx = rx.0.lock().unwrap();
-                                    rx.recv_timeout(std::time::Duration::from_secs(30))
-                                        .map_err(|_| ())
-                                };
-
-                                match translation_resp {
-                                    Ok(TranslationResponse::TranslationComplete(Ok(
-                                        translated,
-                                    ))) => {
-                                        let translated_text = translated.trim().to_string();
-                                        let translated_text = if translated_text.is_empty() {
-                                            source_text.clone()
-                                        } else {
-                                            translated_text
-                                        };
-
-                                        let preview_state = DictationState::TranslationPreview {
-                                            source_text: source_text.clone(),
-                                            translated_text: translated_text.clone(),
-                                            source_lang: source_lang.clone(),
-                                            target_lang: target_lang.clone(),
-                                        };
-
-                                        let shared_state = app_handle_clone.state::<SharedState>();
-                                        {
-                                            let mut state = shared_state.lock();
-                                            state.pending_source_text = Some(source_text);
-                                            state.pending_translated_text = Some(translated_text);
-                                            state.dictation_state = preview_state.clone();
-                                        }
-                                        emit_state(&app_handle_clone, &preview_state);
-                                    }
-                                    Ok(TranslationResponse::TranslationComplete(Err(e))) => {
-                                        log::error!("Translation failed: {}", e);
-                                        let app_for_paste = app_handle_clone.clone();
-                                        let text_to_paste = source_text.clone();
-                                        let _ = app_handle_clone.run_on_main_thread(move || {
-                                            if let Err(e) = input::paste::paste_text(
-                                                &text_to_paste,
-                                                smart_paste,
-                                            ) {
-                                                log::error!("Failed to paste text: {}", e);
-                                                let error_state = DictationState::Error {
-                                                    message: format!("Failed to paste: {}", e),
-                                                };
-                                                let shared_state =
-                                                    app_for_paste.state::<SharedState>();
-                                                {
-                                                    let mut state = shared_state.lock();
-                                                    state.dictation_state = error_state.clone();
-                                                }
-                                                emit_state(&app_for_paste, &error_state);
-                                                return;
-                                            }
-
-                                            let shared_state = app_for_paste.state::<SharedState>();
-                                            {
-                                                let mut state = shared_state.lock();
-                                                state.dictation_state = DictationState::Idle;
-                                            }
-                                            emit_state(&app_for_paste, &DictationState::Idle);
-                                            if let Some(window) =
-                                                app_for_paste.get_webview_window("overlay")
-                                            {
-                                                let _ = window.hide();
-                                            }
-                                        });
-                                    }
-                                    Ok(_) | Err(_) => {
-                                        log::error!("Translation timed out or thread disconnected");
-                                        let app_for_paste = app_handle_clone.clone();
-                                        let text_to_paste = source_text.clone();
-                                        let _ = app_handle_clone.run_on_main_thread(move || {
-                                            if let Err(e) = input::paste::paste_text(
-                                                &text_to_paste,
-                                                smart_paste,
-                                            ) {
-                                                log::error!("Failed to paste text: {}", e);
-                                                let error_state = DictationState::Error {
-                                                    message: format!("Failed to paste: {}", e),
-                                                };
-                                                let shared_state =
-                                                    app_for_paste.state::<SharedState>();
-                                                {
-                                                    let mut state = shared_state.lock();
-                                                    state.dictation_state = error_state.clone();
-                                                }
-                                                emit_state(&app_for_paste, &error_state);
-                                                return;
-                                            }
-
-                                            let shared_state = app_for_paste.state::<SharedState>();
-                                            {
-                                                let mut state = shared_state.lock();
-                                                state.dictation_state = DictationState::Idle;
-                                            }
-                                            emit_state(&app_for_paste, &DictationState::Idle);
-                                            if let Some(window) =
-                                                app_for_paste.get_webview_window("overlay")
-                                            {
-                                                let _ = window.hide();
-                                            }
-                                        });
-                                    }
-                                }
-                            } else if let Some(correction_result) = correction_result {
-                                // Corrections found — show preview, do NOT paste yet
-                                let preview_state = DictationState::CorrectionPreview {
-                                    text: correction_result.text.clone(),
-                                    original_text: trimmed.clone(),
-                                    corrections: correction_result.corrections,
-                                };
-                                let shared_state = app_handle_clone.state::<SharedState>();
-                                {
-                                    let mut state = shared_state.lock();
-                                    state.pending_original_text = Some(trimmed);
-                                    state.pending_corrected_text = Some(correction_result.text);
-                                    state.dictation_state = preview_state.clone();
-                                }
-                                emit_state(&app_handle_clone, &preview_state);
-                            } else {
-                                // No corrections — paste immediately.
-                                let app_for_paste = app_handle_clone.clone();
-                                let text_to_paste = source_text.clone();
-                                let _ = app_handle_clone.run_on_main_thread(move || {
-                                    if let Err(e) =
-                                        input::paste::paste_text(&text_to_paste, smart_paste)
-                                    {
-                                        log::error!("Failed to paste text: {}", e);
-                                        let error_state = DictationState::Error {
-                                            message: format!("Failed to paste: {}", e),
-                                        };
-                                        let shared_state = app_for_paste.state::<SharedState>();
-                                        {
-                                            let mut state = shared_state.lock();
-                                            state.dictation_state = error_state.clone();
-                                        }
-                                        emit_state(&app_for_paste, &error_state);
-                                        return;
-                                    }
-
-                                    // Success — back to Idle
-                                    let shared_state = app_for_paste.state::<SharedState>();
-                                    {
-                                        let mut state = shared_state.lock();
-                                        state.dictation_state = DictationState::Idle;
-                                    }
-                                    emit_state(&app_for_paste, &DictationState::Idle);
-                                    if let Some(window) =
-                                        app_for_paste.get_webview_window("overlay")
-                                    {
-                                        let _ = window.hide();
-                                    }
-                                });
-                            }
+                            notify_if_backgrounded(
+                                &app_handle_clone,
+                                "Transcription complete",
+                                &trimmed,
+                            );
+                            deliver_transcript(
+                                &app_handle_clone,
+                                dictation_id,
+                                trimmed,
+                                transcript.detected_language,
+                                recording_duration_ms,
+                                transcription_ms,
+                            );
                         }
                     }
                     Ok(TranscriptionResponse::TranscriptionComplete(Err(e))) => {
                         log::error!("Transcription error: {}", e);
-                        let error_state = DictationState::Error {
-                            message: format!("Transcription failed: {}", e),
-                        };
-                        let shared_state = app_handle_clone.state::<SharedState>();
-                        {
-                            let mut state = shared_state.lock();
-                            state.dictation_state = error_state.clone();
-                        }
-                        emit_state(&app_handle_clone, &error_state);
+                        notify_if_backgrounded(&app_handle_clone, "Transcription failed", &e);
+                        set_error_state(&app_handle_clone, format!("Transcription failed: {}", e));
                         // Keep overlay visible for error state
                     }
                     Ok(_) => {
@@ -917,79 +1340,792 @@ fn toggle_recording(app_handle: &tauri::AppHandle) {
                     }
                     Err(_) => {
                         log::error!("Transcription timed out or thread disconnected");
-                        let error_state = DictationState::Error {
-                            message: "Transcription timed out — try again".to_string(),
-                        };
-                        let shared_state = app_handle_clone.state::<SharedState>();
-                        {
-                            let mut state = shared_state.lock();
-                            state.dictation_state = error_state.clone();
-                        }
-                        emit_state(&app_handle_clone, &error_state);
+                        notify_if_backgrounded(
+                            &app_handle_clone,
+                            "Transcription failed",
+                            "Transcription timed out — try again",
+                        );
+                        set_error_state(
+                            &app_handle_clone,
+                            "Transcription timed out — try again".to_string(),
+                        );
                     }
                 }
             });
         }
-        DictationState::Processing
-        | DictationState::Translating
-        | DictationState::Downloading { .. }
+        DictationState::Processing | DictationState::Translating => {
+            let (action, dictation) = {
+                let state = shared_state.lock();
+                (state.hotkey_during_processing, state.active_dictation)
+            };
+            // Benchmarks and file transcriptions also show Processing; leave those alone
+            let Some(dictation) = dictation else {
+                return;
+            };
+            match action {
+                HotkeyDuringProcessing::Ignore => {}
+                HotkeyDuringProcessing::Cancel => {
+                    log::info!("Cancelling dictation in progress");
+                    if matches!(current_state, DictationState::Processing) {
+                        app_handle
+                            .state::<TranscriptionAbort>()
+                            .0
+                            .store(dictation, Ordering::SeqCst);
+                    }
+                    reset_to_idle(app_handle);
+                }
+                HotkeyDuringProcessing::Queue => {
+                    log::info!("Queueing a recording for when this dictation finishes");
+                    shared_state.lock().queued_recording = true;
+                }
+            }
+        }
+        DictationState::Downloading { .. }
         | DictationState::CorrectionPreview { .. }
         | DictationState::TranslationPreview { .. } => {
-            // Ignore hotkey during processing, translating, downloading, or preview states
+            // Ignore hotkey during downloading or preview states
         }
         DictationState::Error { .. } => {
             // Reset to Idle on error
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = DictationState::Idle;
-            }
-
-            emit_state(app_handle, &DictationState::Idle);
-
-            // Hide overlay window
-            if let Some(window) = app_handle.get_webview_window("overlay") {
-                let _ = window.hide();
-            }
+            reset_to_idle(app_handle);
         }
     }
 }
 
-/// Downloads the model if needed and loads it into the transcription thread.
-fn setup_model(app_handle: tauri::AppHandle) {
-    let shared_state = app_handle.state::<SharedState>();
-    let selected_model = {
-        let state = shared_state.lock();
-        state.selected_model.clone()
-    };
+/// How many level ticks (~33ms each) pass between checks for an input device change.
+const INPUT_DEVICE_CHECK_TICKS: u32 = 30;
 
-    let needs_download = !transcription::model_manager::model_exists(&selected_model);
+/// Emitted as 'recording-warning' about once a second while a recording is
+/// within `max_recording_warning_ms` of being auto-stopped.
+#[derive(Clone, Serialize)]
+struct RecordingWarning {
+    remaining_ms: u64,
+}
 
-    if needs_download {
-        // Show overlay and emit Downloading state
-        {
-            let mut state = shared_state.lock();
-            state.dictation_state = DictationState::Downloading { progress: 0.0 };
-        }
-        emit_state(&app_handle, &DictationState::Downloading { progress: 0.0 });
-        if let Some(window) = app_handle.get_webview_window("overlay") {
-            let _ = window.show();
-        }
+/// Emitted as 'recording-limit-reached' when a recording is stopped automatically
+/// at `max_recording_ms`, so the overlay can say why it stopped.
+#[derive(Clone, Serialize)]
+struct RecordingLimitReached {
+    max_recording_ms: u64,
+}
 
-        // Run async download on a tokio runtime in a separate thread
-        let app_handle_dl = app_handle.clone();
-        let model_name = selected_model.clone();
+/// Emitted as 'silence-auto-stop' when hands-free mode stops a recording after
+/// `silence_timeout_ms` of trailing silence.
+#[derive(Clone, Serialize)]
+struct SilenceAutoStop {
+    silence_timeout_ms: u64,
+}
+
+/// The level bars cover about this much audio; until a recording is this old,
+/// the bars still include silence from before it started.
+const TOO_QUIET_GRACE_MS: u128 = 1600;
+
+/// Emits audio levels (~30fps) and keeps `duration_ms` current while recording.
+/// About once a second it also checks whether the default input device changed
+/// and, if so, moves the capture to the new device. With `max_recording_ms` set,
+/// it warns as the limit approaches and stops the recording once it's reached.
+/// With `vad_enabled`, it also stops the recording once speech is followed by
+/// `silence_timeout_ms` of silence.
+fn emit_levels_loop(
+    app_handle: tauri::AppHandle,
+    flag: Arc<AtomicBool>,
+    recording_start: std::time::Instant,
+) {
+    let (max_recording_ms, warning_ms, too_quiet_threshold, silence_timeout_ms) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let state = shared_state.lock();
+        (
+            state.max_recording_ms,
+            state.max_recording_warning_ms,
+            state.too_quiet_threshold,
+            state.vad_enabled.then_some(state.silence_timeout_ms),
+        )
+    };
+    let mut silence_detector = silence_timeout_ms.map(audio::levels::SilenceDetector::new);
+    let mut last_warning_secs: Option<u64> = None;
+    let mut tick: u32 = 0;
+    while flag.load(Ordering::SeqCst) {
+        tick = tick.wrapping_add(1);
+        let sampled = {
+            let active_capture = app_handle.state::<ActiveCapture>();
+            let mut ac = active_capture.0.lock().unwrap();
+            match ac.as_mut() {
+                Some(capture) => {
+                    let switched = if tick % INPUT_DEVICE_CHECK_TICKS == 0
+                        && capture.default_input_changed()
+                    {
+                        switch_input_device(&app_handle, capture)
+                    } else {
+                        Ok(())
+                    };
+                    switched.map(|()| {
+                        let buf = capture.buffer().lock().unwrap();
+                        // The bars are zero-padded until they fill, so the level of
+                        // the newest audio is measured separately
+                        (
+                            audio::levels::compute_levels(&buf, capture.sample_rate(), 48),
+                            audio::levels::compute_levels(&buf, capture.sample_rate(), 1)[0],
+                        )
+                    })
+                }
+                None => break,
+            }
+        };
+        let (bars, latest_level) = match sampled {
+            Ok(sampled) => sampled,
+            Err(message) => {
+                // The old device may be gone, so the recording could be capturing nothing
+                flag.store(false, Ordering::SeqCst);
+                translation::engine::cancel_partials();
+                if let Some(mut capture) =
+                    app_handle.state::<ActiveCapture>().0.lock().unwrap().take()
+                {
+                    let _ = capture.stop_recording();
+                }
+                set_error_state(&app_handle, message);
+                break;
+            }
+        };
+
+        // Don't tell the user to speak up before they've had a chance to
+        let threshold = if recording_start.elapsed().as_millis() < TOO_QUIET_GRACE_MS {
+            0.0
+        } else {
+            too_quiet_threshold
+        };
+        let levels = audio::levels::summarize_levels(bars, threshold);
+        let _ = app_handle.emit("audio-levels", &levels);
+        api::publish(&app_handle, api::levels_frame(&levels));
+
+        // Update duration_ms in shared state
+        let elapsed_ms = recording_start.elapsed().as_millis() as u64;
+        let shared_state = app_handle.state::<SharedState>();
+        let new_state = {
+            let mut state = shared_state.lock();
+            if let DictationState::Recording {
+                partial_text,
+                partial_translation,
+                source_lang,
+                target_lang,
+                ..
+            } = &state.dictation_state
+            {
+                state.dictation_state = DictationState::Recording {
+                    duration_ms: elapsed_ms,
+                    partial_text: partial_text.clone(),
+                    partial_translation: partial_translation.clone(),
+                    source_lang: source_lang.clone(),
+                    target_lang: target_lang.clone(),
+                };
+                Some(state.dictation_state.clone())
+            } else {
+                None
+            }
+        };
+        if let Some(new_state) = new_state {
+            emit_state(&app_handle, &new_state);
+        }
+
+        if let Some(detector) = silence_detector.as_mut() {
+            if detector.update(latest_level, elapsed_ms) {
+                let silence_timeout_ms = silence_timeout_ms.unwrap_or_default();
+                log::info!(
+                    "Stopping recording after {}ms of silence",
+                    silence_timeout_ms
+                );
+                let _ =
+                    app_handle.emit("silence-auto-stop", SilenceAutoStop { silence_timeout_ms });
+                toggle_recording(&app_handle);
+                break;
+            }
+        }
+
+        if max_recording_ms > 0 {
+            let remaining_ms = max_recording_ms.saturating_sub(elapsed_ms);
+            if remaining_ms == 0 {
+                log::info!("Stopping recording at the {}ms limit", max_recording_ms);
+                let _ = app_handle.emit(
+                    "recording-limit-reached",
+                    RecordingLimitReached { max_recording_ms },
+                );
+                toggle_recording(&app_handle);
+                break;
+            }
+            // Once per second of countdown, so the overlay can tick without being flooded
+            let remaining_secs = remaining_ms.div_ceil(1000);
+            if remaining_ms <= warning_ms && last_warning_secs != Some(remaining_secs) {
+                last_warning_secs = Some(remaining_secs);
+                let _ = app_handle.emit("recording-warning", RecordingWarning { remaining_ms });
+            }
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(33));
+    }
+}
+
+/// Re-opens `capture` on the new default input device and tells the frontend
+/// via an `input-device-changed` event. If the new device can't be opened, the
+/// returned message is for ending the recording with an error, so a
+/// disconnected mic never silently records nothing.
+fn switch_input_device(
+    app_handle: &tauri::AppHandle,
+    capture: &mut audio::capture::AudioCapture,
+) -> Result<(), String> {
+    match capture.switch_to_default_input() {
+        Ok(()) => {
+            log::info!("Default input device changed; switched recording to it");
+            let _ = app_handle.emit(
+                "input-device-changed",
+                "Input device changed — now recording from the new default mic",
+            );
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Failed to switch to new input device: {}", e);
+            Err(format!(
+                "Input device changed and could not be reopened: {}",
+                e
+            ))
+        }
+    }
+}
+
+/// Transcribes the growing recording about once a second and publishes the
+/// partial text (and partial translation, if enabled) in the Recording state.
+fn stream_partials_loop(app_handle: tauri::AppHandle, flag: Arc<AtomicBool>) {
+    // Wait for initial audio to accumulate
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let mut translation_debouncer = translation::partial::PartialTranslationDebouncer::default();
+
+    // Loaded once per recording so partials don't re-read the vocabulary every tick.
+    // Partials only get the cheap corrections: enabled entries matched literally.
+    // Fuzzy entries are left for the final pass, and since every phrase is escaped
+    // before matching there are no regex entries to exclude.
+    let partial_vocabulary = {
+        let shared_state = app_handle.state::<SharedState>();
+        let state = shared_state.lock();
+        (state.correct_partials && state.vocab_enabled).then(|| {
+            let mut vocabulary = vocabulary::load_vocabulary();
+            vocabulary
+                .entries
+                .retain(|entry| entry.enabled && !entry.fuzzy);
+            vocabulary
+        })
+    };
+
+    while flag.load(Ordering::SeqCst) {
+        let tick_start = std::time::Instant::now();
+
+        // Clone only the tail of the audio buffer so partials stay fast on long recordings
+        let window_secs = app_handle.state::<SharedState>().lock().partial_window_secs;
+        let audio_data = {
+            let active_capture = app_handle.state::<ActiveCapture>();
+            let ac = active_capture.0.lock().unwrap();
+            match ac.as_ref() {
+                Some(capture) => capture.clone_tail_resampled(window_secs),
+                None => break,
+            }
+        };
+
+        if audio_data.is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            continue;
+        }
+
+        // Send partial transcription request
+        {
+            let tx = app_handle.state::<TranscriptionSender>();
+            let tx = tx.0.lock().unwrap();
+            let _ = tx.send(TranscriptionRequest::TranscribePartial(audio_data));
+        }
+
+        // Wait for partial result on the dedicated channel
+        let resp = {
+            let rx = app_handle.state::<PartialTranscriptionReceiver>();
+            let rx = rx.0.lock().unwrap();
+            rx.recv_timeout(std::time::Duration::from_millis(5000))
+        };
+
+        if !flag.load(Ordering::SeqCst) {
+            break;
+        }
+
+        if let Ok(text) = resp {
+            let partial_text = match &partial_vocabulary {
+                Some(vocab) => vocabulary::apply_corrections(text.trim(), vocab).text,
+                None => text.trim().to_string(),
+            };
+            let partial = if partial_text.is_empty() {
+                None
+            } else {
+                Some(partial_text.clone())
+            };
+
+            let (
+                recording_duration_ms,
+                source_lang,
+                target_lang,
+                target_variant,
+                translation_enabled,
+            ) = {
+                let shared_state = app_handle.state::<SharedState>();
+                let state = shared_state.lock();
+                if let DictationState::Recording {
+                    duration_ms,
+                    source_lang,
+                    target_lang,
+                    ..
+                } = &state.dictation_state
+                {
+                    (
+                        Some(*duration_ms),
+                        source_lang.clone(),
+                        target_lang.clone(),
+                        state.translation_target_variant.clone(),
+                        state.translation_enabled,
+                    )
+                } else {
+                    (None, String::new(), String::new(), None, false)
+                }
+            };
+
+            let partial_translation =
+                if translation_enabled && partial.is_some() && recording_duration_ms.is_some() {
+                    // Only re-translate once the partial has materially changed
+                    // Recording may have stopped since the state was read
+                    if translation_debouncer.should_translate(&partial_text)
+                        && flag.load(Ordering::SeqCst)
+                    {
+                        {
+                            let tx = app_handle.state::<TranslationSender>();
+                            let tx = tx.0.lock().unwrap();
+                            let _ = tx.send(TranslationRequest::TranslatePartial(TranslationJob {
+                                text: partial_text.clone(),
+                                source_lang: source_lang.clone(),
+                                target_lang: target_lang.clone(),
+                                target_variant: target_variant.clone(),
+                                generation: translation::engine::next_partial_generation(),
+                            }));
+                        }
+
+                        let resp = {
+                            let rx = app_handle.state::<PartialTranslationReceiver>();
+                            let rx = rx.0.lock().unwrap();
+                            rx.recv_timeout(std::time::Duration::from_millis(1500))
+                        };
+
+                        if let Ok(translated) = resp {
+                            let trimmed = translated.trim().to_string();
+                            if !trimmed.is_empty() {
+                                translation_debouncer.accept(&partial_text, trimmed);
+                            }
+                        }
+                    }
+
+                    translation_debouncer.shown().map(str::to_string)
+                } else {
+                    None
+                };
+
+            let shared_state = app_handle.state::<SharedState>();
+            let new_state = {
+                let mut state = shared_state.lock();
+                if let DictationState::Recording {
+                    duration_ms: d,
+                    source_lang,
+                    target_lang,
+                    ..
+                } = state.dictation_state.clone()
+                {
+                    state.dictation_state = DictationState::Recording {
+                        duration_ms: d,
+                        partial_text: partial,
+                        partial_translation,
+                        source_lang,
+                        target_lang,
+                    };
+                    Some(state.dictation_state.clone())
+                } else {
+                    None
+                }
+            };
+
+            if let Some(new_state) = new_state {
+                emit_state(&app_handle, &new_state);
+            }
+        }
+
+        // Sleep remaining time to hit ~1s interval
+        let elapsed = tick_start.elapsed();
+        if elapsed < std::time::Duration::from_millis(1000) {
+            std::thread::sleep(std::time::Duration::from_millis(1000) - elapsed);
+        }
+    }
+}
+
+/// Payload for the 'transcription-result' event sent by `transcribe_to_result` sessions.
+#[derive(Debug, Clone, Serialize)]
+struct TranscriptionResultPayload {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+}
+
+/// Hands a finished transcript to the frontend instead of pasting it, then returns to Idle.
+fn emit_transcription_result(
+    app_handle: &tauri::AppHandle,
+    text: String,
+    translation: Option<String>,
+) {
+    let _ = app_handle.emit(
+        "transcription-result",
+        TranscriptionResultPayload { text, translation },
+    );
+    reset_to_idle(app_handle);
+}
+
+/// Runs a final translation job on the translation thread and waits for the result.
+fn run_translation(app_handle: &tauri::AppHandle, job: TranslationJob) -> Result<String, String> {
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    {
+        let tx = app_handle.state::<TranslationSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranslationRequest::Translate(job, reply_tx));
+    }
+
+    match reply_rx.recv_timeout(std::time::Duration::from_secs(30)) {
+        Ok(result) => result,
+        Err(_) => Err("Translation timed out or thread disconnected".to_string()),
+    }
+}
+
+/// Applies formatting commands and vocabulary corrections to a finished transcript,
+/// records it in history, then translates, previews, pastes, or returns it to the
+/// frontend depending on the current settings.
+fn deliver_transcript(
+    app_handle: &tauri::AppHandle,
+    dictation_id: u64,
+    trimmed: String,
+    detected_language: Option<String>,
+    recording_duration_ms: u64,
+    transcription_ms: u64,
+) {
+    // Spoken formatting commands become symbols first, so corrections, history,
+    // and translation all see the formatted text
+    let trimmed = {
+        let shared_state = app_handle.state::<SharedState>();
+        let state = shared_state.lock();
+        formatting::apply_formatting(&trimmed, state.formatting_profile, &state.formatting_rules)
+    };
+    let (
+        model,
+        language,
+        vocab_enabled,
+        translation_enabled,
+        source_lang,
+        target_lang,
+        target_variant,
+        smart_paste,
+        show_overlay,
+        result_only,
+        auto_accept_corrections,
+        auto_accept_translation,
+        correction_stage,
+    ) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let mut state = shared_state.lock();
+        (
+            state.selected_model.clone(),
+            state.active_language().to_string(),
+            state.vocab_enabled,
+            state.translation_enabled,
+            source_language_for_translation(state.active_language()),
+            state.translation_target_lang.clone(),
+            state.translation_target_variant.clone(),
+            state.smart_paste,
+            state.show_overlay,
+            std::mem::take(&mut state.result_only),
+            state.auto_accept_corrections,
+            state.auto_accept_translation,
+            state.correction_stage,
+        )
+    };
+    if let Some(code) = &detected_language {
+        let _ = app_handle.emit("language-detected", code);
+    }
+    // Whisper already identified the language, so translation doesn't need to guess it again
+    let source_lang = match detected_language.as_deref() {
+        Some(code)
+            if source_lang == "auto"
+                && translation::engine::nllb_lang_for_app_lang(code).is_some() =>
+        {
+            code.to_string()
+        }
+        _ => source_lang,
+    };
+    // Previews need the overlay to be confirmed, and result-only sessions skip them entirely.
+    // Each preview can also be turned off on its own.
+    let show_previews = show_overlay && !result_only;
+    let show_correction_preview = show_previews && !auto_accept_corrections;
+    let show_translation_preview = show_previews && !auto_accept_translation;
+
+    // Without translation the transcript is the final text, so it is always corrected
+    let correct_source =
+        !translation_enabled || correction_stage != CorrectionStage::AfterTranslation;
+    let correct_translation =
+        translation_enabled && correction_stage != CorrectionStage::BeforeTranslation;
+    let vocab = (vocab_enabled && (correct_source || correct_translation))
+        .then(vocabulary::load_vocabulary);
+
+    let correction_result = if let Some(vocab) = vocab.as_ref().filter(|_| correct_source) {
+        let result = vocabulary::apply_corrections(&trimmed, vocab);
+        if result.corrections.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    } else {
+        None
+    };
+
+    let source_text = correction_result
+        .as_ref()
+        .map_or_else(|| trimmed.clone(), |r| r.text.clone());
+
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let entry = history::HistoryEntry {
+        id: timestamp_ms,
+        text: source_text.clone(),
+        timestamp_ms,
+        duration_ms: recording_duration_ms,
+        source_text: None,
+        detected_language: detected_language.clone(),
+    };
+    record_history(app_handle, entry);
+    append_to_journal(app_handle, &source_text);
+
+    let log_metrics = |translated: bool, translation_ms: Option<u64>| {
+        let record = metrics::MetricsRecord {
+            timestamp_ms,
+            duration_ms: recording_duration_ms,
+            word_count: source_text.split_whitespace().count(),
+            model: model.clone(),
+            language: language.clone(),
+            corrections_applied: correction_result.is_some(),
+            translated,
+            transcription_ms,
+            translation_ms,
+        };
+        record_metrics(app_handle, record);
+    };
+    // Translated sessions are logged once the translation has been timed
+    if !translation_enabled {
+        log_metrics(false, None);
+    }
+
+    if translation_enabled {
+        {
+            let shared_state = app_handle.state::<SharedState>();
+            let mut state = shared_state.lock();
+            state.dictation_state = DictationState::Translating;
+        }
+        emit_state(app_handle, &DictationState::Translating);
+
+        let job = TranslationJob {
+            text: source_text.clone(),
+            source_lang: source_lang.clone(),
+            target_lang: target_lang.clone(),
+            target_variant,
+            generation: 0,
+        };
+        let translation_start = std::time::Instant::now();
+        let translation = run_translation(app_handle, job);
+        let translation_ms = translation_start.elapsed().as_millis() as u64;
+        log_metrics(translation.is_ok(), Some(translation_ms));
+
+        if !dictation_is_active(app_handle, dictation_id) {
+            log::info!("Discarding translation of cancelled dictation");
+            return;
+        }
+
+        match translation {
+            Ok(translated) => {
+                let translated_text = translated.trim().to_string();
+                let translated_text = if translated_text.is_empty() {
+                    source_text.clone()
+                } else {
+                    translated_text
+                };
+                let translated_text = match vocab.as_ref().filter(|_| correct_translation) {
+                    Some(vocab) => vocabulary::apply_corrections(&translated_text, vocab).text,
+                    None => translated_text,
+                };
+
+                if result_only {
+                    emit_transcription_result(app_handle, source_text, Some(translated_text));
+                    return;
+                }
+
+                if !show_translation_preview {
+                    // Nothing to confirm the translation in — accept it right away.
+                    update_history_translation(app_handle, source_text, translated_text.clone());
+                    let _ = paste_and_reset(app_handle, translated_text, smart_paste);
+                    return;
+                }
+
+                let preview_state = DictationState::TranslationPreview {
+                    source_text: source_text.clone(),
+                    translated_text: translated_text.clone(),
+                    source_lang,
+                    target_lang,
+                };
+
+                let shared_state = app_handle.state::<SharedState>();
+                {
+                    let mut state = shared_state.lock();
+                    state.pending_source_text = Some(source_text);
+                    state.pending_translated_text = Some(translated_text);
+                    state.dictation_state = preview_state.clone();
+                }
+                emit_state(app_handle, &preview_state);
+            }
+            Err(e) => {
+                log::error!("Translation failed: {}", e);
+                if result_only {
+                    emit_transcription_result(app_handle, source_text, None);
+                } else {
+                    let _ = paste_and_reset(app_handle, source_text, smart_paste);
+                }
+            }
+        }
+    } else if result_only {
+        emit_transcription_result(app_handle, source_text, None);
+    } else if let Some(correction_result) = correction_result.filter(|_| show_correction_preview) {
+        // Corrections found — show preview, do NOT paste yet
+        let preview_state = DictationState::CorrectionPreview {
+            text: correction_result.text.clone(),
+            original_text: trimmed.clone(),
+            corrections: correction_result.corrections,
+        };
+        let shared_state = app_handle.state::<SharedState>();
+        {
+            let mut state = shared_state.lock();
+            state.pending_original_text = Some(trimmed);
+            state.pending_corrected_text = Some(correction_result.text);
+            state.dictation_state = preview_state.clone();
+        }
+        emit_state(app_handle, &preview_state);
+    } else {
+        // No corrections, or corrections are auto-accepted — paste immediately.
+        let _ = paste_and_reset(app_handle, source_text, smart_paste);
+    }
+}
+
+/// Shown when a model is missing and `offline_mode` forbids downloading it.
+const OFFLINE_MODEL_ERROR: &str = "Model not available offline — connect to download";
+
+/// Emitted as 'model-download-progress' while a whisper model downloads, so the
+/// settings window can show progress next to the model being fetched.
+#[derive(Clone, Serialize)]
+struct ModelDownloadProgress {
+    model: String,
+    progress: f32,
+    downloaded_bytes: u64,
+    total_bytes: u64,
+}
+
+/// Emitted as 'model-ready' once a whisper model has loaded and recording can start.
+#[derive(Clone, Serialize)]
+struct ModelReady {
+    model: String,
+}
+
+/// Downloads the model if needed and loads it into the transcription thread.
+/// Emits 'model-unready' first, since the current model is about to be replaced.
+fn setup_model(app_handle: tauri::AppHandle) {
+    let _ = app_handle.emit("model-unready", ());
+    let shared_state = app_handle.state::<SharedState>();
+    let (selected_model, custom_model_path) = {
+        let state = shared_state.lock();
+        (
+            state.selected_model.clone(),
+            state.custom_model_path.clone(),
+        )
+    };
+
+    // A custom model is never downloaded; load it straight from the user's path
+    if selected_model == transcription::model_manager::CUSTOM_MODEL_NAME {
+        match custom_model_path {
+            // The file may have been moved or deleted since it was selected
+            Some(path) if std::path::Path::new(&path).is_file() => {
+                load_model(&app_handle, &path, &selected_model);
+            }
+            path => {
+                log::error!("Custom model not found: {:?}", path);
+                set_error_state(
+                    &app_handle,
+                    format!("Custom model not found: {}", path.unwrap_or_default()),
+                );
+                show_overlay(&app_handle);
+            }
+        }
+        return;
+    }
+
+    let needs_download = !transcription::model_manager::model_exists(&selected_model);
+
+    if needs_download && shared_state.lock().offline_mode {
+        log::error!(
+            "Model '{}' is missing and offline mode is on",
+            selected_model
+        );
+        set_error_state(&app_handle, OFFLINE_MODEL_ERROR.to_string());
+        show_overlay(&app_handle);
+    } else if needs_download {
+        // Show overlay and emit Downloading state
+        {
+            let mut state = shared_state.lock();
+            state.dictation_state = DictationState::Downloading { progress: 0.0 };
+        }
+        emit_state(&app_handle, &DictationState::Downloading { progress: 0.0 });
+        show_overlay(&app_handle);
+
+        // Run async download on a tokio runtime in a separate thread
+        let app_handle_dl = app_handle.clone();
+        let model_name = selected_model.clone();
+        let cancel = Arc::clone(&app_handle.state::<DownloadCancel>().0);
+        let base_url = config::model_base_url();
         let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
         let download_result = rt.block_on(async {
             let app_handle_progress = app_handle_dl.clone();
-            transcription::model_manager::download_model(&model_name, move |downloaded, total| {
-                let progress = if total > 0 {
-                    downloaded as f32 / total as f32
-                } else {
-                    0.0
-                };
-                let dl_state = DictationState::Downloading { progress };
-                emit_state(&app_handle_progress, &dl_state);
-            })
+            let progress_model = model_name.clone();
+            transcription::model_manager::download_model(
+                &model_name,
+                &base_url,
+                &cancel,
+                move |downloaded, total| {
+                    let progress = if total > 0 {
+                        downloaded as f32 / total as f32
+                    } else {
+                        0.0
+                    };
+                    let dl_state = DictationState::Downloading { progress };
+                    emit_state(&app_handle_progress, &dl_state);
+                    let _ = app_handle_progress.emit(
+                        "model-download-progress",
+                        ModelDownloadProgress {
+                            model: progress_model.clone(),
+                            progress,
+                            downloaded_bytes: downloaded,
+                            total_bytes: total,
+                        },
+                    );
+                },
+            )
             .await
         });
 
@@ -1000,14 +2136,7 @@ fn setup_model(app_handle: tauri::AppHandle) {
             }
             Err(e) => {
                 log::error!("Failed to download model: {}", e);
-                let error_state = DictationState::Error {
-                    message: format!("Model download failed: {}", e),
-                };
-                {
-                    let mut state = shared_state.lock();
-                    state.dictation_state = error_state.clone();
-                }
-                emit_state(&app_handle, &error_state);
+                set_error_state(&app_handle, format!("Model download failed: {}", e));
             }
         }
     } else {
@@ -1020,19 +2149,18 @@ fn setup_model(app_handle: tauri::AppHandle) {
 }
 
 fn setup_translation_model(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let model_name = {
+    let (model_name, offline_mode) = {
         let shared_state = app_handle.state::<SharedState>();
         let state = shared_state.lock();
-        state.translation_model.clone()
+        (state.translation_model.clone(), state.offline_mode)
     };
 
     let model_path = if translation::model_manager::model_exists(&model_name) {
         translation::model_manager::model_path(&model_name)
+    } else if offline_mode {
+        return Err("Translation model not available offline — connect to download".to_string());
     } else {
-        log::info!(
-            "Translation model '{}' not found; downloading from Hugging Face",
-            model_name
-        );
+        log::info!("Translation model '{}' not found; downloading", model_name);
 
         let rt = match tokio::runtime::Runtime::new() {
             Ok(rt) => rt,
@@ -1044,8 +2172,16 @@ fn setup_translation_model(app_handle: tauri::AppHandle) -> Result<(), String> {
             }
         };
 
+        let cancel = Arc::clone(&app_handle.state::<DownloadCancel>().0);
+        let base_url = config::model_base_url();
         match rt.block_on(async {
-            translation::model_manager::download_model(&model_name, |_downloaded, _total| {}).await
+            translation::model_manager::download_model(
+                &model_name,
+                &base_url,
+                &cancel,
+                |_downloaded, _total| {},
+            )
+            .await
         }) {
             Ok(path) => path,
             Err(e) => {
@@ -1054,6 +2190,15 @@ fn setup_translation_model(app_handle: tauri::AppHandle) -> Result<(), String> {
         }
     };
 
+    // A corrupt tokenizer or vocabulary otherwise only shows up as a generic load error
+    if let Some((_, reason)) = translation::model_manager::find_bad_file(&model_name) {
+        log::error!("Translation model '{}' is damaged: {}", model_name, reason);
+        return Err(format!(
+            "Translation model is damaged: {} — repair it to download the file again",
+            reason
+        ));
+    }
+
     let model_path_str = model_path.to_string_lossy().to_string();
 
     {
@@ -1078,10 +2223,6 @@ fn setup_translation_model(app_handle: tauri::AppHandle) -> Result<(), String> {
             log::error!("Failed to initialize translation model: {}", e);
             Err(format!("Failed to initialize translation model: {}", e))
         }
-        Ok(_) => {
-            log::error!("Unexpected translation response during model initialization");
-            Err("Unexpected translation response during model initialization".to_string())
-        }
         Err(_) => {
             log::error!("Translation model initialization timed out");
             Err("Translation model initialization timed out".to_string())
@@ -1092,20 +2233,95 @@ fn setup_translation_model(app_handle: tauri::AppHandle) -> Result<(), String> {
 /// Stores the currently active hotkey string.
 pub struct CurrentHotkey(pub std::sync::Mutex<String>);
 
-#[tauri::command]
-fn get_hotkey(current_hotkey: tauri::State<'_, CurrentHotkey>) -> String {
-    current_hotkey.0.lock().unwrap().clone()
-}
+/// When the record hotkey was last pressed, for the double-tap-to-cancel gesture.
+pub struct LastHotkeyPress(pub std::sync::Mutex<Option<std::time::Instant>>);
 
-#[tauri::command]
-fn set_hotkey(
-    app: tauri::AppHandle,
-    current_hotkey: tauri::State<'_, CurrentHotkey>,
-    new_hotkey: String,
-) -> Result<(), String> {
-    let old_hotkey = current_hotkey.0.lock().unwrap().clone();
+/// A second hotkey press within this window cancels the recording instead of stopping it.
+const DOUBLE_TAP_WINDOW_MS: u64 = 300;
 
-    // Unregister the old shortcut
+/// Handles a press of the record hotkey. With `double_tap_cancel` on, a press that
+/// would stop a recording waits out the double-tap window first, so a quick second
+/// press can cancel instead.
+fn on_hotkey_pressed(app_handle: &tauri::AppHandle) {
+    let (double_tap_cancel, recording) = {
+        let shared_state = app_handle.state::<SharedState>();
+        let state = shared_state.lock();
+        (
+            state.double_tap_cancel,
+            matches!(state.dictation_state, DictationState::Recording { .. }),
+        )
+    };
+    if !double_tap_cancel {
+        toggle_recording(app_handle);
+        return;
+    }
+
+    let window = std::time::Duration::from_millis(DOUBLE_TAP_WINDOW_MS);
+    let now = std::time::Instant::now();
+    let previous = app_handle
+        .state::<LastHotkeyPress>()
+        .0
+        .lock()
+        .unwrap()
+        .replace(now);
+
+    if !recording {
+        toggle_recording(app_handle);
+        return;
+    }
+    if previous.is_some_and(|previous| now.duration_since(previous) < window) {
+        *app_handle.state::<LastHotkeyPress>().0.lock().unwrap() = None;
+        cancel_recording(app_handle.clone());
+        return;
+    }
+
+    // Stop normally unless another press arrives within the window
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(window);
+        let still_pending = {
+            let last_press = app_handle.state::<LastHotkeyPress>();
+            let mut last_press = last_press.0.lock().unwrap();
+            let pending = *last_press == Some(now);
+            if pending {
+                *last_press = None;
+            }
+            pending
+        };
+        let still_recording = matches!(
+            app_handle.state::<SharedState>().lock().dictation_state,
+            DictationState::Recording { .. }
+        );
+        if still_pending && still_recording {
+            toggle_recording(&app_handle);
+        }
+    });
+}
+
+#[tauri::command]
+fn get_hotkey(current_hotkey: tauri::State<'_, CurrentHotkey>) -> String {
+    current_hotkey.0.lock().unwrap().clone()
+}
+
+/// Checks a hotkey string before it is applied, rejecting unparseable
+/// accelerators and combos reserved by macOS.
+#[tauri::command]
+fn validate_hotkey(shortcut: String) -> Result<(), String> {
+    hotkey::validate(&shortcut).map(|_| ())
+}
+
+#[tauri::command]
+fn set_hotkey(
+    app: tauri::AppHandle,
+    current_hotkey: tauri::State<'_, CurrentHotkey>,
+    new_hotkey: String,
+) -> Result<(), String> {
+    // Validate before touching the old registration so a bad hotkey can't leave nothing bound
+    validate_hotkey(new_hotkey.clone())?;
+
+    let old_hotkey = current_hotkey.0.lock().unwrap().clone();
+
+    // Unregister the old shortcut
     let gs = app.global_shortcut();
     if gs.is_registered(old_hotkey.as_str()) {
         gs.unregister(old_hotkey.as_str())
@@ -1115,14 +2331,14 @@ fn set_hotkey(
     // Register the new shortcut with our handler
     gs.on_shortcut(new_hotkey.as_str(), |app, _shortcut, event| {
         if event.state == ShortcutState::Pressed {
-            toggle_recording(app);
+            on_hotkey_pressed(app);
         }
     })
     .map_err(|e| {
         // Re-register the old shortcut on failure
         let _ = gs.on_shortcut(old_hotkey.as_str(), |app, _shortcut, event| {
             if event.state == ShortcutState::Pressed {
-                toggle_recording(app);
+                on_hotkey_pressed(app);
             }
         });
         format!("Failed to register new hotkey '{}': {}", new_hotkey, e)
@@ -1154,83 +2370,1505 @@ fn get_models(shared_state: tauri::State<'_, SharedState>) -> Vec<ModelInfoPaylo
     let state = shared_state.lock();
     let selected = &state.selected_model;
 
-    transcription::model_manager::AVAILABLE_MODELS
-        .iter()
-        .map(|m| ModelInfoPayload {
-            name: m.name.to_string(),
-            size_mb: m.size_mb,
-            description: m.description.to_string(),
-            downloaded: transcription::model_manager::model_exists(m.name),
-            selected: m.name == selected,
-            english_only: m.english_only,
-        })
-        .collect()
+    transcription::model_manager::AVAILABLE_MODELS
+        .iter()
+        .map(|m| ModelInfoPayload {
+            name: m.name.to_string(),
+            size_mb: m.size_mb,
+            description: m.description.to_string(),
+            downloaded: transcription::model_manager::model_exists(m.name),
+            selected: m.name == selected,
+            english_only: m.english_only,
+        })
+        .chain(state.custom_model_path.as_ref().map(|path| {
+            let size_mb = std::fs::metadata(path)
+                .map(|m| (m.len() / (1024 * 1024)) as u32)
+                .unwrap_or(0);
+            ModelInfoPayload {
+                name: transcription::model_manager::CUSTOM_MODEL_NAME.to_string(),
+                size_mb,
+                description: path.clone(),
+                downloaded: std::path::Path::new(path).is_file(),
+                selected: selected == transcription::model_manager::CUSTOM_MODEL_NAME,
+                english_only: false,
+            }
+        }))
+        .collect()
+}
+
+#[tauri::command]
+async fn select_model(app: tauri::AppHandle, model_name: String) -> Result<(), String> {
+    // Validate model name; "custom" is only valid once a custom model has been set
+    let valid = transcription::model_manager::AVAILABLE_MODELS
+        .iter()
+        .any(|m| m.name == model_name)
+        || (model_name == transcription::model_manager::CUSTOM_MODEL_NAME
+            && app
+                .state::<SharedState>()
+                .lock()
+                .custom_model_path
+                .is_some());
+    if !valid {
+        return Err(format!("Unknown model: {}", model_name));
+    }
+
+    // Update selected_model in state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.selected_model = model_name.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.selected_model = model_name.clone();
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Download + load the model in a blocking thread
+    let app_clone = app.clone();
+    tokio::task::spawn_blocking(move || {
+        setup_model(app_clone);
+    })
+    .await
+    .map_err(|e| format!("Model setup failed: {}", e))?;
+
+    // Emit event so the settings UI can refresh
+    let _ = app.emit("model-changed", ());
+
+    Ok(())
+}
+
+/// Switches to the next or previous model in the list, skipping models that
+/// aren't downloaded (unless `download` is set and offline mode is off) and
+/// English-only models when the language isn't English. Returns the new model.
+async fn cycle_model(
+    app: tauri::AppHandle,
+    forward: bool,
+    download: bool,
+) -> Result<String, String> {
+    let (current, language, offline_mode) = {
+        let shared_state = app.state::<SharedState>();
+        let state = shared_state.lock();
+        (
+            state.selected_model.clone(),
+            state.language.clone(),
+            state.offline_mode,
+        )
+    };
+    let allow_download = download && !offline_mode;
+
+    let next = transcription::model_manager::cycle_model(&current, forward, |m| {
+        (language::base(&language) == "en" || !m.english_only)
+            && (allow_download || transcription::model_manager::model_exists(m.name))
+    })
+    .ok_or_else(|| "No other compatible model to switch to".to_string())?;
+
+    select_model(app, next.to_string()).await?;
+    Ok(next.to_string())
+}
+
+#[tauri::command]
+async fn next_model(app: tauri::AppHandle, download: Option<bool>) -> Result<String, String> {
+    cycle_model(app, true, download.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn previous_model(app: tauri::AppHandle, download: Option<bool>) -> Result<String, String> {
+    cycle_model(app, false, download.unwrap_or(false)).await
+}
+
+/// Selects a user-provided ggml model file, bypassing the built-in model list
+/// and download flow.
+#[tauri::command]
+async fn set_custom_model(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    if !std::path::Path::new(&path).is_file() {
+        return Err(format!("Model file not found: {}", path));
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.custom_model_path = Some(path.clone());
+        state.selected_model = transcription::model_manager::CUSTOM_MODEL_NAME.to_string();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.custom_model_path = Some(path);
+    cfg.selected_model = transcription::model_manager::CUSTOM_MODEL_NAME.to_string();
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Load the model in a blocking thread
+    let app_clone = app.clone();
+    tokio::task::spawn_blocking(move || {
+        setup_model(app_clone);
+    })
+    .await
+    .map_err(|e| format!("Model setup failed: {}", e))?;
+
+    // Emit event so the settings UI can refresh
+    let _ = app.emit("model-changed", ());
+
+    Ok(())
+}
+
+#[tauri::command]
+fn save_overlay_position(x: f64, y: f64) -> Result<(), String> {
+    let mut cfg = config::load_config();
+    cfg.overlay_x = Some(x);
+    cfg.overlay_y = Some(y);
+    config::save_config(&cfg).map_err(|e| format!("Failed to save position: {}", e))?;
+    Ok(())
+}
+
+/// Forgets the saved overlay position and moves the overlay back to its default
+/// spot, centered near the top of the primary monitor.
+#[tauri::command]
+fn reset_overlay_position(app: tauri::AppHandle) -> Result<(), String> {
+    let mut cfg = config::load_config();
+    cfg.overlay_x = None;
+    cfg.overlay_y = None;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save position: {}", e))?;
+
+    let window = app
+        .get_webview_window("overlay")
+        .ok_or("Overlay window not found")?;
+    let monitor = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .ok_or("No monitor found")?;
+    let scale = monitor.scale_factor();
+    let position = monitor.position().to_logical::<f64>(scale);
+    let size = monitor.size().to_logical::<f64>(scale);
+    let screen = overlay::ScreenRect {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let width = window
+        .outer_size()
+        .map(|size| {
+            size.to_logical::<f64>(window.scale_factor().unwrap_or(scale))
+                .width
+        })
+        .map_err(|e| format!("Failed to read overlay size: {}", e))?;
+    let (x, y) = overlay::default_position(width, &screen);
+    window
+        .set_position(tauri::Position::Logical(tauri::LogicalPosition::new(x, y)))
+        .map_err(|e| format!("Failed to move overlay: {}", e))
+}
+
+#[tauri::command]
+fn save_overlay_size(width: f64, height: f64) -> Result<(), String> {
+    let mut cfg = config::load_config();
+    cfg.overlay_width = Some(width);
+    cfg.overlay_height = Some(height);
+    config::save_config(&cfg).map_err(|e| format!("Failed to save size: {}", e))?;
+    Ok(())
+}
+
+/// Returns an RMS envelope of the whole current recording at `resolution` points
+/// (capped), or of the last finished recording when none is in progress.
+#[tauri::command]
+fn get_waveform(
+    active_capture: tauri::State<'_, ActiveCapture>,
+    last_recording: tauri::State<'_, LastRecording>,
+    resolution: usize,
+) -> Vec<f32> {
+    if let Some(capture) = active_capture.0.lock().unwrap().as_ref() {
+        let buf = capture.buffer().lock().unwrap();
+        return audio::levels::compute_waveform(&buf, resolution);
+    }
+    audio::levels::compute_waveform(&last_recording.0.lock().unwrap().samples, resolution)
+}
+
+#[tauri::command]
+fn get_smart_paste(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().smart_paste
+}
+
+#[tauri::command]
+fn set_smart_paste(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.smart_paste = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.smart_paste = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_target_app(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().target_app.clone()
+}
+
+/// Sets the app (by bundle id) that pastes always go to; `None` or an empty
+/// string goes back to pasting into whatever is focused.
+#[tauri::command]
+fn set_target_app(app: tauri::AppHandle, bundle_id: Option<String>) -> Result<(), String> {
+    let bundle_id = bundle_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.target_app = bundle_id.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.target_app = bundle_id;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+/// Longest wait `capture_target_app` allows for switching to the target app.
+const MAX_CAPTURE_DELAY_MS: u64 = 10_000;
+
+/// Makes the frontmost app the paste target and returns its bundle id. Calling
+/// from Wren's own window makes Wren frontmost, so `delay_ms` gives the user time
+/// to switch to the target app first.
+#[tauri::command]
+async fn capture_target_app(
+    app: tauri::AppHandle,
+    delay_ms: Option<u64>,
+) -> Result<String, String> {
+    let delay_ms = delay_ms.unwrap_or(0).min(MAX_CAPTURE_DELAY_MS);
+    tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+
+    let bundle_id =
+        input::paste::frontmost_app_bundle_id().ok_or("Couldn't read the frontmost app")?;
+    if bundle_id == app.config().identifier {
+        return Err("Wren is the frontmost app; switch to the target app first".to_string());
+    }
+    set_target_app(app, Some(bundle_id.clone()))?;
+    Ok(bundle_id)
+}
+
+#[tauri::command]
+fn get_show_overlay(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().show_overlay
+}
+
+/// Turns the floating overlay on or off. With the overlay off, dictation goes
+/// straight to paste/clipboard and correction/translation previews are
+/// auto-accepted, and errors are posted as system notifications instead.
+#[tauri::command]
+fn set_show_overlay(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.show_overlay = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.show_overlay = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    if !enabled {
+        hide_overlay(&app);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_overlay_show_delay_ms(shared_state: tauri::State<'_, SharedState>) -> u64 {
+    shared_state.lock().overlay_show_delay_ms
+}
+
+#[tauri::command]
+fn set_overlay_show_delay_ms(app: tauri::AppHandle, delay_ms: u64) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.overlay_show_delay_ms = delay_ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.overlay_show_delay_ms = delay_ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_guard_enter_while_recording(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().guard_enter_while_recording
+}
+
+#[tauri::command]
+fn set_guard_enter_while_recording(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state; takes effect from the next state change
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.guard_enter_while_recording = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.guard_enter_while_recording = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_intercepted_preview_keys(shared_state: tauri::State<'_, SharedState>) -> Vec<String> {
+    shared_state.lock().intercepted_preview_keys.clone()
+}
+
+#[tauri::command]
+fn set_intercepted_preview_keys(app: tauri::AppHandle, keys: Vec<String>) -> Result<(), String> {
+    if let Some(unknown) = keys
+        .iter()
+        .find(|key| !PREVIEW_KEYS.iter().any(|(_, name)| name == key))
+    {
+        return Err(format!("Unknown preview key: {}", unknown));
+    }
+
+    // Update in-memory state; takes effect the next time a preview shows
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.intercepted_preview_keys = keys.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.intercepted_preview_keys = keys;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct EncryptionStatus {
+    enabled: bool,
+    unlocked: bool,
+}
+
+#[tauri::command]
+fn get_encryption_status() -> EncryptionStatus {
+    EncryptionStatus {
+        enabled: crypto::is_enabled(),
+        unlocked: crypto::is_unlocked(),
+    }
+}
+
+/// Unlocks encrypted history and vocabulary for this session.
+#[tauri::command]
+async fn unlock_data(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    let cfg = config::load_config();
+    let (Some(salt), Some(verifier)) = (cfg.encryption_salt, cfg.encryption_verifier) else {
+        return Err("Encryption is not set up".to_string());
+    };
+    // Key derivation is deliberately slow; keep it off the main thread
+    tokio::task::spawn_blocking(move || crypto::unlock(&passphrase, &salt, &verifier))
+        .await
+        .map_err(|e| format!("Unlock failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+
+    let _ = app.emit("history-updated", ());
+    Ok(())
+}
+
+/// Turns on at-rest encryption and re-saves history and vocabulary encrypted.
+#[tauri::command]
+async fn enable_encryption(app: tauri::AppHandle, passphrase: String) -> Result<(), String> {
+    if crypto::is_enabled() {
+        return Err("Encryption is already on".to_string());
+    }
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let history = history::load_history();
+        let vocabulary = vocabulary::load_vocabulary();
+
+        let (salt, verifier) = crypto::setup(&passphrase).map_err(|e| e.to_string())?;
+        let saved = history::save_history(&history)
+            .and_then(|_| vocabulary::save_vocabulary(&vocabulary))
+            .and_then(|_| {
+                let mut cfg = config::load_config();
+                cfg.encrypt_data = true;
+                cfg.encryption_salt = Some(salt);
+                cfg.encryption_verifier = Some(verifier);
+                config::save_config(&cfg)
+            });
+        if let Err(e) = saved {
+            // Put back whatever was already written encrypted
+            crypto::disable();
+            let _ = history::save_history(&history);
+            let _ = vocabulary::save_vocabulary(&vocabulary);
+            return Err(format!("Failed to encrypt data: {}", e));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to encrypt data: {}", e))??;
+
+    let _ = app.emit("history-updated", ());
+    Ok(())
+}
+
+/// Turns off at-rest encryption, writing history and vocabulary back as
+/// plaintext. Requires the data to be unlocked first.
+#[tauri::command]
+fn disable_encryption(app: tauri::AppHandle) -> Result<(), String> {
+    if !crypto::is_enabled() {
+        return Ok(());
+    }
+    if !crypto::is_unlocked() {
+        return Err("Unlock your data before turning encryption off".to_string());
+    }
+
+    let history = history::load_history();
+    let vocabulary = vocabulary::load_vocabulary();
+    crypto::disable();
+    history::save_history(&history).map_err(|e| format!("Failed to save history: {}", e))?;
+    vocabulary::save_vocabulary(&vocabulary)
+        .map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+
+    let mut cfg = config::load_config();
+    cfg.encrypt_data = false;
+    cfg.encryption_salt = None;
+    cfg.encryption_verifier = None;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    let _ = app.emit("history-updated", ());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_history_enabled(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().history_enabled
+}
+
+#[tauri::command]
+fn set_history_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.history_enabled = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.history_enabled = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    let _ = app.emit("history-updated", ());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_double_tap_cancel(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().double_tap_cancel
+}
+
+#[tauri::command]
+fn set_double_tap_cancel(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.double_tap_cancel = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.double_tap_cancel = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_correction_stage(shared_state: tauri::State<'_, SharedState>) -> CorrectionStage {
+    shared_state.lock().correction_stage
+}
+
+#[tauri::command]
+fn set_correction_stage(app: tauri::AppHandle, stage: CorrectionStage) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.correction_stage = stage;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.correction_stage = stage;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_hotkey_during_processing(
+    shared_state: tauri::State<'_, SharedState>,
+) -> HotkeyDuringProcessing {
+    shared_state.lock().hotkey_during_processing
+}
+
+#[tauri::command]
+fn set_hotkey_during_processing(
+    app: tauri::AppHandle,
+    action: HotkeyDuringProcessing,
+) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.hotkey_during_processing = action;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.hotkey_during_processing = action;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_quiet_hours(shared_state: tauri::State<'_, SharedState>) -> quiet_hours::QuietHours {
+    shared_state.lock().quiet_hours.clone()
+}
+
+#[tauri::command]
+fn set_quiet_hours(
+    app: tauri::AppHandle,
+    quiet_hours: quiet_hours::QuietHours,
+) -> Result<(), String> {
+    quiet_hours.validate()?;
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.quiet_hours = quiet_hours.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.quiet_hours = quiet_hours;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_quiet_hours_active(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().quiet_hours_active()
+}
+
+/// Lets recordings through despite quiet hours for the next `minutes`; 0 ends
+/// an override early. Not persisted.
+#[tauri::command]
+fn override_quiet_hours(shared_state: tauri::State<'_, SharedState>, minutes: u64) {
+    shared_state.lock().quiet_hours_override_until = (minutes > 0)
+        .then(|| std::time::Instant::now() + std::time::Duration::from_secs(minutes * 60));
+}
+
+#[tauri::command]
+fn get_auto_append_file(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().auto_append_file.clone()
+}
+
+/// Sets the path template transcriptions are appended to, e.g.
+/// `~/Journal/%Y-%m-%d.md`. An empty or `None` path turns it off.
+#[tauri::command]
+fn set_auto_append_file(app: tauri::AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = path.filter(|p| !p.trim().is_empty());
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.auto_append_file = path.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.auto_append_file = path;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_processing_timeout_ms(shared_state: tauri::State<'_, SharedState>) -> u64 {
+    shared_state.lock().processing_timeout_ms
+}
+
+#[tauri::command]
+fn set_processing_timeout_ms(app: tauri::AppHandle, timeout_ms: u64) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.processing_timeout_ms = timeout_ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.processing_timeout_ms = timeout_ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_offline_mode(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().offline_mode
+}
+
+#[tauri::command]
+fn set_offline_mode(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.offline_mode = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.offline_mode = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_metrics_logging(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().metrics_logging
+}
+
+#[tauri::command]
+fn set_metrics_logging(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.metrics_logging = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.metrics_logging = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_notify_on_complete(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().notify_on_complete
+}
+
+#[tauri::command]
+fn set_notify_on_complete(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.notify_on_complete = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.notify_on_complete = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_append_mode(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().append_mode
+}
+
+/// Turns append mode on or off. Turning it off keeps any buffered text until it
+/// is flushed or cleared.
+#[tauri::command]
+fn set_append_mode(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.append_mode = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.append_mode = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_append_buffer(shared_state: tauri::State<'_, SharedState>) -> String {
+    shared_state.lock().append_buffer.clone()
+}
+
+/// Pastes the accumulated append-mode text (honoring smart paste) and empties the buffer.
+/// Called from Wren's own window, Wren steps aside first so the text lands in the
+/// app the user was in; if the paste fails, the text goes back into the buffer.
+#[tauri::command]
+fn flush_append_buffer(app: tauri::AppHandle) -> Result<(), String> {
+    let (text, smart_paste, max_paste_chars, target_app) = {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        (
+            std::mem::take(&mut state.append_buffer),
+            state.smart_paste,
+            state.max_paste_chars,
+            state.target_app.clone(),
+        )
+    };
+    if text.is_empty() {
+        return Ok(());
+    }
+    let pasted = limit_paste_length(&app, text.clone(), max_paste_chars);
+
+    let app_for_paste = app.clone();
+    app.run_on_main_thread(move || {
+        let yielded = input::paste::yield_focus();
+        let result = input::paste::paste_text(&pasted, smart_paste, target_app.as_deref());
+        if yielded {
+            input::paste::unhide_wren();
+        }
+        if let Err(e) = result {
+            log::error!("Failed to paste text: {}", e);
+            // Keep the text, ahead of anything dictated since the flush began
+            {
+                let shared_state = app_for_paste.state::<SharedState>();
+                let mut state = shared_state.lock();
+                state.append_buffer = append::join_dictation(&text, &state.append_buffer);
+            }
+            set_error_state(&app_for_paste, format!("Failed to paste: {}", e));
+            show_overlay(&app_for_paste);
+        }
+    })
+    .map_err(|e| format!("Failed to run on main thread: {}", e))
+}
+
+#[tauri::command]
+fn copy_append_buffer(shared_state: tauri::State<'_, SharedState>) -> Result<(), String> {
+    let text = shared_state.lock().append_buffer.clone();
+    copy_to_clipboard(text)
+}
+
+#[tauri::command]
+fn clear_append_buffer(shared_state: tauri::State<'_, SharedState>) {
+    shared_state.lock().append_buffer.clear();
+}
+
+#[tauri::command]
+fn get_error_auto_dismiss_ms(shared_state: tauri::State<'_, SharedState>) -> u64 {
+    shared_state.lock().error_auto_dismiss_ms
+}
+
+#[tauri::command]
+fn set_error_auto_dismiss_ms(app: tauri::AppHandle, ms: u64) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.error_auto_dismiss_ms = ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.error_auto_dismiss_ms = ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_auto_accept_corrections(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().auto_accept_corrections
+}
+
+#[tauri::command]
+fn set_auto_accept_corrections(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.auto_accept_corrections = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.auto_accept_corrections = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_auto_accept_translation(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().auto_accept_translation
+}
+
+#[tauri::command]
+fn set_auto_accept_translation(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.auto_accept_translation = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.auto_accept_translation = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_min_word_chars(shared_state: tauri::State<'_, SharedState>) -> usize {
+    shared_state.lock().min_word_chars
+}
+
+#[tauri::command]
+fn set_min_word_chars(app: tauri::AppHandle, min_word_chars: usize) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.min_word_chars = min_word_chars;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.min_word_chars = min_word_chars;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_min_recording_ms(shared_state: tauri::State<'_, SharedState>) -> u64 {
+    shared_state.lock().min_recording_ms
+}
+
+#[tauri::command]
+fn set_min_recording_ms(app: tauri::AppHandle, min_recording_ms: u64) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.min_recording_ms = min_recording_ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.min_recording_ms = min_recording_ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_trim_silence(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().trim_silence
+}
+
+#[tauri::command]
+fn set_trim_silence(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.trim_silence = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.trim_silence = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_partial_window_secs(shared_state: tauri::State<'_, SharedState>) -> f32 {
+    shared_state.lock().partial_window_secs
+}
+
+#[tauri::command]
+fn set_partial_window_secs(app: tauri::AppHandle, secs: f32) -> Result<(), String> {
+    if !secs.is_finite() || secs < 1.0 {
+        return Err("Partial window must be at least 1 second".to_string());
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.partial_window_secs = secs;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.partial_window_secs = secs;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_partial_model(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().partial_model.clone()
+}
+
+/// Sets a separate, usually smaller, model for live partials; `None` uses the
+/// selected model for both. The model must already be downloaded.
+#[tauri::command]
+async fn set_partial_model(app: tauri::AppHandle, model: Option<String>) -> Result<(), String> {
+    if let Some(name) = &model {
+        if !transcription::model_manager::AVAILABLE_MODELS
+            .iter()
+            .any(|m| m.name == name.as_str())
+        {
+            return Err(format!("Unknown model: {}", name));
+        }
+        if !transcription::model_manager::model_exists(name) {
+            return Err(format!(
+                "Download the {} model before using it for partials",
+                name
+            ));
+        }
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.partial_model = model.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.partial_model = model;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    tokio::task::spawn_blocking(move || sync_partial_model(&app))
+        .await
+        .map_err(|e| format!("Partial model setup failed: {}", e))?
+}
+
+#[tauri::command]
+fn get_use_gpu(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().use_gpu
+}
+
+/// Turns GPU (Metal) acceleration for whisper on or off, reloading the loaded
+/// models so it takes effect immediately.
+#[tauri::command]
+async fn set_use_gpu(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.use_gpu = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.use_gpu = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Reloading can take a while for large models; wait off the main thread
+    tokio::task::spawn_blocking(move || {
+        let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+        {
+            let tx = app.state::<TranscriptionSender>();
+            let tx = tx.0.lock().unwrap();
+            tx.send(TranscriptionRequest::SetUseGpu(enabled, reply_tx))
+                .map_err(|_| "Transcription thread is not running".to_string())?;
+        }
+        reply_rx
+            .recv_timeout(std::time::Duration::from_secs(60))
+            .map_err(|_| "Reloading the model timed out".to_string())?
+    })
+    .await
+    .map_err(|e| format!("Model reload failed: {}", e))?
+}
+
+#[tauri::command]
+fn get_paragraph_on_pause(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().paragraph_on_pause
+}
+
+#[tauri::command]
+fn set_paragraph_on_pause(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.paragraph_on_pause = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.paragraph_on_pause = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Send to transcription thread
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetParagraphOnPause(enabled));
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+struct WhisperThreads {
+    /// The setting; 0 means all available CPUs.
+    threads: usize,
+    available: usize,
+}
+
+#[tauri::command]
+fn get_whisper_threads(shared_state: tauri::State<'_, SharedState>) -> WhisperThreads {
+    WhisperThreads {
+        threads: shared_state.lock().whisper_threads,
+        available: transcription::whisper::available_threads(),
+    }
+}
+
+/// Sets how many threads whisper decodes with, trading latency for CPU load.
+/// 0 uses every available CPU; other values are clamped to the CPU count.
+#[tauri::command]
+fn set_whisper_threads(app: tauri::AppHandle, threads: usize) -> Result<(), String> {
+    let threads = threads.min(transcription::whisper::available_threads());
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.whisper_threads = threads;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.whisper_threads = threads;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Send to transcription thread
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetThreads(threads));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_strip_nonspeech_tags(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().strip_nonspeech_tags
+}
+
+#[tauri::command]
+fn set_strip_nonspeech_tags(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.strip_nonspeech_tags = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.strip_nonspeech_tags = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    // Send to transcription thread
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetStripNonspeechTags(enabled));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_correct_partials(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().correct_partials
+}
+
+#[tauri::command]
+fn set_correct_partials(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.correct_partials = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.correct_partials = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+struct RecordingLimit {
+    max_recording_ms: u64,
+    warning_ms: u64,
+}
+
+#[tauri::command]
+fn get_recording_limit(shared_state: tauri::State<'_, SharedState>) -> RecordingLimit {
+    let state = shared_state.lock();
+    RecordingLimit {
+        max_recording_ms: state.max_recording_ms,
+        warning_ms: state.max_recording_warning_ms,
+    }
+}
+
+/// Sets the auto-stop length (0 for no limit) and how long before it the
+/// 'recording-warning' countdown starts. Applies from the next recording.
+#[tauri::command]
+fn set_recording_limit(
+    app: tauri::AppHandle,
+    max_recording_ms: u64,
+    warning_ms: u64,
+) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.max_recording_ms = max_recording_ms;
+        state.max_recording_warning_ms = warning_ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.max_recording_ms = max_recording_ms;
+    cfg.max_recording_warning_ms = warning_ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_too_quiet_threshold(shared_state: tauri::State<'_, SharedState>) -> f32 {
+    shared_state.lock().too_quiet_threshold
+}
+
+#[tauri::command]
+fn set_too_quiet_threshold(app: tauri::AppHandle, threshold: f32) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("Threshold must be between 0 and 1".to_string());
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.too_quiet_threshold = threshold;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.too_quiet_threshold = threshold;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_vad_enabled(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().vad_enabled
+}
+
+/// Turns hands-free auto-stop on or off. Applies from the next recording.
+#[tauri::command]
+fn set_vad_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.vad_enabled = enabled;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.vad_enabled = enabled;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_silence_timeout_ms(shared_state: tauri::State<'_, SharedState>) -> u64 {
+    shared_state.lock().silence_timeout_ms
+}
+
+/// Shortest silence `set_silence_timeout_ms` accepts, so a breath between words
+/// doesn't end the recording.
+const MIN_SILENCE_TIMEOUT_MS: u64 = 300;
+
+#[tauri::command]
+fn set_silence_timeout_ms(app: tauri::AppHandle, timeout_ms: u64) -> Result<(), String> {
+    if timeout_ms < MIN_SILENCE_TIMEOUT_MS {
+        return Err(format!(
+            "Silence timeout must be at least {}ms",
+            MIN_SILENCE_TIMEOUT_MS
+        ));
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.silence_timeout_ms = timeout_ms;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.silence_timeout_ms = timeout_ms;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_max_paste_chars(shared_state: tauri::State<'_, SharedState>) -> usize {
+    shared_state.lock().max_paste_chars
+}
+
+#[tauri::command]
+fn set_max_paste_chars(app: tauri::AppHandle, max_chars: usize) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.max_paste_chars = max_chars;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.max_paste_chars = max_chars;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_formatting_profile(
+    shared_state: tauri::State<'_, SharedState>,
+) -> formatting::FormattingProfile {
+    shared_state.lock().formatting_profile
+}
+
+#[tauri::command]
+fn set_formatting_profile(
+    app: tauri::AppHandle,
+    profile: formatting::FormattingProfile,
+) -> Result<(), String> {
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.formatting_profile = profile;
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.formatting_profile = profile;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_formatting_rules(
+    shared_state: tauri::State<'_, SharedState>,
+) -> Vec<formatting::FormattingRule> {
+    shared_state.lock().formatting_rules.clone()
+}
+
+#[tauri::command]
+fn set_formatting_rules(
+    app: tauri::AppHandle,
+    rules: Vec<formatting::FormattingRule>,
+) -> Result<(), String> {
+    if rules.iter().any(|rule| rule.phrase.trim().is_empty()) {
+        return Err("Formatting phrases cannot be empty".to_string());
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.formatting_rules = rules.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.formatting_rules = rules;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+struct LocalApiSettings {
+    enabled: bool,
+    port: u16,
+    token: Option<String>,
+}
+
+#[tauri::command]
+fn get_local_api_settings() -> LocalApiSettings {
+    let cfg = config::load_config();
+    LocalApiSettings {
+        enabled: cfg.enable_local_api,
+        port: cfg.local_api_port,
+        token: cfg.local_api_token,
+    }
+}
+
+#[tauri::command]
+fn set_local_api_settings(
+    app: tauri::AppHandle,
+    enabled: bool,
+    port: u16,
+) -> Result<LocalApiSettings, String> {
+    if port < 1024 {
+        return Err("Port must be between 1024 and 65535".to_string());
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.enable_local_api = enabled;
+    cfg.local_api_port = port;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    restart_local_api(&app)?;
+    Ok(get_local_api_settings())
+}
+
+#[tauri::command]
+fn regenerate_local_api_token(app: tauri::AppHandle) -> Result<String, String> {
+    let token = api::generate_token();
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.local_api_token = Some(token.clone());
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    restart_local_api(&app)?;
+    Ok(token)
+}
+
+#[tauri::command]
+fn get_whisper_thresholds(shared_state: tauri::State<'_, SharedState>) -> WhisperThresholds {
+    shared_state.lock().whisper_thresholds
 }
 
+/// Updates whisper's speech/no-speech decoder thresholds. Values are clamped to valid ranges.
 #[tauri::command]
-async fn select_model(app: tauri::AppHandle, model_name: String) -> Result<(), String> {
-    // Validate model name
-    let valid = transcription::model_manager::AVAILABLE_MODELS
-        .iter()
-        .any(|m| m.name == model_name);
-    if !valid {
-        return Err(format!("Unknown model: {}", model_name));
-    }
+fn set_whisper_thresholds(
+    app: tauri::AppHandle,
+    thresholds: WhisperThresholds,
+) -> Result<(), String> {
+    let thresholds = thresholds.clamped();
 
-    // Update selected_model in state
+    // Update in-memory state
     {
         let shared_state = app.state::<SharedState>();
         let mut state = shared_state.lock();
-        state.selected_model = model_name.clone();
+        state.whisper_thresholds = thresholds;
     }
 
     // Persist to config
     let mut cfg = config::load_config();
-    cfg.selected_model = model_name.clone();
+    cfg.no_speech_threshold = thresholds.no_speech_threshold;
+    cfg.temperature = thresholds.temperature;
+    cfg.entropy_threshold = thresholds.entropy_threshold;
     config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
 
-    // Download + load the model in a blocking thread
-    let app_clone = app.clone();
-    tokio::task::spawn_blocking(move || {
-        setup_model(app_clone);
-    })
-    .await
-    .map_err(|e| format!("Model setup failed: {}", e))?;
-
-    // Emit event so the settings UI can refresh
-    let _ = app.emit("model-changed", ());
+    // Send to transcription thread
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetThresholds(thresholds));
+    }
 
     Ok(())
 }
 
 #[tauri::command]
-fn save_overlay_position(x: f64, y: f64) -> Result<(), String> {
-    let mut cfg = config::load_config();
-    cfg.overlay_x = Some(x);
-    cfg.overlay_y = Some(y);
-    config::save_config(&cfg).map_err(|e| format!("Failed to save position: {}", e))?;
-    Ok(())
+fn get_whisper_sampling(shared_state: tauri::State<'_, SharedState>) -> WhisperSampling {
+    shared_state.lock().whisper_sampling
 }
 
+/// Updates `best_of` and the temperature-fallback count for final transcriptions.
+/// Partials always use the fast profile.
 #[tauri::command]
-fn get_smart_paste(shared_state: tauri::State<'_, SharedState>) -> bool {
-    shared_state.lock().smart_paste
-}
+fn set_whisper_sampling(app: tauri::AppHandle, sampling: WhisperSampling) -> Result<(), String> {
+    let sampling = sampling.validated()?;
 
-#[tauri::command]
-fn set_smart_paste(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
     // Update in-memory state
     {
         let shared_state = app.state::<SharedState>();
         let mut state = shared_state.lock();
-        state.smart_paste = enabled;
+        state.whisper_sampling = sampling;
     }
 
     // Persist to config
     let mut cfg = config::load_config();
-    cfg.smart_paste = enabled;
+    cfg.best_of = sampling.best_of;
+    cfg.temperature_fallbacks = sampling.temperature_fallbacks;
     config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
 
+    // Send to transcription thread
+    {
+        let tx = app.state::<TranscriptionSender>();
+        let tx = tx.0.lock().unwrap();
+        let _ = tx.send(TranscriptionRequest::SetSampling(sampling));
+    }
+
     Ok(())
 }
 
@@ -1256,6 +3894,31 @@ fn set_vocab_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String>
     Ok(())
 }
 
+/// Re-downloads the translation model file that `find_bad_file` reports as
+/// missing or corrupt, then loads the model again. Returns the repaired file's
+/// name, or `None` if every file was fine.
+#[tauri::command]
+fn repair_translation_model(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let (model_name, offline_mode) = {
+        let shared_state = app.state::<SharedState>();
+        let state = shared_state.lock();
+        (state.translation_model.clone(), state.offline_mode)
+    };
+    let Some((filename, reason)) = translation::model_manager::find_bad_file(&model_name) else {
+        return Ok(None);
+    };
+    // Keep the damaged file rather than deleting it with no way to fetch it again
+    if offline_mode {
+        return Err(format!("{} — connect to download it again", reason));
+    }
+
+    log::info!("Repairing translation model: {}", reason);
+    translation::model_manager::remove_model_file(&model_name, filename)
+        .map_err(|e| format!("Failed to remove {}: {}", filename, e))?;
+    setup_translation_model(app)?;
+    Ok(Some(filename.to_string()))
+}
+
 #[tauri::command]
 fn get_translation_enabled(shared_state: tauri::State<'_, SharedState>) -> bool {
     shared_state.lock().translation_enabled
@@ -1287,11 +3950,42 @@ fn get_translation_target_lang(shared_state: tauri::State<'_, SharedState>) -> S
 }
 
 #[tauri::command]
-fn set_translation_target_lang(app: tauri::AppHandle, target_lang: String) -> Result<(), String> {
+fn get_translation_target_variant(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().translation_target_variant.clone()
+}
+
+/// Lists the NLLB variants the target language can be written in (empty if only one).
+#[tauri::command]
+fn get_translation_target_variants(target_lang: String) -> Vec<&'static str> {
+    translation::engine::target_variants(&target_lang).to_vec()
+}
+
+/// Sets the target language and, for languages with several written variants,
+/// which one to use. A `variant` of `None` uses the language's default.
+#[tauri::command]
+fn set_translation_target_lang(
+    app: tauri::AppHandle,
+    target_lang: String,
+    variant: Option<String>,
+) -> Result<(), String> {
+    let target_lang = normalize_language(&target_lang)?;
+    if target_lang == "auto" {
+        return Err("The translation target can't be auto-detected".to_string());
+    }
+    if let Some(variant) = &variant {
+        if !translation::engine::target_variants(&target_lang).contains(&variant.as_str()) {
+            return Err(format!(
+                "'{}' is not a variant of '{}'",
+                variant, target_lang
+            ));
+        }
+    }
+
     let updated_state = {
         let shared_state = app.state::<SharedState>();
         let mut state = shared_state.lock();
         state.translation_target_lang = target_lang.clone();
+        state.translation_target_variant = variant.clone();
         if let DictationState::Recording {
             duration_ms,
             partial_text,
@@ -1315,6 +4009,7 @@ fn set_translation_target_lang(app: tauri::AppHandle, target_lang: String) -> Re
 
     let mut cfg = config::load_config();
     cfg.translation_target_lang = target_lang;
+    cfg.translation_target_variant = variant;
     config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
 
     sync_translation_languages(&app);
@@ -1330,7 +4025,27 @@ fn get_vocabulary() -> Vec<vocabulary::VocabEntry> {
 }
 
 #[tauri::command]
-fn add_vocab_entry(phrase: String, replacement: String) -> Result<(), String> {
+fn validate_vocab_pattern(
+    phrase: String,
+    is_regex: bool,
+    replacement: Option<String>,
+) -> Result<(), String> {
+    vocabulary::validate_pattern(&phrase, is_regex, replacement.as_deref().unwrap_or(""))
+}
+
+#[tauri::command]
+fn add_vocab_entry(
+    phrase: String,
+    replacement: String,
+    match_mode: Option<vocabulary::MatchMode>,
+    priority: Option<i32>,
+    fuzzy: Option<bool>,
+    is_regex: Option<bool>,
+) -> Result<(), String> {
+    let is_regex = is_regex.unwrap_or_default();
+    if is_regex {
+        vocabulary::validate_pattern(&phrase, true, &replacement)?;
+    }
     let timestamp_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -1340,19 +4055,73 @@ fn add_vocab_entry(phrase: String, replacement: String) -> Result<(), String> {
         phrase,
         replacement,
         enabled: true,
+        match_mode: match_mode.unwrap_or_default(),
+        priority: priority.unwrap_or_default(),
+        fuzzy: fuzzy.unwrap_or_default(),
+        is_regex,
     };
     vocabulary::add_entry(entry).map_err(|e| format!("Failed to add vocab entry: {}", e))
 }
 
+/// Imports a two-column "phrase,replacement" CSV (e.g. an autocorrect list) into
+/// the vocabulary. With `merge`, entries are added to the existing vocabulary;
+/// otherwise they replace it.
 #[tauri::command]
-fn update_vocab_entry(
-    id: u64,
-    phrase: String,
-    replacement: String,
-    enabled: bool,
-) -> Result<(), String> {
-    vocabulary::update_entry(id, phrase, replacement, enabled)
-        .map_err(|e| format!("Failed to update vocab entry: {}", e))
+fn import_vocabulary_csv(path: String, merge: bool) -> Result<vocabulary::CsvImportResult, String> {
+    let contents =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (pairs, skipped_lines) = vocabulary::parse_csv(&contents);
+    if pairs.is_empty() {
+        return Err("No phrase,replacement lines found".to_string());
+    }
+
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let mut vocab = vocabulary::load_vocabulary();
+    let imported = vocabulary::import_pairs(&mut vocab, pairs, merge, timestamp_ms);
+    vocabulary::save_vocabulary(&vocab).map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+
+    if !skipped_lines.is_empty() {
+        log::warn!(
+            "Skipped malformed vocabulary CSV lines: {:?}",
+            skipped_lines
+        );
+    }
+    Ok(vocabulary::CsvImportResult {
+        imported,
+        skipped_lines,
+    })
+}
+
+/// Replaces the entry with the same id. Takes the whole entry, so settings the
+/// caller isn't changing are passed back as they were rather than reset.
+#[tauri::command]
+fn update_vocab_entry(entry: vocabulary::VocabEntry) -> Result<(), String> {
+    if entry.is_regex {
+        vocabulary::validate_pattern(&entry.phrase, true, &entry.replacement)?;
+    }
+    vocabulary::update_entry(entry).map_err(|e| format!("Failed to update vocab entry: {}", e))
+}
+
+#[tauri::command]
+fn get_fuzzy_max_distance() -> usize {
+    vocabulary::load_vocabulary().fuzzy_max_distance
+}
+
+/// Sets how many edits fuzzy vocabulary entries tolerate (1 to `MAX_FUZZY_DISTANCE`).
+#[tauri::command]
+fn set_fuzzy_max_distance(distance: usize) -> Result<(), String> {
+    if !(1..=vocabulary::MAX_FUZZY_DISTANCE).contains(&distance) {
+        return Err(format!(
+            "Fuzzy distance must be between 1 and {}",
+            vocabulary::MAX_FUZZY_DISTANCE
+        ));
+    }
+    let mut vocab = vocabulary::load_vocabulary();
+    vocab.fuzzy_max_distance = distance;
+    vocabulary::save_vocabulary(&vocab).map_err(|e| format!("Failed to save vocabulary: {}", e))
 }
 
 #[tauri::command]
@@ -1376,35 +4145,48 @@ fn accept_corrections(
         (text, sp)
     };
 
-    let app_for_paste = app.clone();
-    let text_to_paste = corrected_text;
-    app.run_on_main_thread(move || {
-        if let Err(e) = input::paste::paste_text(&text_to_paste, smart_paste) {
-            log::error!("Failed to paste corrected text: {}", e);
-            let error_state = DictationState::Error {
-                message: format!("Failed to paste: {}", e),
-            };
-            let shared_state = app_for_paste.state::<SharedState>();
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(&app_for_paste, &error_state);
-            return;
-        }
+    paste_and_reset(&app, corrected_text, smart_paste)
+        .map_err(|e| format!("Failed to run on main thread: {}", e))?;
 
-        let shared_state = app_for_paste.state::<SharedState>();
-        {
-            let mut state = shared_state.lock();
-            state.dictation_state = DictationState::Idle;
-        }
-        emit_state(&app_for_paste, &DictationState::Idle);
-        if let Some(window) = app_for_paste.get_webview_window("overlay") {
-            let _ = window.hide();
-        }
-    })
-    .map_err(|e| format!("Failed to run on main thread: {}", e))?;
+    Ok(())
+}
+
+/// Picks which vocabulary entry's replacement to use for the correction at
+/// `position` (a byte offset into the original text) while the preview is up.
+#[tauri::command]
+fn choose_correction(
+    app: tauri::AppHandle,
+    shared_state: tauri::State<'_, SharedState>,
+    position: usize,
+    entry_id: u64,
+) -> Result<(), String> {
+    let (preview_state, text) = {
+        let mut state = shared_state.lock();
+        let DictationState::CorrectionPreview {
+            original_text,
+            corrections,
+            ..
+        } = &state.dictation_state
+        else {
+            return Err("No pending corrections to choose from".to_string());
+        };
+        let original_text = original_text.clone();
+        let mut corrections = corrections.clone();
+        vocabulary::choose_candidate(&mut corrections, position, entry_id)?;
+        let text = vocabulary::splice_corrections(&original_text, &corrections);
+
+        state.pending_corrected_text = Some(text.clone());
+        state.dictation_state = DictationState::CorrectionPreview {
+            text: text.clone(),
+            original_text,
+            corrections,
+        };
+        (state.dictation_state.clone(), text)
+    };
 
+    // History was recorded with the default choice
+    update_history_text(&app, text);
+    emit_state(&app, &preview_state);
     Ok(())
 }
 
@@ -1425,39 +4207,10 @@ fn undo_corrections(
     };
 
     // Update the most recent history entry to use the original text
-    if let Err(e) = history::update_most_recent_text(original_text.clone()) {
-        log::error!("Failed to update history entry: {}", e);
-    }
-    let _ = app.emit("history-updated", ());
-
-    let app_for_paste = app.clone();
-    let text_to_paste = original_text;
-    app.run_on_main_thread(move || {
-        if let Err(e) = input::paste::paste_text(&text_to_paste, smart_paste) {
-            log::error!("Failed to paste original text: {}", e);
-            let error_state = DictationState::Error {
-                message: format!("Failed to paste: {}", e),
-            };
-            let shared_state = app_for_paste.state::<SharedState>();
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(&app_for_paste, &error_state);
-            return;
-        }
+    update_history_text(&app, original_text.clone());
 
-        let shared_state = app_for_paste.state::<SharedState>();
-        {
-            let mut state = shared_state.lock();
-            state.dictation_state = DictationState::Idle;
-        }
-        emit_state(&app_for_paste, &DictationState::Idle);
-        if let Some(window) = app_for_paste.get_webview_window("overlay") {
-            let _ = window.hide();
-        }
-    })
-    .map_err(|e| format!("Failed to run on main thread: {}", e))?;
+    paste_and_reset(&app, original_text, smart_paste)
+        .map_err(|e| format!("Failed to run on main thread: {}", e))?;
 
     Ok(())
 }
@@ -1467,48 +4220,23 @@ fn accept_translation(
     app: tauri::AppHandle,
     shared_state: tauri::State<'_, SharedState>,
 ) -> Result<(), String> {
-    let (translated_text, smart_paste) = {
+    let (source_text, translated_text, smart_paste) = {
         let mut state = shared_state.lock();
         let text = state
             .pending_translated_text
             .take()
             .ok_or_else(|| "No pending translation to accept".to_string())?;
-        state.pending_source_text = None;
-        (text, state.smart_paste)
+        let source_text = state.pending_source_text.take();
+        (source_text, text, state.smart_paste)
     };
 
-    if let Err(e) = history::update_most_recent_text(translated_text.clone()) {
-        log::error!("Failed to update history entry: {}", e);
+    match source_text {
+        Some(source_text) => update_history_translation(&app, source_text, translated_text.clone()),
+        None => update_history_text(&app, translated_text.clone()),
     }
-    let _ = app.emit("history-updated", ());
-
-    let app_for_paste = app.clone();
-    app.run_on_main_thread(move || {
-        if let Err(e) = input::paste::paste_text(&translated_text, smart_paste) {
-            log::error!("Failed to paste translated text: {}", e);
-            let error_state = DictationState::Error {
-                message: format!("Failed to paste: {}", e),
-            };
-            let shared_state = app_for_paste.state::<SharedState>();
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(&app_for_paste, &error_state);
-            return;
-        }
 
-        let shared_state = app_for_paste.state::<SharedState>();
-        {
-            let mut state = shared_state.lock();
-            state.dictation_state = DictationState::Idle;
-        }
-        emit_state(&app_for_paste, &DictationState::Idle);
-        if let Some(window) = app_for_paste.get_webview_window("overlay") {
-            let _ = window.hide();
-        }
-    })
-    .map_err(|e| format!("Failed to run on main thread: {}", e))?;
+    paste_and_reset(&app, translated_text, smart_paste)
+        .map_err(|e| format!("Failed to run on main thread: {}", e))?;
 
     Ok(())
 }
@@ -1528,60 +4256,253 @@ fn reject_translation(
         (text, state.smart_paste)
     };
 
-    let app_for_paste = app.clone();
-    app.run_on_main_thread(move || {
-        if let Err(e) = input::paste::paste_text(&source_text, smart_paste) {
-            log::error!("Failed to paste source text: {}", e);
-            let error_state = DictationState::Error {
-                message: format!("Failed to paste: {}", e),
-            };
-            let shared_state = app_for_paste.state::<SharedState>();
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(&app_for_paste, &error_state);
-            return;
-        }
+    paste_and_reset(&app, source_text, smart_paste)
+        .map_err(|e| format!("Failed to run on main thread: {}", e))?;
+
+    Ok(())
+}
+
+/// Translates arbitrary text with the loaded translation model, whether or not
+/// dictation translation is turned on. `source` of `None` means auto-detect.
+#[tauri::command]
+async fn translate_text(
+    app: tauri::AppHandle,
+    text: String,
+    source: Option<String>,
+    target: String,
+    variant: Option<String>,
+) -> Result<String, String> {
+    let job = TranslationJob {
+        text,
+        source_lang: source.unwrap_or_else(|| "auto".to_string()),
+        target_lang: target,
+        target_variant: variant,
+        generation: 0,
+    };
+
+    // Inference can take a while; keep it off the main thread
+    tokio::task::spawn_blocking(move || run_translation(&app, job))
+        .await
+        .map_err(|e| format!("Translation failed: {}", e))?
+}
+
+/// Like `translate_text`, but returns up to `n` candidate translations, best first,
+/// for comparing phrasings.
+#[tauri::command]
+async fn translate_text_nbest(
+    app: tauri::AppHandle,
+    text: String,
+    source: Option<String>,
+    target: String,
+    n: usize,
+) -> Result<Vec<String>, String> {
+    let job = TranslationJob {
+        text,
+        source_lang: source.unwrap_or_else(|| "auto".to_string()),
+        target_lang: target,
+        target_variant: None,
+        generation: 0,
+    };
 
-        let shared_state = app_for_paste.state::<SharedState>();
+    tokio::task::spawn_blocking(move || {
+        let (reply_tx, reply_rx) = std::sync::mpsc::channel();
         {
-            let mut state = shared_state.lock();
-            state.dictation_state = DictationState::Idle;
+            let tx = app.state::<TranslationSender>();
+            let tx = tx.0.lock().unwrap();
+            let _ = tx.send(TranslationRequest::TranslateNBest(job, n, reply_tx));
+        }
+
+        // Beam search over several hypotheses is slower than the single-result path
+        match reply_rx.recv_timeout(std::time::Duration::from_secs(60)) {
+            Ok(result) => result,
+            Err(_) => Err("Translation timed out or thread disconnected".to_string()),
+        }
+    })
+    .await
+    .map_err(|e| format!("Translation failed: {}", e))?
+}
+
+#[tauri::command]
+fn get_language(shared_state: tauri::State<'_, SharedState>) -> String {
+    shared_state.lock().language.clone()
+}
+
+#[tauri::command]
+fn get_initial_prompt(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().initial_prompt.clone()
+}
+
+/// Sets the Whisper initial prompt used for languages without a preset; `None`
+/// or an empty string removes it.
+#[tauri::command]
+fn set_initial_prompt(app: tauri::AppHandle, prompt: Option<String>) -> Result<(), String> {
+    let prompt = prompt.filter(|prompt| !prompt.trim().is_empty());
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.initial_prompt = prompt.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.initial_prompt = prompt;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    sync_whisper_prompt(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_language_prompts(
+    shared_state: tauri::State<'_, SharedState>,
+) -> std::collections::HashMap<String, String> {
+    shared_state.lock().language_prompts.clone()
+}
+
+/// Sets the Whisper initial prompt preset for `language`, used instead of the
+/// global prompt whenever that language is active. `None` or an empty string
+/// removes the preset.
+#[tauri::command]
+fn set_language_prompt(
+    app: tauri::AppHandle,
+    language: String,
+    prompt: Option<String>,
+) -> Result<(), String> {
+    let prompt = prompt.filter(|prompt| !prompt.trim().is_empty());
+
+    // Update in-memory state
+    let language_prompts = {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        match prompt {
+            Some(prompt) => state.language_prompts.insert(language, prompt),
+            None => state.language_prompts.remove(&language),
+        };
+        state.language_prompts.clone()
+    };
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.language_prompts = language_prompts;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    sync_whisper_prompt(&app);
+    Ok(())
+}
+
+/// Puts back the translation settings quick English replaced, returning whether
+/// it was on.
+fn restore_quick_english(state: &mut state::AppState) -> bool {
+    match state.quick_english_restore.take() {
+        Some(restore) => {
+            state.translation_enabled = restore.translation_enabled;
+            state.translation_target_lang = restore.target_lang;
+            state.translation_target_variant = restore.target_variant;
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+fn get_quick_english_active(shared_state: tauri::State<'_, SharedState>) -> bool {
+    shared_state.lock().quick_english_restore.is_some()
+}
+
+/// Turns "quick English" on or off: the next recording is auto-detected and
+/// translated to English, then the previous translation settings come back.
+/// Nothing is persisted. Returns whether it is now on.
+#[tauri::command]
+fn quick_english_toggle(app: tauri::AppHandle) -> Result<bool, String> {
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        if restore_quick_english(&mut state) {
+            state.session_language = None;
+            drop(state);
+            sync_whisper_language(&app);
+            sync_translation_languages(&app);
+            return Ok(false);
+        }
+        if !matches!(state.dictation_state, DictationState::Idle) {
+            return Err("Quick English can only be turned on while idle".to_string());
         }
-        emit_state(&app_for_paste, &DictationState::Idle);
-        if let Some(window) = app_for_paste.get_webview_window("overlay") {
-            let _ = window.hide();
+        if transcription::model_manager::is_english_only(&state.selected_model) {
+            return Err(format!(
+                "The {} model is English-only; select a multilingual model to translate from other languages",
+                state.selected_model
+            ));
         }
-    })
-    .map_err(|e| format!("Failed to run on main thread: {}", e))?;
+    }
 
-    Ok(())
+    setup_translation_model(app.clone())?;
+
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.quick_english_restore = Some(state::TranslationRestore {
+            translation_enabled: state.translation_enabled,
+            target_lang: state.translation_target_lang.clone(),
+            target_variant: state.translation_target_variant.clone(),
+        });
+        state.translation_enabled = true;
+        state.translation_target_lang = "en".to_string();
+        state.translation_target_variant = None;
+        state.session_language = Some("auto".to_string());
+    }
+    sync_whisper_language(&app);
+    sync_translation_languages(&app);
+    Ok(true)
 }
 
+/// Overrides the language for the next recording only, without persisting it or
+/// swapping models. The persisted language is restored once that recording finishes.
 #[tauri::command]
-fn get_language(shared_state: tauri::State<'_, SharedState>) -> String {
-    shared_state.lock().language.clone()
+fn set_session_language(app: tauri::AppHandle, language: String) -> Result<(), String> {
+    let language = normalize_language(&language)?;
+    let shared_state = app.state::<SharedState>();
+    let mut state = shared_state.lock();
+
+    if !matches!(state.dictation_state, DictationState::Idle) {
+        return Err("The session language can only be set while idle".to_string());
+    }
+    if language::base(&language) != "en"
+        && transcription::model_manager::is_english_only(&state.selected_model)
+    {
+        return Err(format!(
+            "The {} model is English-only; select a multilingual model to dictate in other languages",
+            state.selected_model
+        ));
+    }
+
+    state.session_language = Some(language);
+    Ok(())
 }
 
 #[tauri::command]
 async fn set_language(app: tauri::AppHandle, language: String) -> Result<(), String> {
+    let language = normalize_language(&language)?;
+    let needs_multilingual = language::base(&language) != "en";
+
     // Check if we need to switch between English-only and multilingual models
     let (current_model, needs_model_switch) = {
         let shared_state = app.state::<SharedState>();
         let state = shared_state.lock();
         let current = state.selected_model.clone();
         let is_en = transcription::model_manager::is_english_only(&current);
-        let needs_multilingual = language != "en";
+        // A custom model is kept regardless of language
+        let is_custom = current == transcription::model_manager::CUSTOM_MODEL_NAME;
         (
             current.clone(),
-            (needs_multilingual && is_en) || (!needs_multilingual && !is_en),
+            !is_custom && ((needs_multilingual && is_en) || (!needs_multilingual && !is_en)),
         )
     };
 
     // Determine new model if switching is needed
     let new_model = if needs_model_switch {
-        if language != "en" {
+        if needs_multilingual {
             transcription::model_manager::multilingual_equivalent(&current_model)
                 .unwrap_or("base")
                 .to_string()
@@ -1626,16 +4547,14 @@ async fn set_language(app: tauri::AppHandle, language: String) -> Result<(), Str
     config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
 
     // Send language to transcription thread
-    let lang_for_whisper = if language == "auto" {
-        None
-    } else {
-        Some(language)
-    };
     {
         let tx = app.state::<TranscriptionSender>();
         let tx = tx.0.lock().unwrap();
-        let _ = tx.send(TranscriptionRequest::SetLanguage(lang_for_whisper));
+        let _ = tx.send(TranscriptionRequest::SetLanguage(
+            language::whisper_language(&language),
+        ));
     }
+    sync_whisper_prompt(&app);
     sync_translation_languages(&app);
     if let Some(state) = updated_state {
         emit_state(&app, &state);
@@ -1659,9 +4578,10 @@ fn cancel_recording(app: tauri::AppHandle) {
 
     match current_state {
         DictationState::Recording { .. } => {
-            // Stop the streaming loop
+            // Stop the streaming loop and its queued partial translations
             let streaming_flag = app.state::<StreamingActive>();
             streaming_flag.0.store(false, Ordering::SeqCst);
+            translation::engine::cancel_partials();
 
             // Stop recording and discard audio
             {
@@ -1673,33 +4593,460 @@ fn cancel_recording(app: tauri::AppHandle) {
             }
 
             // Reset to Idle
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = DictationState::Idle;
-            }
-            emit_state(&app, &DictationState::Idle);
-            if let Some(window) = app.get_webview_window("overlay") {
-                let _ = window.hide();
-            }
+            reset_to_idle(&app);
         }
         DictationState::Error { .. } => {
             // Dismiss error
+            reset_to_idle(&app);
+        }
+        _ => {}
+    }
+}
+
+/// Starts or stops a recording like the hotkey, but delivers the final text (and
+/// translation, if enabled) through a 'transcription-result' event instead of pasting.
+#[tauri::command]
+fn transcribe_to_result(app: tauri::AppHandle) {
+    let shared_state = app.state::<SharedState>();
+    let starting = {
+        let mut state = shared_state.lock();
+        let idle = matches!(state.dictation_state, DictationState::Idle);
+        if idle {
+            state.result_only = true;
+        }
+        idle
+    };
+
+    toggle_recording(&app);
+
+    // Don't leave the flag behind for the next hotkey recording if we failed to start
+    if starting {
+        let mut state = shared_state.lock();
+        if !matches!(state.dictation_state, DictationState::Recording { .. }) {
+            state.result_only = false;
+        }
+    }
+}
+
+#[tauri::command]
+fn get_dictation_state(shared_state: tauri::State<'_, SharedState>) -> DictationState {
+    shared_state.lock().dictation_state.clone()
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RecordingStats {
+    recording: bool,
+    duration_ms: u64,
+    partial_length: usize,
+    translation_active: bool,
+}
+
+/// Snapshot of the current recording for polling integrations; zeroed when not recording.
+#[tauri::command]
+fn get_recording_stats(shared_state: tauri::State<'_, SharedState>) -> RecordingStats {
+    let state = shared_state.lock();
+    match &state.dictation_state {
+        DictationState::Recording {
+            duration_ms,
+            partial_text,
+            ..
+        } => RecordingStats {
+            recording: true,
+            duration_ms: *duration_ms,
+            partial_length: partial_text.as_deref().map_or(0, |t| t.chars().count()),
+            translation_active: state.translation_enabled,
+        },
+        _ => RecordingStats {
+            recording: false,
+            duration_ms: 0,
+            partial_length: 0,
+            translation_active: false,
+        },
+    }
+}
+
+/// Reports the input device and format recordings use, for diagnosing silent recordings.
+#[tauri::command]
+fn get_audio_info(
+    shared_state: tauri::State<'_, SharedState>,
+) -> Result<audio::capture::AudioInfo, String> {
+    let input_device = shared_state.lock().input_device.clone();
+    // Recordings fall back to the default input when the chosen device is gone
+    audio::capture::input_info(input_device.as_deref())
+        .or_else(|_| audio::capture::input_info(None))
+        .map_err(|e| format!("Failed to read input device: {}", e))
+}
+
+#[tauri::command]
+fn list_input_devices() -> Vec<String> {
+    audio::capture::list_input_devices()
+}
+
+#[tauri::command]
+fn get_input_device(shared_state: tauri::State<'_, SharedState>) -> Option<String> {
+    shared_state.lock().input_device.clone()
+}
+
+/// Chooses the input device to record from by name; `None` follows the system
+/// default. Takes effect from the next recording.
+#[tauri::command]
+fn set_input_device(app: tauri::AppHandle, name: Option<String>) -> Result<(), String> {
+    if let Some(name) = &name {
+        if !audio::capture::list_input_devices().contains(name) {
+            return Err(format!("Input device not found: {}", name));
+        }
+    }
+
+    // Update in-memory state
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        state.input_device = name.clone();
+    }
+
+    // Persist to config
+    let mut cfg = config::load_config();
+    cfg.input_device = name;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn check_microphone_permission() -> audio::permission::MicrophonePermission {
+    audio::permission::check_microphone_permission()
+}
+
+/// Runs the current vocabulary over `text` and returns what would change, without
+/// pasting or touching any state, so entries can be tried out in Settings.
+#[tauri::command]
+fn preview_corrections(text: String) -> vocabulary::CorrectionResult {
+    vocabulary::apply_corrections(&text, &vocabulary::load_vocabulary())
+}
+
+/// Suggests vocabulary phrases from frequently transcribed, uncommon words in history.
+#[tauri::command]
+fn suggest_vocabulary() -> Vec<String> {
+    let history = history::load_history();
+    let vocab = vocabulary::load_vocabulary();
+    vocabulary::suggest_entries(history.entries.iter().map(|e| e.text.as_str()), &vocab)
+}
+
+/// Emitted as 'file-transcription-progress' after each window of a file is transcribed.
+#[derive(Clone, Serialize)]
+struct FileTranscriptionProgress {
+    completed: usize,
+    total: usize,
+}
+
+/// Transcribes an audio file through the loaded model and records the result in history.
+#[tauri::command]
+async fn transcribe_file(app: tauri::AppHandle, path: String) -> Result<String, String> {
+    // Claim the transcription thread so the hotkey can't start a recording meanwhile
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        if !matches!(state.dictation_state, DictationState::Idle) {
+            return Err("Can't transcribe a file while dictation is in progress".to_string());
+        }
+        state.dictation_state = DictationState::Processing;
+    }
+    emit_state(&app, &DictationState::Processing);
+
+    let app_clone = app.clone();
+    let result = tokio::task::spawn_blocking(move || transcribe_file_blocking(&app_clone, &path))
+        .await
+        .map_err(|e| format!("Transcription failed: {}", e))
+        .and_then(|r| r);
+
+    reset_to_idle(&app);
+    result
+}
+
+/// How long `health_check` waits for a worker thread to answer a ping.
+const HEALTH_PING_TIMEOUT_MS: u64 = 500;
+
+#[derive(Clone, Serialize)]
+struct HealthStatus {
+    /// Idle with everything loaded, so a recording started now will work.
+    ready: bool,
+    state: DictationState,
+    whisper_model_loaded: bool,
+    translation_enabled: bool,
+    translation_model_loaded: bool,
+    /// A worker busy with a long job (e.g. a transcription) may miss the ping
+    /// and show as unresponsive until it finishes.
+    transcription_thread_responsive: bool,
+    translation_thread_responsive: bool,
+}
+
+/// Sends a ping built by `request` and waits briefly for the worker's answer
+/// (whether its model is loaded). `None` means it didn't answer in time.
+fn ping_worker<R>(
+    sender: &std::sync::Mutex<std::sync::mpsc::Sender<R>>,
+    request: impl FnOnce(std::sync::mpsc::Sender<bool>) -> R,
+) -> Option<bool> {
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    sender.lock().unwrap().send(request(reply_tx)).ok()?;
+    reply_rx
+        .recv_timeout(std::time::Duration::from_millis(HEALTH_PING_TIMEOUT_MS))
+        .ok()
+}
+
+/// Reports whether Wren is ready to record: the current state, which models are
+/// loaded, and whether the worker threads answer a ping.
+#[tauri::command]
+async fn health_check(app: tauri::AppHandle) -> Result<HealthStatus, String> {
+    tokio::task::spawn_blocking(move || {
+        let transcription = ping_worker(
+            &app.state::<TranscriptionSender>().0,
+            TranscriptionRequest::Ping,
+        );
+        let translation = ping_worker(
+            &app.state::<TranslationSender>().0,
+            TranslationRequest::Ping,
+        );
+
+        let (state, translation_enabled) = {
+            let shared_state = app.state::<SharedState>();
+            let state = shared_state.lock();
+            (state.dictation_state.clone(), state.translation_enabled)
+        };
+        let whisper_model_loaded = transcription == Some(true);
+        let translation_model_loaded = translation == Some(true);
+        HealthStatus {
+            ready: matches!(state, DictationState::Idle)
+                && whisper_model_loaded
+                && (!translation_enabled || translation_model_loaded),
+            state,
+            whisper_model_loaded,
+            translation_enabled,
+            translation_model_loaded,
+            transcription_thread_responsive: transcription.is_some(),
+            translation_thread_responsive: translation.is_some(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Health check failed: {}", e))
+}
+
+/// Times the loaded whisper model on the start of the last recording (or a
+/// built-in clip before there is one) so users can compare models on their own
+/// hardware.
+#[tauri::command]
+async fn benchmark_model(
+    app: tauri::AppHandle,
+) -> Result<transcription::benchmark::BenchmarkResult, String> {
+    if app.state::<SharedState>().lock().model_path.is_none() {
+        return Err("No model loaded".to_string());
+    }
+    // Claim the transcription thread so the hotkey can't start a recording meanwhile
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        if !matches!(state.dictation_state, DictationState::Idle) {
+            return Err("Can't benchmark while dictation is in progress".to_string());
+        }
+        state.dictation_state = DictationState::Processing;
+    }
+    emit_state(&app, &DictationState::Processing);
+
+    let last_recording = app
+        .state::<LastRecording>()
+        .0
+        .lock()
+        .unwrap()
+        .resampled_16k();
+    let (clip, source) = transcription::benchmark::benchmark_clip(last_recording);
+    let threads =
+        transcription::whisper::resolve_threads(app.state::<SharedState>().lock().whisper_threads);
+
+    let app_clone = app.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut timings = Vec::with_capacity(transcription::benchmark::RUNS);
+        for _ in 0..transcription::benchmark::RUNS {
+            // Large models can take a while per run; re-arm the watchdog each time
+            emit_state(&app_clone, &DictationState::Processing);
             {
-                let mut state = shared_state.lock();
-                state.dictation_state = DictationState::Idle;
+                let tx = app_clone.state::<TranscriptionSender>();
+                let tx = tx.0.lock().unwrap();
+                let _ = tx.send(TranscriptionRequest::Benchmark(clip.clone()));
             }
-            emit_state(&app, &DictationState::Idle);
-            if let Some(window) = app.get_webview_window("overlay") {
-                let _ = window.hide();
+            let rx = app_clone.state::<TranscriptionReceiver>();
+            let rx = rx.0.lock().unwrap();
+            match rx.recv_timeout(std::time::Duration::from_secs(300)) {
+                Ok(TranscriptionResponse::BenchmarkComplete(result)) => timings.push(result?),
+                Ok(_) => return Err("Unexpected transcription response".to_string()),
+                Err(_) => return Err("Benchmark timed out or thread disconnected".to_string()),
             }
         }
-        _ => {}
+        Ok(transcription::benchmark::BenchmarkResult::from_timings(
+            &timings,
+            source,
+            transcription::benchmark::clip_secs(&clip),
+            threads as i32,
+        ))
+    })
+    .await
+    .map_err(|e| format!("Benchmark failed: {}", e))
+    .and_then(|r| r);
+
+    reset_to_idle(&app);
+    result
+}
+
+/// Transcribes the last recording again with per-segment start/end times, for
+/// caption or subtitle export. Normal dictation output is unaffected.
+#[tauri::command]
+async fn transcribe_last_recording_segments(
+    app: tauri::AppHandle,
+) -> Result<Vec<transcription::segments::Segment>, String> {
+    let audio = app
+        .state::<LastRecording>()
+        .0
+        .lock()
+        .unwrap()
+        .resampled_16k();
+    if audio.is_empty() {
+        return Err("No recording to transcribe".to_string());
+    }
+    // Claim the transcription thread so the hotkey can't start a recording meanwhile
+    {
+        let shared_state = app.state::<SharedState>();
+        let mut state = shared_state.lock();
+        if !matches!(state.dictation_state, DictationState::Idle) {
+            return Err("Can't transcribe while dictation is in progress".to_string());
+        }
+        state.dictation_state = DictationState::Processing;
+    }
+    emit_state(&app, &DictationState::Processing);
+
+    let app_clone = app.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        {
+            let tx = app_clone.state::<TranscriptionSender>();
+            let tx = tx.0.lock().unwrap();
+            let _ = tx.send(TranscriptionRequest::TranscribeTimestamped(audio));
+        }
+        let rx = app_clone.state::<TranscriptionReceiver>();
+        let rx = rx.0.lock().unwrap();
+        match rx.recv_timeout(std::time::Duration::from_secs(300)) {
+            Ok(TranscriptionResponse::TimestampedComplete(result)) => result,
+            Ok(_) => Err("Unexpected transcription response".to_string()),
+            Err(_) => Err("Transcription timed out or thread disconnected".to_string()),
+        }
+    })
+    .await
+    .map_err(|e| format!("Transcription failed: {}", e))
+    .and_then(|r| r);
+
+    reset_to_idle(&app);
+    result
+}
+
+fn transcribe_file_blocking(app_handle: &tauri::AppHandle, path: &str) -> Result<String, String> {
+    let audio = audio::decode::decode_file_16k(std::path::Path::new(path))
+        .map_err(|e| format!("Failed to decode {}: {}", path, e))?;
+
+    let ranges = transcription::chunking::chunk_ranges(
+        audio.len(),
+        transcription::chunking::CHUNK_SECS * 16000,
+        transcription::chunking::OVERLAP_SECS * 16000,
+    );
+    let total = ranges.len();
+    let mut text = String::new();
+    let mut detected_language = None;
+    for (index, range) in ranges.into_iter().enumerate() {
+        // Long files legitimately stay in Processing; re-arm the watchdog per chunk
+        emit_state(app_handle, &DictationState::Processing);
+        {
+            let tx = app_handle.state::<TranscriptionSender>();
+            let tx = tx.0.lock().unwrap();
+            let _ = tx.send(TranscriptionRequest::Transcribe(
+                audio[range].to_vec(),
+                None,
+            ));
+        }
+
+        let resp = {
+            let rx = app_handle.state::<TranscriptionReceiver>();
+            let rx = rx.0.lock().unwrap();
+            rx.recv_timeout(std::time::Duration::from_secs(300))
+        };
+        match resp {
+            Ok(TranscriptionResponse::TranscriptionComplete(Ok(chunk))) => {
+                // Consecutive windows share a few seconds, so drop the words heard twice
+                text = transcription::chunking::stitch(&text, chunk.text.trim());
+                detected_language = detected_language.or(chunk.detected_language);
+            }
+            Ok(TranscriptionResponse::TranscriptionComplete(Err(e))) => return Err(e),
+            Ok(_) => return Err("Unexpected transcription response".to_string()),
+            Err(_) => return Err("Transcription timed out or thread disconnected".to_string()),
+        }
+
+        let _ = app_handle.emit(
+            "file-transcription-progress",
+            FileTranscriptionProgress {
+                completed: index + 1,
+                total,
+            },
+        );
+    }
+
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let entry = history::HistoryEntry {
+        id: timestamp_ms,
+        text: text.clone(),
+        timestamp_ms,
+        duration_ms: (audio.len() / 16) as u64,
+        source_text: None,
+        detected_language,
+    };
+    record_history(app_handle, entry);
+
+    Ok(text)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HistoryEntryPayload {
+    #[serde(flatten)]
+    entry: history::HistoryEntry,
+    display_time: String,
+}
+
+/// Returns an empty list while history is turned off.
+#[tauri::command]
+fn get_history(
+    shared_state: tauri::State<'_, SharedState>,
+) -> Result<Vec<HistoryEntryPayload>, String> {
+    if !shared_state.lock().history_enabled {
+        return Ok(Vec::new());
     }
+    let format = config::load_config().history_time_format;
+    Ok(history::load_history()
+        .entries
+        .into_iter()
+        .map(|entry| HistoryEntryPayload {
+            display_time: history::format_timestamp(entry.timestamp_ms, &format),
+            entry,
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn get_history_time_format() -> String {
+    config::load_config().history_time_format
 }
 
 #[tauri::command]
-fn get_history() -> Result<Vec<history::HistoryEntry>, String> {
-    Ok(history::load_history().entries)
+fn set_history_time_format(format: String) -> Result<(), String> {
+    let mut cfg = config::load_config();
+    cfg.history_time_format = format;
+    config::save_config(&cfg).map_err(|e| format!("Failed to save config: {}", e))?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -1712,8 +5059,43 @@ fn clear_history() -> Result<(), String> {
     history::clear_history().map_err(|e| format!("Failed to clear history: {}", e))
 }
 
+/// Repairs the history file: drops entries that no longer parse, re-sorts
+/// newest first and re-applies the entry cap.
+#[tauri::command]
+fn compact_history(app: tauri::AppHandle) -> Result<history::CompactStats, String> {
+    let stats =
+        history::compact_history().map_err(|e| format!("Failed to compact history: {}", e))?;
+    let _ = app.emit("history-updated", ());
+    Ok(stats)
+}
+
 #[tauri::command]
 fn copy_history_entry(text: String) -> Result<(), String> {
+    copy_to_clipboard(text)
+}
+
+/// Copies the translation stored with a history entry.
+#[tauri::command]
+fn copy_history_entry_translated(id: u64) -> Result<(), String> {
+    let entry = history::find_entry(id).ok_or("History entry not found")?;
+    let text = entry
+        .translated_text()
+        .ok_or("This entry has no stored translation")?;
+    copy_to_clipboard(text.to_string())
+}
+
+/// Copies a history entry's source and translation as "source / translation".
+#[tauri::command]
+fn copy_history_entry_both(id: u64) -> Result<(), String> {
+    let entry = history::find_entry(id).ok_or("History entry not found")?;
+    let text = entry
+        .bilingual_text()
+        .ok_or("This entry has no stored translation")?;
+    copy_to_clipboard(text)
+}
+
+/// Replaces the clipboard contents with `text`.
+fn copy_to_clipboard(text: String) -> Result<(), String> {
     let mut clipboard =
         arboard::Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
     clipboard
@@ -1723,7 +5105,7 @@ fn copy_history_entry(text: String) -> Result<(), String> {
 }
 
 /// Sends LoadModel request to transcription thread and waits for response.
-fn load_model(app_handle: &tauri::AppHandle, path: &str, _model_name: &str) {
+fn load_model(app_handle: &tauri::AppHandle, path: &str, model_name: &str) {
     let tx = app_handle.state::<TranscriptionSender>();
     {
         let tx = tx.0.lock().unwrap();
@@ -1747,31 +5129,28 @@ fn load_model(app_handle: &tauri::AppHandle, path: &str, _model_name: &str) {
                 state.dictation_state = DictationState::Idle;
             }
             emit_state(app_handle, &DictationState::Idle);
-            if let Some(window) = app_handle.get_webview_window("overlay") {
-                let _ = window.hide();
+            hide_overlay(app_handle);
+            let _ = app_handle.emit(
+                "model-ready",
+                ModelReady {
+                    model: model_name.to_string(),
+                },
+            );
+            // The partial model may not suit the new main model's language
+            if let Err(e) = sync_partial_model(app_handle) {
+                log::error!("Failed to load partial model: {}", e);
             }
         }
         Ok(TranscriptionResponse::ModelLoaded(Err(e))) => {
             log::error!("Failed to load model: {}", e);
-            let error_state = DictationState::Error {
-                message: format!("Failed to load model: {}", e),
-            };
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(app_handle, &error_state);
+            set_error_state(app_handle, format!("Failed to load model: {}", e));
         }
         Err(_) => {
             log::error!("Model loading timed out or thread disconnected");
-            let error_state = DictationState::Error {
-                message: "Model loading timed out — try again".to_string(),
-            };
-            {
-                let mut state = shared_state.lock();
-                state.dictation_state = error_state.clone();
-            }
-            emit_state(app_handle, &error_state);
+            set_error_state(
+                app_handle,
+                "Model loading timed out — try again".to_string(),
+            );
         }
         _ => {
             log::error!("Unexpected response when loading model");
@@ -1785,24 +5164,142 @@ pub fn run() {
     let app_config = config::load_config();
     let hotkey = app_config.hotkey.clone();
     let selected_model = app_config.selected_model.clone();
+    let custom_model_path = app_config.custom_model_path.clone();
     let smart_paste = app_config.smart_paste;
+    let target_app = app_config.target_app.clone();
     let vocab_enabled = app_config.vocab_enabled;
-    let language = app_config.language.clone();
+    // A hand-edited code that isn't valid falls back to the default rather than
+    // reaching whisper or the translator
+    let language = language::normalize(&app_config.language).unwrap_or_else(|| "en".to_string());
+    let initial_prompt = app_config.initial_prompt.clone();
+    let language_prompts = app_config.language_prompts.clone();
     let translation_enabled = app_config.translation_enabled;
-    let translation_target_lang = app_config.translation_target_lang.clone();
+    let translation_target_lang = language::normalize(&app_config.translation_target_lang)
+        .filter(|lang| lang != "auto")
+        .unwrap_or_else(|| "en".to_string());
+    let translation_target_variant = app_config.translation_target_variant.clone();
     let translation_model = app_config.translation_model.clone();
+    let show_overlay = app_config.show_overlay;
+    let overlay_show_delay_ms = app_config.overlay_show_delay_ms;
+    let min_recording_ms = app_config.min_recording_ms;
+    let min_word_chars = app_config.min_word_chars;
+    let whisper_thresholds = WhisperThresholds {
+        no_speech_threshold: app_config.no_speech_threshold,
+        temperature: app_config.temperature,
+        entropy_threshold: app_config.entropy_threshold,
+    }
+    .clamped();
+    // Fall back to the defaults if the config was hand-edited out of range
+    let whisper_sampling = WhisperSampling {
+        best_of: app_config.best_of,
+        temperature_fallbacks: app_config.temperature_fallbacks,
+    }
+    .validated()
+    .unwrap_or_default();
+    let trim_silence = app_config.trim_silence;
+    let input_device = app_config.input_device.clone();
+    let partial_window_secs = app_config.partial_window_secs;
+    let partial_model = app_config.partial_model.clone();
+    let paragraph_on_pause = app_config.paragraph_on_pause;
+    let strip_nonspeech_tags = app_config.strip_nonspeech_tags;
+    let whisper_threads = app_config.whisper_threads;
+    let use_gpu = app_config.use_gpu;
+    let correct_partials = app_config.correct_partials;
+    let max_recording_ms = app_config.max_recording_ms;
+    let max_recording_warning_ms = app_config.max_recording_warning_ms;
+    let too_quiet_threshold = app_config.too_quiet_threshold;
+    let vad_enabled = app_config.vad_enabled;
+    let silence_timeout_ms = app_config.silence_timeout_ms;
+    let max_paste_chars = app_config.max_paste_chars;
+    let formatting_profile = app_config.formatting_profile;
+    let formatting_rules = app_config.formatting_rules.clone();
+    let translation_max_input_tokens = app_config.translation_max_input_tokens;
+    let notify_on_complete = app_config.notify_on_complete;
+    let append_mode = app_config.append_mode;
+    let error_auto_dismiss_ms = app_config.error_auto_dismiss_ms;
+    let auto_accept_corrections = app_config.auto_accept_corrections;
+    let auto_accept_translation = app_config.auto_accept_translation;
+    let metrics_logging = app_config.metrics_logging;
+    let offline_mode = app_config.offline_mode;
+    let intercepted_preview_keys = app_config.intercepted_preview_keys;
+    let guard_enter_while_recording = app_config.guard_enter_while_recording;
+    let processing_timeout_ms = app_config.processing_timeout_ms;
+    let auto_append_file = app_config.auto_append_file.clone();
+    let history_enabled = app_config.history_enabled;
+    let double_tap_cancel = app_config.double_tap_cancel;
+    let quiet_hours = app_config.quiet_hours.clone();
+    let hotkey_during_processing = app_config.hotkey_during_processing;
+    let correction_stage = app_config.correction_stage;
+    // Encrypted data stays locked until the user enters their passphrase
+    if app_config.encrypt_data {
+        crypto::set_locked();
+    }
 
     // Create shared state with persisted settings
     let shared_state: SharedState = Arc::new(parking_lot::Mutex::new(state::AppState {
         dictation_state: DictationState::Idle,
         model_path: None,
         selected_model,
+        custom_model_path,
+        session_language: None,
         smart_paste,
+        target_app,
         language: language.clone(),
+        initial_prompt,
+        language_prompts,
         vocab_enabled,
         translation_enabled,
         translation_target_lang,
+        translation_target_variant,
         translation_model,
+        translation_max_input_tokens,
+        show_overlay,
+        overlay_show_delay_ms,
+        result_only: false,
+        min_recording_ms,
+        min_word_chars,
+        max_recording_ms,
+        max_recording_warning_ms,
+        too_quiet_threshold,
+        vad_enabled,
+        silence_timeout_ms,
+        whisper_thresholds,
+        whisper_sampling,
+        trim_silence,
+        input_device,
+        partial_window_secs,
+        partial_model,
+        paragraph_on_pause,
+        strip_nonspeech_tags,
+        whisper_threads,
+        use_gpu,
+        correct_partials,
+        notify_on_complete,
+        append_mode,
+        append_buffer: String::new(),
+        max_paste_chars,
+        formatting_profile,
+        formatting_rules,
+        error_auto_dismiss_ms,
+        error_generation: 0,
+        auto_accept_corrections,
+        auto_accept_translation,
+        metrics_logging,
+        offline_mode,
+        intercepted_preview_keys,
+        guard_enter_while_recording,
+        processing_timeout_ms,
+        auto_append_file,
+        history_enabled,
+        double_tap_cancel,
+        quiet_hours,
+        quiet_hours_override_until: None,
+        hotkey_during_processing,
+        correction_stage,
+        active_dictation: None,
+        last_dictation_id: 0,
+        recording_generation: 0,
+        queued_recording: false,
         pending_original_text: None,
         pending_corrected_text: None,
         pending_source_text: None,
@@ -1810,8 +5307,13 @@ pub fn run() {
     }));
 
     // Spawn transcription thread
-    let (req_tx, resp_rx, partial_rx) = transcription::whisper::spawn_transcription_thread();
-    let (translation_req_tx, translation_resp_rx, partial_translation_rx) =
+    let transcription_abort = Arc::new(AtomicU64::new(0));
+    let (req_tx, resp_rx, partial_rx, transcription_thread) =
+        transcription::whisper::spawn_transcription_thread(
+            Arc::clone(&transcription_abort),
+            use_gpu,
+        );
+    let (translation_req_tx, translation_resp_rx, partial_translation_rx, translation_thread) =
         translation::engine::spawn_translation_thread();
 
     tauri::Builder::default()
@@ -1819,47 +5321,202 @@ pub fn run() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
+        .plugin(tauri_plugin_notification::init())
         .manage(shared_state)
         .manage(TranscriptionSender(std::sync::Mutex::new(req_tx)))
         .manage(TranscriptionReceiver(std::sync::Mutex::new(resp_rx)))
-        .manage(PartialTranscriptionReceiver(std::sync::Mutex::new(partial_rx)))
+        .manage(PartialTranscriptionReceiver(std::sync::Mutex::new(
+            partial_rx,
+        )))
         .manage(TranslationSender(std::sync::Mutex::new(translation_req_tx)))
-        .manage(TranslationReceiver(std::sync::Mutex::new(translation_resp_rx)))
+        .manage(TranslationReceiver(std::sync::Mutex::new(
+            translation_resp_rx,
+        )))
         .manage(PartialTranslationReceiver(std::sync::Mutex::new(
             partial_translation_rx,
         )))
+        .manage(TranscriptionAbort(transcription_abort))
         .manage(ActiveCapture(std::sync::Mutex::new(None)))
+        .manage(LastRecording(std::sync::Mutex::new(
+            audio::capture::RawAudio::default(),
+        )))
         .manage(StreamingActive(Arc::new(AtomicBool::new(false))))
+        .manage(DownloadCancel(Arc::new(AtomicBool::new(false))))
+        .manage(ProcessingWatchdog(AtomicU64::new(0)))
+        .manage(WorkerThreads(std::sync::Mutex::new(vec![
+            transcription_thread,
+            translation_thread,
+        ])))
         .manage(CurrentHotkey(std::sync::Mutex::new(hotkey.clone())))
+        .manage(LastHotkeyPress(std::sync::Mutex::new(None)))
+        .manage(LastPaste(std::sync::Mutex::new(None)))
+        .manage(LocalApiServer(std::sync::Mutex::new(None)))
+        .manage(StreamSubscribers(parking_lot::Mutex::new(Vec::new())))
         .invoke_handler(tauri::generate_handler![
             get_hotkey,
             set_hotkey,
+            validate_hotkey,
             get_models,
             select_model,
+            next_model,
+            previous_model,
+            set_custom_model,
+            get_waveform,
             get_smart_paste,
             set_smart_paste,
+            get_target_app,
+            set_target_app,
+            capture_target_app,
+            get_show_overlay,
+            set_show_overlay,
+            get_overlay_show_delay_ms,
+            set_overlay_show_delay_ms,
+            get_notify_on_complete,
+            set_notify_on_complete,
+            get_intercepted_preview_keys,
+            set_intercepted_preview_keys,
+            get_guard_enter_while_recording,
+            set_guard_enter_while_recording,
+            get_encryption_status,
+            unlock_data,
+            enable_encryption,
+            disable_encryption,
+            get_history_enabled,
+            set_history_enabled,
+            get_double_tap_cancel,
+            set_double_tap_cancel,
+            get_hotkey_during_processing,
+            set_hotkey_during_processing,
+            get_correction_stage,
+            set_correction_stage,
+            get_quiet_hours,
+            set_quiet_hours,
+            get_quiet_hours_active,
+            override_quiet_hours,
+            get_auto_append_file,
+            set_auto_append_file,
+            get_processing_timeout_ms,
+            set_processing_timeout_ms,
+            get_offline_mode,
+            set_offline_mode,
+            get_metrics_logging,
+            set_metrics_logging,
+            get_append_mode,
+            set_append_mode,
+            get_append_buffer,
+            flush_append_buffer,
+            copy_append_buffer,
+            clear_append_buffer,
+            get_error_auto_dismiss_ms,
+            set_error_auto_dismiss_ms,
+            get_auto_accept_corrections,
+            set_auto_accept_corrections,
+            get_auto_accept_translation,
+            set_auto_accept_translation,
+            get_min_recording_ms,
+            set_min_recording_ms,
+            get_min_word_chars,
+            set_min_word_chars,
+            get_trim_silence,
+            set_trim_silence,
+            get_partial_window_secs,
+            set_partial_window_secs,
+            get_partial_model,
+            set_partial_model,
+            get_paragraph_on_pause,
+            set_paragraph_on_pause,
+            get_strip_nonspeech_tags,
+            set_strip_nonspeech_tags,
+            get_whisper_threads,
+            set_whisper_threads,
+            get_use_gpu,
+            set_use_gpu,
+            get_correct_partials,
+            set_correct_partials,
+            get_recording_limit,
+            set_recording_limit,
+            get_max_paste_chars,
+            set_max_paste_chars,
+            get_too_quiet_threshold,
+            set_too_quiet_threshold,
+            get_vad_enabled,
+            set_vad_enabled,
+            get_silence_timeout_ms,
+            set_silence_timeout_ms,
+            get_formatting_profile,
+            set_formatting_profile,
+            get_formatting_rules,
+            set_formatting_rules,
+            get_whisper_thresholds,
+            set_whisper_thresholds,
+            get_whisper_sampling,
+            set_whisper_sampling,
+            get_local_api_settings,
+            set_local_api_settings,
+            regenerate_local_api_token,
             get_vocab_enabled,
             set_vocab_enabled,
             get_translation_enabled,
+            repair_translation_model,
             set_translation_enabled,
             get_translation_target_lang,
             set_translation_target_lang,
+            get_translation_target_variant,
+            get_translation_target_variants,
             get_vocabulary,
+            validate_vocab_pattern,
             add_vocab_entry,
+            import_vocabulary_csv,
             update_vocab_entry,
             delete_vocab_entry,
+            get_fuzzy_max_distance,
+            set_fuzzy_max_distance,
+            suggest_vocabulary,
+            preview_corrections,
             accept_corrections,
+            choose_correction,
             undo_corrections,
+            repeat_last_paste,
             accept_translation,
             reject_translation,
+            translate_text,
+            translate_text_nbest,
+            get_translation_max_input_tokens,
+            set_translation_max_input_tokens,
             get_language,
             set_language,
+            get_initial_prompt,
+            set_initial_prompt,
+            get_language_prompts,
+            set_language_prompt,
+            set_session_language,
+            get_quick_english_active,
+            quick_english_toggle,
             save_overlay_position,
+            reset_overlay_position,
+            save_overlay_size,
             cancel_recording,
+            transcribe_to_result,
+            transcribe_file,
+            benchmark_model,
+            transcribe_last_recording_segments,
+            health_check,
+            get_dictation_state,
+            check_microphone_permission,
+            get_audio_info,
+            list_input_devices,
+            get_input_device,
+            set_input_device,
+            get_recording_stats,
             get_history,
+            get_history_time_format,
+            set_history_time_format,
             delete_history_entry,
             clear_history,
-            copy_history_entry
+            compact_history,
+            copy_history_entry,
+            copy_history_entry_translated,
+            copy_history_entry_both
         ])
         .setup(move |app| {
             // Register global shortcut plugin with saved hotkey
@@ -1868,7 +5525,7 @@ pub fn run() {
                     .with_shortcuts([hotkey.as_str()])?
                     .with_handler(|app, _shortcut, event| {
                         if event.state == ShortcutState::Pressed {
-                            toggle_recording(app);
+                            on_hotkey_pressed(app);
                         }
                     })
                     .build(),
@@ -1880,51 +5537,130 @@ pub fn run() {
             // Set up system tray icon
             tray::setup_tray(app)?;
 
+            // Prompt for the passphrase so history and vocabulary can be read
+            if crypto::is_enabled() && !crypto::is_unlocked() {
+                tray::show_settings_window(app.handle());
+            }
+
             // Make overlay window non-activating (doesn't steal focus)
             #[cfg(target_os = "macos")]
             if let Some(window) = app.get_webview_window("overlay") {
                 make_window_non_activating(&window);
             }
 
-            // Restore saved overlay position
+            // Restore saved overlay size and position
             if let Some(window) = app.get_webview_window("overlay") {
                 let cfg = config::load_config();
+                // Swap in a user-supplied overlay page, if configured and valid
+                if let Some(setting) = &cfg.overlay_url {
+                    match custom_overlay_url(setting) {
+                        Some(url) => {
+                            if let Err(e) = app.add_capability(custom_overlay_capability(&url)) {
+                                log::error!("Failed to grant custom overlay event access: {}", e);
+                            }
+                            if let Err(e) = window.navigate(url) {
+                                log::error!("Failed to load custom overlay: {}", e);
+                            }
+                        }
+                        None => log::warn!("Ignoring invalid overlay_url: {}", setting),
+                    }
+                }
+                if let (Some(width), Some(height)) = (cfg.overlay_width, cfg.overlay_height) {
+                    let screen = window.current_monitor().ok().flatten().map(|monitor| {
+                        let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+                        (size.width, size.height)
+                    });
+                    let (width, height) = overlay::clamp_size(width, height, screen);
+                    let _ = window
+                        .set_size(tauri::Size::Logical(tauri::LogicalSize::new(width, height)));
+                }
                 if let (Some(x), Some(y)) = (cfg.overlay_x, cfg.overlay_y) {
-                    let _ = window.set_position(tauri::Position::Logical(
-                        tauri::LogicalPosition::new(x, y),
-                    ));
+                    // Snap back on-screen if the saved monitor is no longer connected
+                    let monitors: Vec<overlay::ScreenRect> = window
+                        .available_monitors()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|monitor| {
+                            let scale = monitor.scale_factor();
+                            let position = monitor.position().to_logical::<f64>(scale);
+                            let size = monitor.size().to_logical::<f64>(scale);
+                            overlay::ScreenRect {
+                                x: position.x,
+                                y: position.y,
+                                width: size.width,
+                                height: size.height,
+                            }
+                        })
+                        .collect();
+                    let scale = window.scale_factor().unwrap_or(1.0);
+                    let size = window
+                        .outer_size()
+                        .map(|size| {
+                            let size = size.to_logical::<f64>(scale);
+                            (size.width, size.height)
+                        })
+                        .unwrap_or((0.0, 0.0));
+                    let (x, y) = overlay::clamp_to_visible(x, y, size, &monitors);
+                    let _ = window
+                        .set_position(tauri::Position::Logical(tauri::LogicalPosition::new(x, y)));
                 }
             }
 
             // Check accessibility permission on startup
             if !input::paste::check_accessibility_permission() {
                 let app_handle = app.handle().clone();
-                let error_state = DictationState::Error {
-                    message: "Accessibility access needed — check System Settings > Privacy > Accessibility".to_string(),
-                };
-                let shared_state = app_handle.state::<SharedState>();
-                {
-                    let mut state = shared_state.lock();
-                    state.dictation_state = error_state.clone();
-                }
-                emit_state(&app_handle, &error_state);
-                if let Some(window) = app_handle.get_webview_window("overlay") {
-                    let _ = window.show();
-                }
+                set_error_state(
+                    &app_handle,
+                    "Accessibility access needed — check System Settings > Privacy > Accessibility"
+                        .to_string(),
+                );
+                show_overlay(&app_handle);
+            }
+
+            // Check microphone permission on startup; "not determined" is left for
+            // macOS to prompt on the first recording
+            if audio::permission::check_microphone_permission().is_blocked() {
+                let app_handle = app.handle().clone();
+                set_error_state(
+                    &app_handle,
+                    "Microphone access needed — check System Settings > Privacy > Microphone"
+                        .to_string(),
+                );
+                show_overlay(&app_handle);
             }
 
-            // Send initial language to transcription thread
+            // Send initial language, prompt, decoder settings and output filters to transcription thread
+            let whisper_prompt = app.state::<SharedState>().lock().whisper_prompt();
             {
                 let tx = app.state::<TranscriptionSender>();
                 let tx = tx.0.lock().unwrap();
-                let lang_for_whisper = if language == "auto" {
-                    None
-                } else {
-                    Some(language.clone())
-                };
-                let _ = tx.send(TranscriptionRequest::SetLanguage(lang_for_whisper));
+                let _ = tx.send(TranscriptionRequest::SetLanguage(
+                    language::whisper_language(&language),
+                ));
+                let _ = tx.send(TranscriptionRequest::SetInitialPrompt(whisper_prompt));
+                let _ = tx.send(TranscriptionRequest::SetThresholds(whisper_thresholds));
+                let _ = tx.send(TranscriptionRequest::SetSampling(whisper_sampling));
+                let _ = tx.send(TranscriptionRequest::SetParagraphOnPause(
+                    paragraph_on_pause,
+                ));
+                let _ = tx.send(TranscriptionRequest::SetStripNonspeechTags(
+                    strip_nonspeech_tags,
+                ));
+                let _ = tx.send(TranscriptionRequest::SetThreads(whisper_threads));
             }
             sync_translation_languages(&app.handle());
+            {
+                let tx = app.state::<TranslationSender>();
+                let tx = tx.0.lock().unwrap();
+                let _ = tx.send(TranslationRequest::SetMaxInputTokens(
+                    translation_max_input_tokens,
+                ));
+            }
+
+            // Start the local API if enabled
+            if let Err(e) = restart_local_api(&app.handle()) {
+                log::error!("{}", e);
+            }
 
             // Download/load model on startup in a background thread
             let app_handle = app.handle().clone();
@@ -1944,6 +5680,12 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            // Covers both the tray "Quit" item and the last window closing.
+            if let tauri::RunEvent::Exit = event {
+                shutdown(app_handle);
+            }
+        });
 }
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Once the log grows past this, it is rotated to `metrics.jsonl.1`.
+const MAX_METRICS_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Metadata about one finished transcription. Never holds the transcript text.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsRecord {
+    pub timestamp_ms: u64,
+    pub duration_ms: u64,
+    pub word_count: usize,
+    pub model: String,
+    pub language: String,
+    pub corrections_applied: bool,
+    pub translated: bool,
+    pub transcription_ms: u64,
+    pub translation_ms: Option<u64>,
+}
+
+fn metrics_path() -> PathBuf {
+    let data_dir = dirs::data_dir().expect("Failed to get data directory");
+    data_dir.join("com.wren.app").join("metrics.jsonl")
+}
+
+/// Appends `record` as one JSON line, rotating the log first if it is over the size cap.
+pub fn append(record: &MetricsRecord) -> Result<()> {
+    let path = metrics_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_METRICS_BYTES) {
+        std::fs::rename(&path, path.with_extension("jsonl.1"))?;
+    }
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
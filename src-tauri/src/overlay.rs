@@ -0,0 +1,174 @@
+/// Distance of the overlay from the top of the screen when it has no saved
+/// position, matching the window's `y` in `tauri.conf.json`.
+const DEFAULT_OVERLAY_TOP: f64 = 80.0;
+
+/// Smallest overlay size we will restore, in logical pixels.
+const MIN_OVERLAY_WIDTH: f64 = 160.0;
+const MIN_OVERLAY_HEIGHT: f64 = 80.0;
+
+/// Clamps a saved overlay size so it is neither degenerate nor larger than the screen.
+/// `screen` is the logical size of the monitor the overlay will appear on, if known.
+pub fn clamp_size(width: f64, height: f64, screen: Option<(f64, f64)>) -> (f64, f64) {
+    let (max_width, max_height) = screen.unwrap_or((f64::MAX, f64::MAX));
+    (
+        width.min(max_width).max(MIN_OVERLAY_WIDTH.min(max_width)),
+        height
+            .min(max_height)
+            .max(MIN_OVERLAY_HEIGHT.min(max_height)),
+    )
+}
+
+/// A monitor's bounds in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ScreenRect {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// The closest top-left corner that keeps a `width` x `height` window on this
+    /// monitor. A window larger than the monitor is pinned to its top-left.
+    fn clamp(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        (
+            x.clamp(self.x, (self.x + self.width - width).max(self.x)),
+            y.clamp(self.y, (self.y + self.height - height).max(self.y)),
+        )
+    }
+}
+
+/// Returns the top-left corner `(x, y)` unchanged if it lies on one of `monitors`,
+/// otherwise moves the `size` window to the closest spot where it fits on the nearest
+/// monitor. With no monitors, returns the input.
+pub fn clamp_to_visible(x: f64, y: f64, size: (f64, f64), monitors: &[ScreenRect]) -> (f64, f64) {
+    if monitors.is_empty() || monitors.iter().any(|m| m.contains(x, y)) {
+        return (x, y);
+    }
+
+    let (width, height) = size;
+    monitors
+        .iter()
+        .map(|m| m.clamp(x, y, width, height))
+        .min_by(|a, b| {
+            let da = (a.0 - x).powi(2) + (a.1 - y).powi(2);
+            let db = (b.0 - x).powi(2) + (b.1 - y).powi(2);
+            da.total_cmp(&db)
+        })
+        .unwrap_or((x, y))
+}
+
+/// Where an overlay `width` pixels wide goes without a saved position:
+/// centered horizontally on `screen`, `DEFAULT_OVERLAY_TOP` from its top.
+pub fn default_position(width: f64, screen: &ScreenRect) -> (f64, f64) {
+    (
+        screen.x + ((screen.width - width) / 2.0).max(0.0),
+        screen.y + DEFAULT_OVERLAY_TOP,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> ScreenRect {
+        ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    const OVERLAY: (f64, f64) = (320.0, 120.0);
+
+    #[test]
+    fn test_visible_position_unchanged() {
+        let monitors = vec![rect(0.0, 0.0, 1440.0, 900.0)];
+        assert_eq!(
+            clamp_to_visible(100.0, 80.0, OVERLAY, &monitors),
+            (100.0, 80.0)
+        );
+    }
+
+    #[test]
+    fn test_position_on_secondary_monitor_unchanged() {
+        let monitors = vec![
+            rect(0.0, 0.0, 1440.0, 900.0),
+            rect(1440.0, 0.0, 2560.0, 1440.0),
+        ];
+        assert_eq!(
+            clamp_to_visible(3000.0, 1200.0, OVERLAY, &monitors),
+            (3000.0, 1200.0)
+        );
+    }
+
+    #[test]
+    fn test_disconnected_monitor_snaps_to_nearest() {
+        // Saved on an external monitor to the right that is no longer connected.
+        let monitors = vec![rect(0.0, 0.0, 1440.0, 900.0)];
+        let (x, y) = clamp_to_visible(3000.0, 200.0, OVERLAY, &monitors);
+        assert_eq!((x, y), (1120.0, 200.0));
+        assert!(monitors[0].contains(x, y));
+        assert!(monitors[0].contains(x + OVERLAY.0 - 1.0, y + OVERLAY.1 - 1.0));
+    }
+
+    #[test]
+    fn test_oversized_window_pinned_to_top_left() {
+        let monitors = vec![rect(0.0, 0.0, 1440.0, 900.0)];
+        assert_eq!(
+            clamp_to_visible(-400.0, 2000.0, (2000.0, 1000.0), &monitors),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_picks_closest_of_several_monitors() {
+        let monitors = vec![
+            rect(0.0, 0.0, 1440.0, 900.0),
+            rect(0.0, 900.0, 1920.0, 1080.0),
+        ];
+        assert_eq!(
+            clamp_to_visible(1800.0, -50.0, OVERLAY, &monitors),
+            (1120.0, 0.0)
+        );
+        assert_eq!(
+            clamp_to_visible(2500.0, 1500.0, OVERLAY, &monitors),
+            (1600.0, 1500.0)
+        );
+    }
+
+    #[test]
+    fn test_no_monitors_returns_input() {
+        assert_eq!(
+            clamp_to_visible(-500.0, -500.0, OVERLAY, &[]),
+            (-500.0, -500.0)
+        );
+    }
+
+    #[test]
+    fn test_default_position_centers_on_screen() {
+        assert_eq!(
+            default_position(320.0, &rect(0.0, 0.0, 1440.0, 900.0)),
+            (560.0, 80.0)
+        );
+        assert_eq!(
+            default_position(320.0, &rect(1440.0, -200.0, 2560.0, 1440.0)),
+            (2560.0, -120.0)
+        );
+    }
+
+    #[test]
+    fn test_clamp_size_to_screen() {
+        assert_eq!(
+            clamp_size(3000.0, 2000.0, Some((1440.0, 900.0))),
+            (1440.0, 900.0)
+        );
+        assert_eq!(clamp_size(0.0, 0.0, Some((1440.0, 900.0))), (160.0, 80.0));
+        assert_eq!(clamp_size(320.0, 400.0, None), (320.0, 400.0));
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// A daily window during which the record hotkey is ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Local start time, "HH:MM".
+    pub start: String,
+    /// Local end time, "HH:MM". An end before the start runs past midnight.
+    pub end: String,
+    /// Days the window starts on ("mon" … "sun"); empty means every day.
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            days: Vec::new(),
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", time))
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+    day.trim()
+        .parse::<Weekday>()
+        .map_err(|_| format!("Invalid day '{}'", day))
+}
+
+impl QuietHours {
+    /// Checks the times and days parse, so a bad schedule is rejected when it's
+    /// set rather than silently never matching.
+    pub fn validate(&self) -> Result<(), String> {
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        for day in &self.days {
+            parse_day(day)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `now` (local time) falls inside the quiet window.
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let starts_on = |day: Weekday| {
+            self.days.is_empty()
+                || self
+                    .days
+                    .iter()
+                    .any(|d| parse_day(d).is_ok_and(|d| d == day))
+        };
+
+        let time = now.time();
+        let today = now.weekday();
+        if start < end {
+            starts_on(today) && time >= start && time < end
+        } else if start > end {
+            // Overnight: the part after midnight belongs to the previous day's window
+            (starts_on(today) && time >= start) || (starts_on(today.pred()) && time < end)
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn schedule(start: &str, end: &str, days: &[&str]) -> QuietHours {
+        QuietHours {
+            enabled: true,
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_same_day_window() {
+        let quiet = schedule("09:00", "10:30", &[]);
+        // 2024-01-01 is a Monday
+        assert!(!quiet.is_active(at("2024-01-01", "08:59")));
+        assert!(quiet.is_active(at("2024-01-01", "09:00")));
+        assert!(quiet.is_active(at("2024-01-01", "10:29")));
+        assert!(!quiet.is_active(at("2024-01-01", "10:30")));
+    }
+
+    #[test]
+    fn test_overnight_window_uses_start_day() {
+        let quiet = schedule("22:00", "07:00", &["fri"]);
+        // Friday night and early Saturday morning are quiet
+        assert!(quiet.is_active(at("2024-01-05", "23:00")));
+        assert!(quiet.is_active(at("2024-01-06", "06:59")));
+        // Early Friday morning belongs to Thursday's window, which isn't scheduled
+        assert!(!quiet.is_active(at("2024-01-05", "06:00")));
+        assert!(!quiet.is_active(at("2024-01-06", "23:00")));
+    }
+
+    #[test]
+    fn test_disabled_or_invalid_is_never_active() {
+        let mut quiet = schedule("00:00", "23:59", &[]);
+        assert!(quiet.is_active(at("2024-01-01", "12:00")));
+        quiet.enabled = false;
+        assert!(!quiet.is_active(at("2024-01-01", "12:00")));
+
+        let invalid = schedule("25:00", "07:00", &["someday"]);
+        assert!(invalid.validate().is_err());
+        assert!(!invalid.is_active(at("2024-01-01", "23:00")));
+    }
+}
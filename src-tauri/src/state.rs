@@ -1,6 +1,10 @@
+use crate::formatting::{FormattingProfile, FormattingRule};
+use crate::quiet_hours::QuietHours;
+use crate::transcription::whisper::{WhisperSampling, WhisperThresholds};
 use crate::vocabulary::CorrectionApplied;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +42,39 @@ pub enum DictationState {
     },
 }
 
+/// Translation settings to put back once a quick-English recording finishes.
+#[derive(Debug, Clone)]
+pub struct TranslationRestore {
+    pub translation_enabled: bool,
+    pub target_lang: String,
+    pub target_variant: Option<String>,
+}
+
+/// What the record hotkey does while a dictation is being transcribed or translated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum HotkeyDuringProcessing {
+    /// The hotkey does nothing until the dictation finishes.
+    #[default]
+    Ignore,
+    /// Abandons the dictation and returns to Idle.
+    Cancel,
+    /// Starts a new recording once the dictation finishes.
+    Queue,
+}
+
+/// Where vocabulary corrections run when translation is on. Without
+/// translation they always run on the transcript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum CorrectionStage {
+    /// Corrections run on the transcript, which is then translated.
+    #[default]
+    BeforeTranslation,
+    /// Corrections run on the translated text, fixing terms in the target language.
+    AfterTranslation,
+    /// Corrections run on the transcript and again on its translation.
+    Both,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatePayload {
     pub state: DictationState,
@@ -47,30 +84,216 @@ pub struct AppState {
     pub dictation_state: DictationState,
     pub model_path: Option<String>,
     pub selected_model: String,
+    pub custom_model_path: Option<String>,
     pub smart_paste: bool,
+    /// Bundle id of the app that pastes are sent to regardless of focus, if set.
+    pub target_app: Option<String>,
     pub language: String,
+    /// Language override for the next recording only; cleared when it finishes.
+    pub session_language: Option<String>,
+    /// Set while "quick English" translation is on for the next recording; holds
+    /// the settings it temporarily replaced.
+    pub quick_english_restore: Option<TranslationRestore>,
+    /// Whisper initial prompt used when the language has no preset.
+    pub initial_prompt: Option<String>,
+    /// Whisper initial prompt presets keyed by language code.
+    pub language_prompts: HashMap<String, String>,
     pub vocab_enabled: bool,
     pub translation_enabled: bool,
     pub translation_target_lang: String,
+    /// NLLB variant of the target language, e.g. `zho_Hant` for Traditional Chinese.
+    pub translation_target_variant: Option<String>,
     pub translation_model: String,
+    /// Longer inputs are refused with an error instead of being translated partway.
+    pub translation_max_input_tokens: usize,
+    pub show_overlay: bool,
+    /// How long a recording runs before the overlay appears, so quick dictations
+    /// never flash it; 0 shows it immediately.
+    pub overlay_show_delay_ms: u64,
+    /// Set for a `transcribe_to_result` session: the final text is emitted to the
+    /// frontend instead of being pasted.
+    pub result_only: bool,
+    pub min_recording_ms: u64,
+    /// A transcript needs a word with at least this many letters/digits to be
+    /// used; anything less is treated like silence. 0 accepts everything.
+    pub min_word_chars: usize,
+    /// Recordings are stopped automatically at this length; 0 means no limit.
+    pub max_recording_ms: u64,
+    /// How long before `max_recording_ms` the 'recording-warning' countdown starts.
+    pub max_recording_warning_ms: u64,
+    /// Peak level below which `audio-levels` reports `too_quiet`; 0 disables the hint.
+    pub too_quiet_threshold: f32,
+    /// Hands-free mode: stop recording once speech is followed by `silence_timeout_ms` of silence.
+    pub vad_enabled: bool,
+    pub silence_timeout_ms: u64,
+    pub whisper_thresholds: WhisperThresholds,
+    pub whisper_sampling: WhisperSampling,
+    pub trim_silence: bool,
+    /// Name of the input device to record from; `None` uses the system default.
+    pub input_device: Option<String>,
+    pub partial_window_secs: f32,
+    /// Model used only for live partials (e.g. tiny for speed); the selected
+    /// model is used when unset.
+    pub partial_model: Option<String>,
+    pub paragraph_on_pause: bool,
+    /// Drop segments that are only tags like `[BLANK_AUDIO]` or `♪♪`.
+    pub strip_nonspeech_tags: bool,
+    /// Threads whisper decodes with; 0 uses every available CPU.
+    pub whisper_threads: usize,
+    /// Run whisper on the GPU (Metal), falling back to the CPU when unavailable.
+    pub use_gpu: bool,
+    /// Apply vocabulary corrections to the live partial text as well as the final.
+    pub correct_partials: bool,
+    pub notify_on_complete: bool,
+    pub append_mode: bool,
+    /// Dictations joined so far in append mode, waiting to be flushed.
+    pub append_buffer: String,
+    /// Pasted text is cut to this many characters at a word boundary; 0 means no limit.
+    pub max_paste_chars: usize,
+    pub formatting_profile: FormattingProfile,
+    /// Extra spoken-phrase rules applied on top of the profile's built-in ones.
+    pub formatting_rules: Vec<FormattingRule>,
+    /// How long an Error state stays up before returning to Idle; 0 keeps it until dismissed.
+    pub error_auto_dismiss_ms: u64,
+    /// Bumped on every error so a pending auto-dismiss can tell its error was replaced.
+    pub error_generation: u64,
+    pub auto_accept_corrections: bool,
+    pub auto_accept_translation: bool,
+    pub metrics_logging: bool,
+    /// Never download models; missing ones surface an error instead.
+    pub offline_mode: bool,
+    /// Preview keys ("enter", "escape") swallowed while a preview is showing.
+    pub intercepted_preview_keys: Vec<String>,
+    /// Swallow Enter while recording, transcribing, or translating.
+    pub guard_enter_while_recording: bool,
+    /// How long Processing/Translating may last before it's treated as stuck (0 disables).
+    pub processing_timeout_ms: u64,
+    /// Path template (may contain date tokens) every transcription is also appended to.
+    pub auto_append_file: Option<String>,
+    /// When off, transcriptions are never written to history.json.
+    pub history_enabled: bool,
+    /// Double-tapping the record hotkey cancels the recording instead of stopping it.
+    pub double_tap_cancel: bool,
+    pub quiet_hours: QuietHours,
+    /// Quiet hours are ignored until this moment; set by `override_quiet_hours`.
+    pub quiet_hours_override_until: Option<std::time::Instant>,
+    pub hotkey_during_processing: HotkeyDuringProcessing,
+    pub correction_stage: CorrectionStage,
+    /// The dictation whose result is still wanted, from entering Processing until
+    /// Idle or Error. Results for any other id were cancelled and are dropped.
+    pub active_dictation: Option<u64>,
+    pub last_dictation_id: u64,
+    /// Counts recordings started, so a delayed action can tell whether the
+    /// recording it was scheduled for is still the current one.
+    pub recording_generation: u64,
+    /// A hotkey press during Processing asked for a new recording once this one finishes.
+    pub queued_recording: bool,
     pub pending_original_text: Option<String>,
     pub pending_corrected_text: Option<String>,
     pub pending_source_text: Option<String>,
     pub pending_translated_text: Option<String>,
 }
 
+impl AppState {
+    /// The language for the current recording: the session override if set,
+    /// otherwise the persisted setting.
+    pub fn active_language(&self) -> &str {
+        self.session_language.as_deref().unwrap_or(&self.language)
+    }
+
+    /// The Whisper initial prompt for the active language: its preset (or its
+    /// base language's, so "es" covers "es-MX") if there is one, otherwise the
+    /// global prompt.
+    pub fn whisper_prompt(&self) -> Option<String> {
+        let language = self.active_language();
+        self.language_prompts
+            .get(language)
+            .or_else(|| self.language_prompts.get(crate::language::base(language)))
+            .or(self.initial_prompt.as_ref())
+            .filter(|prompt| !prompt.trim().is_empty())
+            .cloned()
+    }
+
+    /// Whether quiet hours currently block new recordings, taking a temporary
+    /// override into account.
+    pub fn quiet_hours_active(&self) -> bool {
+        let overridden = self
+            .quiet_hours_override_until
+            .is_some_and(|until| std::time::Instant::now() < until);
+        !overridden
+            && self
+                .quiet_hours
+                .is_active(chrono::Local::now().naive_local())
+    }
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self {
             dictation_state: DictationState::Idle,
             model_path: None,
             selected_model: String::from("base.en"),
+            custom_model_path: None,
             smart_paste: true,
+            target_app: None,
             language: String::from("en"),
+            session_language: None,
+            quick_english_restore: None,
+            initial_prompt: None,
+            language_prompts: HashMap::new(),
             vocab_enabled: true,
             translation_enabled: false,
             translation_target_lang: String::from("en"),
+            translation_target_variant: None,
             translation_model: String::from("nllb-200-distilled-600M-int8"),
+            translation_max_input_tokens: crate::translation::engine::DEFAULT_MAX_INPUT_TOKENS,
+            show_overlay: true,
+            overlay_show_delay_ms: 0,
+            result_only: false,
+            min_recording_ms: 400,
+            min_word_chars: 2,
+            max_recording_ms: 300_000,
+            max_recording_warning_ms: 5000,
+            too_quiet_threshold: 0.02,
+            vad_enabled: false,
+            silence_timeout_ms: 1500,
+            whisper_thresholds: WhisperThresholds::default(),
+            whisper_sampling: WhisperSampling::default(),
+            trim_silence: true,
+            input_device: None,
+            partial_window_secs: 15.0,
+            partial_model: None,
+            paragraph_on_pause: false,
+            strip_nonspeech_tags: true,
+            whisper_threads: 0,
+            use_gpu: true,
+            correct_partials: false,
+            notify_on_complete: false,
+            append_mode: false,
+            append_buffer: String::new(),
+            max_paste_chars: 0,
+            formatting_profile: FormattingProfile::Off,
+            formatting_rules: Vec::new(),
+            error_auto_dismiss_ms: 0,
+            error_generation: 0,
+            auto_accept_corrections: false,
+            auto_accept_translation: false,
+            metrics_logging: false,
+            offline_mode: false,
+            intercepted_preview_keys: vec!["enter".to_string(), "escape".to_string()],
+            guard_enter_while_recording: false,
+            processing_timeout_ms: 70_000,
+            auto_append_file: None,
+            history_enabled: true,
+            double_tap_cancel: false,
+            quiet_hours: QuietHours::default(),
+            quiet_hours_override_until: None,
+            hotkey_during_processing: HotkeyDuringProcessing::Ignore,
+            correction_stage: CorrectionStage::BeforeTranslation,
+            active_dictation: None,
+            last_dictation_id: 0,
+            recording_generation: 0,
+            queued_recording: false,
             pending_original_text: None,
             pending_corrected_text: None,
             pending_source_text: None,
@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::time::Duration;
+
+/// Length of the benchmark clip.
+pub const CLIP_SECS: f32 = 10.0;
+/// Shortest last recording worth benchmarking on instead of the synthetic clip.
+pub const MIN_SPEECH_SECS: f32 = 5.0;
+/// How many times the clip is transcribed; the first run also warms caches.
+pub const RUNS: usize = 3;
+
+const SAMPLE_RATE: u32 = 16000;
+
+/// What the benchmark transcribed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkClip {
+    /// The start of the user's last recording.
+    LastRecording,
+    /// The built-in `sample_clip`, used until there is a long enough recording.
+    Synthetic,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub runs: usize,
+    pub clip: BenchmarkClip,
+    pub clip_secs: f32,
+    pub average_ms: u64,
+    /// Processing time divided by audio length; below 1.0 is faster than real time.
+    pub real_time_factor: f32,
+    pub threads: i32,
+}
+
+impl BenchmarkResult {
+    pub fn from_timings(
+        timings: &[Duration],
+        clip: BenchmarkClip,
+        clip_secs: f32,
+        threads: i32,
+    ) -> Self {
+        let total: Duration = timings.iter().sum();
+        let average = total / timings.len().max(1) as u32;
+        Self {
+            runs: timings.len(),
+            clip,
+            clip_secs,
+            average_ms: average.as_millis() as u64,
+            real_time_factor: average.as_secs_f32() / clip_secs,
+            threads,
+        }
+    }
+}
+
+/// Picks the 16kHz audio to benchmark on: up to `CLIP_SECS` of the last
+/// recording, which is real speech from the user's own mic, or the synthetic
+/// clip if the recording is shorter than `MIN_SPEECH_SECS`.
+pub fn benchmark_clip(mut last_recording: Vec<f32>) -> (Vec<f32>, BenchmarkClip) {
+    if last_recording.len() < (MIN_SPEECH_SECS * SAMPLE_RATE as f32) as usize {
+        return (sample_clip(), BenchmarkClip::Synthetic);
+    }
+    last_recording.truncate((CLIP_SECS * SAMPLE_RATE as f32) as usize);
+    (last_recording, BenchmarkClip::LastRecording)
+}
+
+/// Length of a 16kHz clip in seconds.
+pub fn clip_secs(clip: &[f32]) -> f32 {
+    clip.len() as f32 / SAMPLE_RATE as f32
+}
+
+/// Builds the fallback clip: 16kHz mono syllable-like bursts of voiced
+/// harmonics with short gaps between them. Whisper decodes it to only a few
+/// tokens, so it measures the encoder but understates decoding cost; timings
+/// on it run faster than on real dictation of the same length.
+pub fn sample_clip() -> Vec<f32> {
+    let len = (CLIP_SECS * SAMPLE_RATE as f32) as usize;
+    // Rough vowel formants (Hz), cycled per syllable
+    const FORMANTS: [(f32, f32); 4] = [
+        (730.0, 1090.0),
+        (270.0, 2290.0),
+        (530.0, 1840.0),
+        (300.0, 870.0),
+    ];
+    const SYLLABLE_SECS: f32 = 0.25;
+
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let syllable = (t / SYLLABLE_SECS) as usize;
+            let phase = (t % SYLLABLE_SECS) / SYLLABLE_SECS;
+            // Voice for the first 80% of each syllable, then a short gap
+            if phase > 0.8 {
+                return 0.0;
+            }
+            let envelope = (phase / 0.8 * std::f32::consts::PI).sin();
+            let pitch = 120.0 + 20.0 * (syllable % 3) as f32;
+            let (f1, f2) = FORMANTS[syllable % FORMANTS.len()];
+            let tau = std::f32::consts::TAU;
+            let voiced =
+                (tau * pitch * t).sin() + 0.5 * (tau * f1 * t).sin() + 0.3 * (tau * f2 * t).sin();
+            0.2 * envelope * voiced
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_clip_length_and_range() {
+        let clip = sample_clip();
+        assert_eq!(clip.len(), (CLIP_SECS * SAMPLE_RATE as f32) as usize);
+        assert!(clip.iter().all(|s| s.abs() <= 1.0));
+        assert!(clip.iter().any(|s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_benchmark_clip_prefers_last_recording() {
+        let long = vec![0.1; (CLIP_SECS * 2.0 * SAMPLE_RATE as f32) as usize];
+        let (clip, source) = benchmark_clip(long);
+        assert_eq!(source, BenchmarkClip::LastRecording);
+        assert_eq!(clip_secs(&clip), CLIP_SECS);
+
+        let short = vec![0.1; SAMPLE_RATE as usize];
+        let (clip, source) = benchmark_clip(short);
+        assert_eq!(source, BenchmarkClip::Synthetic);
+        assert_eq!(clip, sample_clip());
+    }
+
+    #[test]
+    fn test_from_timings_averages_runs() {
+        let timings = [
+            Duration::from_millis(4000),
+            Duration::from_millis(5000),
+            Duration::from_millis(6000),
+        ];
+        let result =
+            BenchmarkResult::from_timings(&timings, BenchmarkClip::Synthetic, CLIP_SECS, 4);
+        assert_eq!(result.runs, 3);
+        assert_eq!(result.average_ms, 5000);
+        assert!((result.real_time_factor - 0.5).abs() < 1e-6);
+        assert_eq!(result.threads, 4);
+    }
+}
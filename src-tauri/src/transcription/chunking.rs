@@ -0,0 +1,131 @@
+use std::ops::Range;
+
+/// Length of each window a long audio file is split into.
+pub const CHUNK_SECS: usize = 30;
+/// How much consecutive windows share, so words cut at a boundary are heard whole
+/// in at least one of them.
+pub const OVERLAP_SECS: usize = 2;
+/// Most words the overlap region can plausibly hold; longer matches aren't searched.
+const MAX_OVERLAP_WORDS: usize = 12;
+/// Fewest repeated words treated as overlap. A single matching word is as likely
+/// to be spoken twice ("that that", "very very") as heard twice, so it is kept.
+const MIN_OVERLAP_WORDS: usize = 2;
+
+/// Splits `len` samples into windows of `chunk` samples where each window
+/// starts `overlap` samples before the previous one ends.
+pub fn chunk_ranges(len: usize, chunk: usize, overlap: usize) -> Vec<Range<usize>> {
+    assert!(chunk > overlap, "chunk must be longer than the overlap");
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let end = (start + chunk).min(len);
+        ranges.push(start..end);
+        if end == len {
+            break;
+        }
+        start = end - overlap;
+    }
+    ranges
+}
+
+/// Compares words ignoring case and surrounding punctuation, since the same
+/// word is often punctuated differently at the end of one window and the
+/// start of the next.
+fn same_word(a: &str, b: &str) -> bool {
+    let trim = |w: &str| {
+        w.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+    trim(a) == trim(b)
+}
+
+/// Appends the transcript of the next window to `text`, dropping the words at
+/// its start that repeat the end of `text` (the overlap region both windows heard).
+pub fn stitch(text: &str, next: &str) -> String {
+    let tail: Vec<&str> = text
+        .split_whitespace()
+        .rev()
+        .take(MAX_OVERLAP_WORDS)
+        .collect();
+    let tail: Vec<&str> = tail.into_iter().rev().collect();
+    let head: Vec<&str> = next.split_whitespace().take(MAX_OVERLAP_WORDS).collect();
+
+    // Longest run of words that ends `text` and starts `next`
+    let overlap = (MIN_OVERLAP_WORDS..=tail.len().min(head.len()))
+        .rev()
+        .find(|&n| {
+            tail[tail.len() - n..]
+                .iter()
+                .zip(&head[..n])
+                .all(|(a, b)| same_word(a, b))
+        })
+        .unwrap_or(0);
+
+    // Skip the repeated words but keep the rest of `next` as-is (paragraph breaks included)
+    let mut rest = next.trim_start();
+    for _ in 0..overlap {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+
+    match (text.is_empty(), rest.is_empty()) {
+        (_, true) => text.to_string(),
+        (true, false) => rest.to_string(),
+        (false, false) => format!("{} {}", text, rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_ranges_overlap() {
+        assert_eq!(chunk_ranges(25, 10, 2), vec![0..10, 8..18, 16..25]);
+        assert_eq!(chunk_ranges(10, 10, 2), vec![0..10]);
+        assert_eq!(chunk_ranges(5, 10, 2), vec![0..5]);
+        assert!(chunk_ranges(0, 10, 2).is_empty());
+    }
+
+    #[test]
+    fn test_stitch_drops_repeated_overlap() {
+        assert_eq!(
+            stitch("we went to the store and", "the store and bought milk"),
+            "we went to the store and bought milk"
+        );
+    }
+
+    #[test]
+    fn test_stitch_ignores_case_and_punctuation() {
+        assert_eq!(
+            stitch("It was late at night.", "At night, we left."),
+            "It was late at night. we left."
+        );
+    }
+
+    #[test]
+    fn test_stitch_without_overlap_appends() {
+        assert_eq!(
+            stitch("first part", "second part"),
+            "first part second part"
+        );
+        assert_eq!(stitch("", "only next"), "only next");
+        assert_eq!(stitch("only text", ""), "only text");
+    }
+
+    #[test]
+    fn test_stitch_keeps_single_repeated_word() {
+        assert_eq!(
+            stitch("I think that", "that we should go"),
+            "I think that that we should go"
+        );
+    }
+
+    #[test]
+    fn test_stitch_keeps_paragraph_breaks_after_overlap() {
+        assert_eq!(
+            stitch("end of one", "of one more\n\nNew paragraph"),
+            "end of one more\n\nNew paragraph"
+        );
+    }
+}
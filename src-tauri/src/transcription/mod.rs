@@ -1,2 +1,5 @@
+pub mod benchmark;
+pub mod chunking;
 pub mod model_manager;
+pub mod segments;
 pub mod whisper;
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
-use futures_util::StreamExt;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 
 pub struct ModelInfo {
     pub name: &'static str,
@@ -76,7 +76,12 @@ pub const AVAILABLE_MODELS: [ModelInfo; 9] = [
     },
 ];
 
-const HF_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+/// Model name used when the user has pointed Wren at their own ggml model file.
+/// The path itself lives in config as `custom_model_path`.
+pub const CUSTOM_MODEL_NAME: &str = "custom";
+
+/// Path of the whisper.cpp model repo under the download base URL.
+const WHISPER_REPO_PATH: &str = "ggerganov/whisper.cpp/resolve/main";
 
 pub fn models_dir() -> PathBuf {
     let data_dir = dirs::data_dir().expect("Could not determine data directory");
@@ -87,18 +92,48 @@ fn find_model(model_name: &str) -> Option<&'static ModelInfo> {
     AVAILABLE_MODELS.iter().find(|m| m.name == model_name)
 }
 
+/// A model file smaller than this fraction of its listed size is treated as a
+/// leftover from an interrupted download rather than a usable model.
+const MIN_SIZE_RATIO: f64 = 0.9;
+
+/// Whether the model is on disk at a plausible size. A file that is far too
+/// small reports as missing, so it gets downloaded again instead of failing to load.
 pub fn model_exists(model_name: &str) -> bool {
-    match find_model(model_name) {
-        Some(model) => models_dir().join(model.filename).exists(),
-        None => false,
+    let Some(model) = find_model(model_name) else {
+        return false;
+    };
+    let path = models_dir().join(model.filename);
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return false;
+    };
+
+    let expected_bytes = model.size_mb as f64 * 1_000_000.0;
+    if (metadata.len() as f64) < expected_bytes * MIN_SIZE_RATIO {
+        log::warn!(
+            "Model file {:?} is only {} bytes (expected about {} MB); treating it as missing",
+            path,
+            metadata.len(),
+            model.size_mb
+        );
+        return false;
     }
+    true
 }
 
 pub fn model_path(model_name: &str) -> Option<PathBuf> {
     find_model(model_name).map(|model| models_dir().join(model.filename))
 }
 
-pub async fn download_model<F>(model_name: &str, progress_callback: F) -> Result<PathBuf>
+/// Downloads a model into a `.part` file and renames it into place once complete,
+/// so an interrupted download never leaves a truncated model behind.
+/// Setting `cancel` or a stalled connection aborts the download and removes the
+/// partial file. `base_url` is Hugging Face or a mirror with the same layout.
+pub async fn download_model<F>(
+    model_name: &str,
+    base_url: &str,
+    cancel: &AtomicBool,
+    progress_callback: F,
+) -> Result<PathBuf>
 where
     F: Fn(u64, u64),
 {
@@ -108,27 +143,45 @@ where
     std::fs::create_dir_all(&dir)?;
 
     let dest = dir.join(model.filename);
-    let url = format!("{}/{}", HF_BASE_URL, model.filename);
+    let url = format!("{}/{}/{}", base_url, WHISPER_REPO_PATH, model.filename);
 
-    let response = reqwest::get(&url).await?;
+    let response = crate::download::client()?.get(&url).send().await?;
 
     let total = response.content_length().unwrap_or(0);
     let mut downloaded: u64 = 0;
 
-    let mut file = std::fs::File::create(&dest)?;
-    let mut stream = response.bytes_stream();
-
-    use std::io::Write;
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        file.write_all(&chunk)?;
-        downloaded += chunk.len() as u64;
+    let tmp = dest.with_extension("part");
+    crate::download::write_body(response, &tmp, cancel, |len| {
+        downloaded += len;
         progress_callback(downloaded, total);
-    }
+    })
+    .await?;
 
+    std::fs::rename(&tmp, &dest)?;
     Ok(dest)
 }
 
+/// Steps through `AVAILABLE_MODELS` from `current`, forwards or backwards
+/// (wrapping at the ends) and returns the first other model `eligible` accepts.
+/// A custom or unknown `current` starts from the beginning (or end) of the list.
+pub fn cycle_model<F>(current: &str, forward: bool, eligible: F) -> Option<&'static str>
+where
+    F: Fn(&ModelInfo) -> bool,
+{
+    let count = AVAILABLE_MODELS.len();
+    let start = AVAILABLE_MODELS.iter().position(|m| m.name == current);
+    (1..=count)
+        .map(|offset| match (start, forward) {
+            (Some(i), true) => (i + offset) % count,
+            (Some(i), false) => (i + count - offset) % count,
+            (None, true) => offset - 1,
+            (None, false) => count - offset,
+        })
+        .map(|i| &AVAILABLE_MODELS[i])
+        .find(|m| m.name != current && eligible(m))
+        .map(|m| m.name)
+}
+
 /// Returns the multilingual equivalent of an English-only model name.
 pub fn multilingual_equivalent(model_name: &str) -> Option<&'static str> {
     match model_name {
@@ -151,7 +204,8 @@ pub fn english_equivalent(model_name: &str) -> Option<&'static str> {
     }
 }
 
-/// Returns whether a model is English-only.
+/// Returns whether a model is English-only. Custom models are treated as
+/// multilingual since we can't tell what they were trained on.
 pub fn is_english_only(model_name: &str) -> bool {
     find_model(model_name)
         .map(|m| m.english_only)
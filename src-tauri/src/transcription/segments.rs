@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+/// Silence between segments, in milliseconds, that starts a new paragraph.
+pub const PARAGRAPH_GAP_MS: i64 = 1500;
+
+/// A decoded whisper segment with its start/end time in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Segment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// Joins segments into a single transcript. With `paragraph_on_pause`, a gap
+/// longer than `PARAGRAPH_GAP_MS` between segments becomes a blank line;
+/// otherwise segment text is concatenated as whisper produced it.
+pub fn join_segments(segments: &[Segment], paragraph_on_pause: bool) -> String {
+    let mut text = String::new();
+    let mut prev_end: Option<i64> = None;
+
+    for segment in segments {
+        let paragraph_break = paragraph_on_pause
+            && prev_end.is_some_and(|end| segment.start_ms - end > PARAGRAPH_GAP_MS);
+
+        if paragraph_break {
+            text.truncate(text.trim_end().len());
+            text.push_str("\n\n");
+            text.push_str(segment.text.trim_start());
+        } else {
+            text.push_str(&segment.text);
+        }
+        prev_end = Some(segment.end_ms);
+    }
+
+    text.trim().to_string()
+}
+
+/// Whether a segment is only non-speech annotations, such as `[BLANK_AUDIO]`,
+/// `(wind blowing)` or `♪♪♪`, with no dictated words around them.
+pub fn is_nonspeech(text: &str) -> bool {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return false;
+    }
+    while let Some(c) = rest.chars().next() {
+        let closer = match c {
+            '[' => Some(']'),
+            '(' => Some(')'),
+            _ => None,
+        };
+        rest = match closer {
+            Some(closer) => match rest.find(closer) {
+                Some(end) => &rest[end + closer.len_utf8()..],
+                None => return false,
+            },
+            None if matches!(c, '♪' | '♫' | '♬' | '♩') => &rest[c.len_utf8()..],
+            None => return false,
+        }
+        .trim_start();
+    }
+    true
+}
+
+/// Drops segments that are only non-speech annotations (see `is_nonspeech`).
+/// Parenthetical text inside a segment with real words is kept.
+pub fn strip_nonspeech(segments: Vec<Segment>) -> Vec<Segment> {
+    segments
+        .into_iter()
+        .filter(|segment| !is_nonspeech(&segment.text))
+        .collect()
+}
+
+/// Whether `text` has at least one word with `min_word_chars` or more letters or
+/// digits, so lone punctuation (".") or a stray letter isn't mistaken for
+/// dictation. A minimum of 0 accepts anything.
+pub fn has_meaningful_content(text: &str, min_word_chars: usize) -> bool {
+    min_word_chars == 0
+        || text
+            .split_whitespace()
+            .any(|word| word.chars().filter(|c| c.is_alphanumeric()).count() >= min_word_chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(start_ms: i64, end_ms: i64, text: &str) -> Segment {
+        Segment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_flat_output_by_default() {
+        let segments = vec![seg(0, 1000, " First point."), seg(4000, 5000, " Second.")];
+        assert_eq!(join_segments(&segments, false), "First point. Second.");
+    }
+
+    #[test]
+    fn test_long_pause_starts_paragraph() {
+        let segments = vec![
+            seg(0, 1000, " First point."),
+            seg(1200, 2000, " Same paragraph."),
+            seg(4000, 5000, " Second point."),
+        ];
+        assert_eq!(
+            join_segments(&segments, true),
+            "First point. Same paragraph.\n\nSecond point."
+        );
+    }
+
+    #[test]
+    fn test_nonspeech_tags() {
+        assert!(is_nonspeech(" [BLANK_AUDIO]"));
+        assert!(is_nonspeech("(wind blowing)"));
+        assert!(is_nonspeech(" ♪♪♪"));
+        assert!(is_nonspeech("♪ (music) ♪"));
+        assert!(is_nonspeech("[Music] [Applause]"));
+        assert!(!is_nonspeech(""));
+        assert!(!is_nonspeech(" Call me (maybe) tomorrow."));
+        assert!(!is_nonspeech("(laughs) That's funny."));
+        assert!(!is_nonspeech("[unclosed"));
+    }
+
+    #[test]
+    fn test_strip_nonspeech_keeps_speech_segments() {
+        let segments = vec![
+            seg(0, 1000, " [BLANK_AUDIO]"),
+            seg(1000, 2000, " Take the (blue) one."),
+            seg(2000, 3000, " ♪♪"),
+        ];
+        assert_eq!(
+            join_segments(&strip_nonspeech(segments), false),
+            "Take the (blue) one."
+        );
+    }
+
+    #[test]
+    fn test_punctuation_only_is_not_meaningful() {
+        assert!(!has_meaningful_content(".", 2));
+        assert!(!has_meaningful_content(" ... ?! ", 2));
+        assert!(!has_meaningful_content("", 1));
+        assert!(has_meaningful_content(".", 0));
+    }
+
+    #[test]
+    fn test_single_short_token() {
+        assert!(!has_meaningful_content("I.", 2));
+        assert!(!has_meaningful_content("a - b", 2));
+        assert!(has_meaningful_content("Hi.", 2));
+        assert!(has_meaningful_content("you", 2));
+        assert!(!has_meaningful_content("you", 4));
+        assert!(has_meaningful_content("See you tomorrow", 4));
+    }
+}
@@ -1,20 +1,199 @@
-use std::sync::mpsc;
+use super::segments::{self, Segment};
+use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
 use whisper_rs::{
     FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
 };
 
+pub const DEFAULT_NO_SPEECH_THRESHOLD: f32 = 0.6;
+pub const DEFAULT_TEMPERATURE: f32 = 0.0;
+pub const DEFAULT_ENTROPY_THRESHOLD: f32 = 2.4;
+/// Thread count used when the number of CPUs can't be determined.
+const FALLBACK_THREADS: usize = 4;
+/// Error returned when a dictation's final transcription is cancelled through `abort`.
+pub const TRANSCRIPTION_CANCELLED: &str = "Transcription cancelled";
+
+/// Decoder thresholds that decide whether a segment counts as speech.
+/// Defaults match whisper.cpp's own.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WhisperThresholds {
+    pub no_speech_threshold: f32,
+    pub temperature: f32,
+    pub entropy_threshold: f32,
+}
+
+impl Default for WhisperThresholds {
+    fn default() -> Self {
+        Self {
+            no_speech_threshold: DEFAULT_NO_SPEECH_THRESHOLD,
+            temperature: DEFAULT_TEMPERATURE,
+            entropy_threshold: DEFAULT_ENTROPY_THRESHOLD,
+        }
+    }
+}
+
+impl WhisperThresholds {
+    /// Clamps each value into the range whisper accepts.
+    pub fn clamped(self) -> Self {
+        Self {
+            no_speech_threshold: self.no_speech_threshold.clamp(0.0, 1.0),
+            temperature: self.temperature.clamp(0.0, 1.0),
+            entropy_threshold: self.entropy_threshold.clamp(0.0, 10.0),
+        }
+    }
+}
+
+/// Sampling effort for final transcriptions. Partials always use `FAST`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WhisperSampling {
+    /// Candidates sampled per decode; the best is kept.
+    pub best_of: u32,
+    /// How many times decoding is retried at a higher temperature when the
+    /// result looks unreliable. 0 disables fallback.
+    pub temperature_fallbacks: u32,
+}
+
+pub const MAX_BEST_OF: u32 = 8;
+pub const MAX_TEMPERATURE_FALLBACKS: u32 = 10;
+
+impl Default for WhisperSampling {
+    /// Matches whisper.cpp's defaults: greedy with a 0.2 temperature step up to 1.0.
+    fn default() -> Self {
+        Self {
+            best_of: 1,
+            temperature_fallbacks: 5,
+        }
+    }
+}
+
+impl WhisperSampling {
+    /// Single greedy pass with no retries, for partial transcripts.
+    const FAST: Self = Self {
+        best_of: 1,
+        temperature_fallbacks: 0,
+    };
+
+    /// Rejects values outside the ranges whisper handles sensibly.
+    pub fn validated(self) -> Result<Self, String> {
+        if !(1..=MAX_BEST_OF).contains(&self.best_of) {
+            return Err(format!("best_of must be between 1 and {}", MAX_BEST_OF));
+        }
+        if self.temperature_fallbacks > MAX_TEMPERATURE_FALLBACKS {
+            return Err(format!(
+                "Temperature fallbacks must be at most {}",
+                MAX_TEMPERATURE_FALLBACKS
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Temperature step that reaches 1.0 from `temperature` in `temperature_fallbacks` retries.
+    fn temperature_inc(self, temperature: f32) -> f32 {
+        if self.temperature_fallbacks == 0 {
+            0.0
+        } else {
+            (1.0 - temperature).max(0.0) / self.temperature_fallbacks as f32
+        }
+    }
+}
+
+/// A finished transcription.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub text: String,
+    /// Language whisper detected (e.g. "de"), set only when the language is auto-detected.
+    pub detected_language: Option<String>,
+}
+
+/// Number of CPUs whisper may use.
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(FALLBACK_THREADS)
+}
+
+/// Threads to decode with for a `whisper_threads` setting: 0 means all
+/// available CPUs, anything else is clamped to 1..=available.
+pub fn resolve_threads(requested: usize) -> usize {
+    let available = available_threads();
+    if requested == 0 {
+        available
+    } else {
+        requested.clamp(1, available)
+    }
+}
+
+/// Loads a whisper model and creates its decoding state. With `use_gpu`, a
+/// model that can't be loaded on the GPU is loaded on the CPU instead.
+fn load_context(path: &str, use_gpu: bool) -> Result<(WhisperContext, WhisperState), String> {
+    let mut params = WhisperContextParameters::default();
+    params.use_gpu(use_gpu);
+    let ctx = match WhisperContext::new_with_params(path, params) {
+        Ok(ctx) => ctx,
+        Err(e) if use_gpu => {
+            log::warn!(
+                "GPU unavailable ({:?}); loading Whisper model on the CPU",
+                e
+            );
+            let mut params = WhisperContextParameters::default();
+            params.use_gpu(false);
+            WhisperContext::new_with_params(path, params)
+                .map_err(|e| format!("Failed to load Whisper model: {:?}", e))?
+        }
+        Err(e) => return Err(format!("Failed to load Whisper model: {:?}", e)),
+    };
+
+    let state = ctx
+        .create_state()
+        .map_err(|e| format!("Failed to create whisper state: {:?}", e))?;
+
+    Ok((ctx, state))
+}
+
 struct TranscriptionService {
     context: Option<WhisperContext>,
     state: Option<WhisperState>,
+    model_path: Option<String>,
+    /// Optional lighter model used only for partials; the main model is used when unset.
+    partial_context: Option<WhisperContext>,
+    partial_state: Option<WhisperState>,
+    partial_model_path: Option<String>,
+    /// Run models on the GPU (Metal) when available.
+    use_gpu: bool,
     language: Option<String>,
+    n_threads: usize,
+    /// Text given to the decoder as preceding context, e.g. names and terms to spell.
+    initial_prompt: Option<String>,
+    thresholds: WhisperThresholds,
+    sampling: WhisperSampling,
+    paragraph_on_pause: bool,
+    strip_nonspeech_tags: bool,
+    /// Set from outside to the id of the dictation whose final transcription
+    /// should stop. Only a transcription for that same dictation checks it, so
+    /// a cancel never outlives its own request.
+    abort: Arc<AtomicU64>,
 }
 
 impl TranscriptionService {
-    fn new() -> Self {
+    fn new(abort: Arc<AtomicU64>, use_gpu: bool) -> Self {
         Self {
             context: None,
             state: None,
+            model_path: None,
+            partial_context: None,
+            partial_state: None,
+            partial_model_path: None,
+            use_gpu,
             language: Some("en".to_string()),
+            n_threads: resolve_threads(0),
+            initial_prompt: None,
+            thresholds: WhisperThresholds::default(),
+            sampling: WhisperSampling::default(),
+            paragraph_on_pause: false,
+            strip_nonspeech_tags: true,
+            abort,
         }
     }
 
@@ -22,73 +201,271 @@ impl TranscriptionService {
         // Drop existing state before replacing context
         self.state = None;
 
-        let ctx = WhisperContext::new_with_params(path, WhisperContextParameters::default())
-            .map_err(|e| format!("Failed to load Whisper model: {:?}", e))?;
-
-        let state = ctx
-            .create_state()
-            .map_err(|e| format!("Failed to create whisper state: {:?}", e))?;
-
+        let (ctx, state) = load_context(path, self.use_gpu)?;
         self.context = Some(ctx);
         self.state = Some(state);
+        self.model_path = Some(path.to_string());
         Ok(())
     }
 
-    fn transcribe(&mut self, audio_data: &[f32]) -> Result<String, String> {
-        let state = self
-            .state
-            .as_mut()
-            .ok_or_else(|| "Model not loaded".to_string())?;
+    /// Loads the model used for partials, or with `None` unloads it so partials
+    /// go back to the main model.
+    fn load_partial_model(&mut self, path: Option<&str>) -> Result<(), String> {
+        if path.is_some() && path == self.partial_model_path.as_deref() {
+            return Ok(());
+        }
+        self.partial_state = None;
+        self.partial_context = None;
+        self.partial_model_path = None;
 
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        params.set_n_threads(4);
+        if let Some(path) = path {
+            let (ctx, state) = load_context(path, self.use_gpu)?;
+            self.partial_context = Some(ctx);
+            self.partial_state = Some(state);
+            self.partial_model_path = Some(path.to_string());
+        }
+        Ok(())
+    }
+
+    /// Switches between GPU and CPU, reloading any loaded models since the
+    /// choice only applies when a model is loaded.
+    fn set_use_gpu(&mut self, use_gpu: bool) -> Result<(), String> {
+        if use_gpu == self.use_gpu {
+            return Ok(());
+        }
+        self.use_gpu = use_gpu;
+
+        if let Some(path) = self.model_path.clone() {
+            self.load_model(&path)?;
+        }
+        if let Some(path) = self.partial_model_path.take() {
+            self.load_partial_model(Some(&path))?;
+        }
+        Ok(())
+    }
+
+    /// Runs `transcribe`, recovering from a panic inside whisper by reloading the
+    /// model so later requests keep working instead of timing out forever.
+    fn transcribe_guarded(
+        &mut self,
+        audio_data: &[f32],
+        partial: bool,
+        dictation: Option<u64>,
+    ) -> Result<Transcript, String> {
+        self.guarded(|service| service.transcribe(audio_data, partial, dictation))
+    }
+
+    /// Runs `run` against the service, reloading the models if whisper panics.
+    fn guarded<T>(
+        &mut self,
+        run: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        match panic::catch_unwind(AssertUnwindSafe(|| run(self))) {
+            Ok(result) => result,
+            Err(_) => {
+                log::error!("Whisper panicked during transcription; reloading model");
+                self.recover();
+                Err("Transcription engine crashed and was restarted — try again".to_string())
+            }
+        }
+    }
+
+    fn recover(&mut self) {
+        // The whisper state may be left inconsistent by the panic; leak it rather
+        // than run its destructor
+        std::mem::forget(self.state.take());
+        std::mem::forget(self.context.take());
+        std::mem::forget(self.partial_state.take());
+        std::mem::forget(self.partial_context.take());
+
+        if let Some(path) = self.model_path.clone() {
+            if let Err(e) = self.load_model(&path) {
+                log::error!("Failed to reload model after crash: {}", e);
+            }
+        }
+        if let Some(path) = self.partial_model_path.clone() {
+            if let Err(e) = self.load_partial_model(Some(&path)) {
+                log::error!("Failed to reload partial model after crash: {}", e);
+            }
+        }
+    }
+
+    /// Times one transcription of a benchmark clip with the loaded model.
+    fn benchmark(&mut self, clip: &[f32]) -> Result<std::time::Duration, String> {
+        if self.state.is_none() {
+            return Err("Model not loaded".to_string());
+        }
+
+        let start = std::time::Instant::now();
+        self.transcribe_guarded(clip, false, None)?;
+        Ok(start.elapsed())
+    }
+
+    /// Transcribes `audio_data`. Partials use the partial model if one is loaded,
+    /// the fast sampling profile, and skip paragraph detection; finals use the
+    /// configured sampling and, with `paragraph_on_pause`, keep segment
+    /// timestamps so long pauses become paragraph breaks. A final for
+    /// `dictation` stops early if that dictation is cancelled.
+    fn transcribe(
+        &mut self,
+        audio_data: &[f32],
+        partial: bool,
+        dictation: Option<u64>,
+    ) -> Result<Transcript, String> {
+        let paragraphs = !partial && self.paragraph_on_pause;
+        let (decoded, detected_language) =
+            self.decode(audio_data, partial, dictation, paragraphs, false)?;
+        Ok(Transcript {
+            text: segments::join_segments(&decoded, paragraphs),
+            detected_language,
+        })
+    }
+
+    /// Transcribes `audio_data` as a final, keeping each segment's timing (with
+    /// token-level timestamps for more precise boundaries), for captions.
+    fn transcribe_timestamped(&mut self, audio_data: &[f32]) -> Result<Vec<Segment>, String> {
+        let (decoded, _) = self.decode(audio_data, false, None, true, true)?;
+        Ok(decoded)
+    }
+
+    /// Runs whisper over `audio_data` and returns its segments and, when the
+    /// language is auto-detected, the language it heard. Segment times are only
+    /// meaningful with `timestamps` set.
+    fn decode(
+        &mut self,
+        audio_data: &[f32],
+        partial: bool,
+        dictation: Option<u64>,
+        timestamps: bool,
+        token_timestamps: bool,
+    ) -> Result<(Vec<Segment>, Option<String>), String> {
+        let sampling = if partial {
+            WhisperSampling::FAST
+        } else {
+            self.sampling
+        };
+        let state = match self.partial_state.as_mut().filter(|_| partial) {
+            Some(state) => state,
+            None => self
+                .state
+                .as_mut()
+                .ok_or_else(|| "Model not loaded".to_string())?,
+        };
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy {
+            best_of: sampling.best_of as i32,
+        });
+        params.set_n_threads(self.n_threads as i32);
         params.set_language(self.language.as_deref());
+        if let Some(prompt) = self.initial_prompt.as_deref() {
+            params.set_initial_prompt(prompt);
+        }
         params.set_no_context(true);
         params.set_single_segment(false);
         params.set_suppress_blank(true);
         params.set_suppress_nst(true);
-        params.set_no_timestamps(true);
+        params.set_no_timestamps(!timestamps);
+        params.set_token_timestamps(token_timestamps);
         params.set_print_progress(false);
+        params.set_no_speech_thold(self.thresholds.no_speech_threshold);
+        params.set_temperature(self.thresholds.temperature);
+        params.set_temperature_inc(sampling.temperature_inc(self.thresholds.temperature));
+        params.set_entropy_thold(self.thresholds.entropy_threshold);
+        if let Some(id) = dictation {
+            let abort = Arc::clone(&self.abort);
+            params.set_abort_callback_safe(move || abort.load(Ordering::SeqCst) == id);
+        }
+
+        let result = state.full(params, audio_data);
+        if dictation.is_some_and(|id| self.abort.load(Ordering::SeqCst) == id) {
+            return Err(TRANSCRIPTION_CANCELLED.to_string());
+        }
+        result.map_err(|e| format!("Transcription failed: {:?}", e))?;
 
-        state
-            .full(params, audio_data)
-            .map_err(|e| format!("Transcription failed: {:?}", e))?;
+        let detected_language = if self.language.is_none() {
+            state
+                .full_lang_id_from_state()
+                .ok()
+                .and_then(whisper_rs::get_lang_str)
+                .map(str::to_string)
+        } else {
+            None
+        };
 
-        let mut text = String::new();
+        // Timestamps are in centiseconds
+        let mut collected = Vec::new();
         for segment in state.as_iter() {
             if let Ok(s) = segment.to_str_lossy() {
-                text.push_str(&s);
+                collected.push(Segment {
+                    start_ms: segment.start_timestamp() * 10,
+                    end_ms: segment.end_timestamp() * 10,
+                    text: s.into_owned(),
+                });
             }
         }
 
-        Ok(text.trim().to_string())
+        if self.strip_nonspeech_tags {
+            collected = segments::strip_nonspeech(collected);
+        }
+        Ok((collected, detected_language))
     }
 }
 
 pub enum TranscriptionRequest {
     LoadModel(String),
+    /// Loads (or with `None` unloads) the model used only for partials, replying
+    /// on the given channel so it doesn't interleave with main model loads.
+    LoadPartialModel(Option<String>, mpsc::Sender<Result<(), String>>),
+    /// Switches between GPU and CPU, reloading loaded models; replies once done.
+    SetUseGpu(bool, mpsc::Sender<Result<(), String>>),
     SetLanguage(Option<String>),
-    Transcribe(Vec<f32>),
+    SetInitialPrompt(Option<String>),
+    /// Decoding threads, as a `whisper_threads` setting (0 for all CPUs).
+    SetThreads(usize),
+    SetThresholds(WhisperThresholds),
+    SetParagraphOnPause(bool),
+    SetStripNonspeechTags(bool),
+    SetSampling(WhisperSampling),
+    /// A final transcription, tagged with the dictation it belongs to (if any)
+    /// so it can be cancelled through the worker's `abort` id.
+    Transcribe(Vec<f32>, Option<u64>),
+    /// A final transcription answered with per-segment timings (`TimestampedComplete`).
+    TranscribeTimestamped(Vec<f32>),
     TranscribePartial(Vec<f32>),
+    /// One timed run of the benchmark clip; the caller repeats it.
+    Benchmark(Vec<f32>),
+    /// Answered on the given channel with whether a model is loaded, so callers can
+    /// check the thread is alive without touching the shared response channel.
+    Ping(mpsc::Sender<bool>),
     Shutdown,
 }
 
 pub enum TranscriptionResponse {
     ModelLoaded(Result<(), String>),
-    TranscriptionComplete(Result<String, String>),
+    TranscriptionComplete(Result<Transcript, String>),
+    TimestampedComplete(Result<Vec<Segment>, String>),
+    BenchmarkComplete(Result<std::time::Duration, String>),
 }
 
-pub fn spawn_transcription_thread() -> (
+/// Spawns the whisper worker. Setting `abort` to a dictation id stops that
+/// dictation's final transcription, which then completes with
+/// `TRANSCRIPTION_CANCELLED`.
+/// Models are loaded on the GPU when `use_gpu` is set (see `SetUseGpu`).
+pub fn spawn_transcription_thread(
+    abort: Arc<AtomicU64>,
+    use_gpu: bool,
+) -> (
     mpsc::Sender<TranscriptionRequest>,
     mpsc::Receiver<TranscriptionResponse>,
     mpsc::Receiver<String>,
+    std::thread::JoinHandle<()>,
 ) {
     let (req_tx, req_rx) = mpsc::channel::<TranscriptionRequest>();
     let (resp_tx, resp_rx) = mpsc::channel::<TranscriptionResponse>();
     let (partial_tx, partial_rx) = mpsc::channel::<String>();
 
-    std::thread::spawn(move || {
-        let mut service = TranscriptionService::new();
+    let handle = std::thread::spawn(move || {
+        let mut service = TranscriptionService::new(abort, use_gpu);
 
         while let Ok(request) = req_rx.recv() {
             match request {
@@ -96,13 +473,42 @@ pub fn spawn_transcription_thread() -> (
                     let result = service.load_model(&path);
                     let _ = resp_tx.send(TranscriptionResponse::ModelLoaded(result));
                 }
+                TranscriptionRequest::LoadPartialModel(path, reply) => {
+                    let _ = reply.send(service.load_partial_model(path.as_deref()));
+                }
+                TranscriptionRequest::SetUseGpu(use_gpu, reply) => {
+                    let _ = reply.send(service.set_use_gpu(use_gpu));
+                }
                 TranscriptionRequest::SetLanguage(lang) => {
                     service.language = lang;
                 }
-                TranscriptionRequest::Transcribe(audio_data) => {
-                    let result = service.transcribe(&audio_data);
+                TranscriptionRequest::SetInitialPrompt(prompt) => {
+                    service.initial_prompt = prompt;
+                }
+                TranscriptionRequest::SetThreads(threads) => {
+                    service.n_threads = resolve_threads(threads);
+                }
+                TranscriptionRequest::SetThresholds(thresholds) => {
+                    service.thresholds = thresholds.clamped();
+                }
+                TranscriptionRequest::SetParagraphOnPause(enabled) => {
+                    service.paragraph_on_pause = enabled;
+                }
+                TranscriptionRequest::SetStripNonspeechTags(enabled) => {
+                    service.strip_nonspeech_tags = enabled;
+                }
+                TranscriptionRequest::SetSampling(sampling) => {
+                    service.sampling = sampling;
+                }
+                TranscriptionRequest::Transcribe(audio_data, dictation) => {
+                    let result = service.transcribe_guarded(&audio_data, false, dictation);
                     let _ = resp_tx.send(TranscriptionResponse::TranscriptionComplete(result));
                 }
+                TranscriptionRequest::TranscribeTimestamped(audio_data) => {
+                    let result =
+                        service.guarded(|service| service.transcribe_timestamped(&audio_data));
+                    let _ = resp_tx.send(TranscriptionResponse::TimestampedComplete(result));
+                }
                 TranscriptionRequest::TranscribePartial(audio_data) => {
                     // Drain stale partials — only process the newest one
                     let mut latest_audio = audio_data;
@@ -112,33 +518,78 @@ pub fn spawn_transcription_thread() -> (
                             TranscriptionRequest::TranscribePartial(newer) => {
                                 latest_audio = newer;
                             }
-                            TranscriptionRequest::Transcribe(final_audio) => {
-                                got_final = Some(final_audio);
+                            TranscriptionRequest::Transcribe(final_audio, dictation) => {
+                                got_final = Some((final_audio, dictation));
                                 break;
                             }
                             TranscriptionRequest::LoadModel(path) => {
                                 let result = service.load_model(&path);
                                 let _ = resp_tx.send(TranscriptionResponse::ModelLoaded(result));
                             }
+                            TranscriptionRequest::LoadPartialModel(path, reply) => {
+                                let _ = reply.send(service.load_partial_model(path.as_deref()));
+                            }
+                            TranscriptionRequest::SetUseGpu(use_gpu, reply) => {
+                                let _ = reply.send(service.set_use_gpu(use_gpu));
+                            }
                             TranscriptionRequest::SetLanguage(lang) => {
                                 service.language = lang;
                             }
+                            TranscriptionRequest::SetInitialPrompt(prompt) => {
+                                service.initial_prompt = prompt;
+                            }
+                            TranscriptionRequest::SetThreads(threads) => {
+                                service.n_threads = resolve_threads(threads);
+                            }
+                            TranscriptionRequest::SetThresholds(thresholds) => {
+                                service.thresholds = thresholds.clamped();
+                            }
+                            TranscriptionRequest::SetParagraphOnPause(enabled) => {
+                                service.paragraph_on_pause = enabled;
+                            }
+                            TranscriptionRequest::SetStripNonspeechTags(enabled) => {
+                                service.strip_nonspeech_tags = enabled;
+                            }
+                            TranscriptionRequest::SetSampling(sampling) => {
+                                service.sampling = sampling;
+                            }
+                            TranscriptionRequest::TranscribeTimestamped(audio_data) => {
+                                let result = service
+                                    .guarded(|service| service.transcribe_timestamped(&audio_data));
+                                let _ = resp_tx
+                                    .send(TranscriptionResponse::TimestampedComplete(result));
+                            }
+                            TranscriptionRequest::Benchmark(clip) => {
+                                let result = service.benchmark(&clip);
+                                let _ =
+                                    resp_tx.send(TranscriptionResponse::BenchmarkComplete(result));
+                            }
+                            TranscriptionRequest::Ping(reply) => {
+                                let _ = reply.send(service.state.is_some());
+                            }
                             TranscriptionRequest::Shutdown => {
                                 return;
                             }
                         }
                     }
 
-                    if let Some(final_audio) = got_final {
-                        let result = service.transcribe(&final_audio);
+                    if let Some((final_audio, dictation)) = got_final {
+                        let result = service.transcribe_guarded(&final_audio, false, dictation);
                         let _ = resp_tx.send(TranscriptionResponse::TranscriptionComplete(result));
                     } else {
-                        let result = service.transcribe(&latest_audio);
-                        if let Ok(text) = result {
-                            let _ = partial_tx.send(text.trim().to_string());
+                        let result = service.transcribe_guarded(&latest_audio, true, None);
+                        if let Ok(transcript) = result {
+                            let _ = partial_tx.send(transcript.text.trim().to_string());
                         }
                     }
                 }
+                TranscriptionRequest::Benchmark(clip) => {
+                    let result = service.benchmark(&clip);
+                    let _ = resp_tx.send(TranscriptionResponse::BenchmarkComplete(result));
+                }
+                TranscriptionRequest::Ping(reply) => {
+                    let _ = reply.send(service.state.is_some());
+                }
                 TranscriptionRequest::Shutdown => {
                     break;
                 }
@@ -146,5 +597,5 @@ pub fn spawn_transcription_thread() -> (
         }
     });
 
-    (req_tx, resp_rx, partial_rx)
+    (req_tx, resp_rx, partial_rx, handle)
 }
@@ -1,30 +1,75 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
 
-use ct2rs::sys::ComputeType;
-use ct2rs::{Config, TranslationOptions, Translator};
+use ct2rs::sys::{ComputeType, Translator};
+use ct2rs::tokenizers::auto::Tokenizer as AutoTokenizer;
+use ct2rs::{Config, Tokenizer, TranslationOptions};
 use whatlang::{detect, Lang};
 
+use super::paragraphs;
+use crate::language;
+
+/// Most candidate translations `translate_nbest` will return.
+pub const MAX_ALTERNATIVES: usize = 5;
+/// Default cap on source tokens. Output is limited to `MAX_DECODING_LENGTH`
+/// tokens, and translations often run longer than their source, so inputs much
+/// past this come back cut off. Final translations split longer paragraphs into
+/// chunks of sentences under this size.
+pub const DEFAULT_MAX_INPUT_TOKENS: usize = 200;
+/// Longest translation the decoder will produce, in tokens.
+const MAX_DECODING_LENGTH: usize = 256;
+
 #[derive(Debug, Clone)]
 pub struct TranslationJob {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// NLLB code of a written variant of `target_lang` (see `target_variants`).
+    pub target_variant: Option<String>,
+    /// For partial jobs, the value from `next_partial_generation`; 0 otherwise.
+    pub generation: u64,
+}
+
+/// Generation of the newest partial job handed out. The translation thread
+/// compares against it after translating, since a newer partial may have been
+/// queued while the old one was still in flight.
+static LATEST_PARTIAL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Numbers a new partial translation job, marking every earlier one as stale.
+pub fn next_partial_generation() -> u64 {
+    LATEST_PARTIAL_GENERATION.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// Marks every partial job handed out so far as stale, so the translation
+/// thread skips any still queued instead of delaying the final translation.
+/// Called when recording stops.
+pub fn cancel_partials() {
+    LATEST_PARTIAL_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+fn is_stale_partial(job: &TranslationJob) -> bool {
+    job.generation != LATEST_PARTIAL_GENERATION.load(Ordering::SeqCst)
 }
 
 struct TranslationService {
-    translator: Option<Translator<ct2rs::tokenizers::auto::Tokenizer>>,
+    // The low-level translator, since the high-level one only decodes the top hypothesis
+    translator: Option<Translator>,
+    tokenizer: Option<AutoTokenizer>,
     source_lang: Option<String>,
     target_lang: String,
     model_loaded: bool,
+    max_input_tokens: usize,
 }
 
 impl TranslationService {
     fn new() -> Self {
         Self {
             translator: None,
+            tokenizer: None,
             source_lang: Some("en".to_string()),
             target_lang: "en".to_string(),
             model_loaded: false,
+            max_input_tokens: DEFAULT_MAX_INPUT_TOKENS,
         }
     }
 
@@ -36,61 +81,140 @@ impl TranslationService {
             .map(|n| n.get().min(8))
             .unwrap_or(4);
 
+        let tokenizer =
+            AutoTokenizer::new(&path).map_err(|e| format!("Failed to load tokenizer: {}", e))?;
         let translator =
             Translator::new(&path, &config).map_err(|e| format!("Failed to load model: {}", e))?;
         self.translator = Some(translator);
+        self.tokenizer = Some(tokenizer);
         self.model_loaded = true;
         Ok(())
     }
 
+    /// Translates `job`, one paragraph at a time so paragraph breaks survive.
     fn translate(&self, job: &TranslationJob) -> Result<String, String> {
+        paragraphs::translate_paragraphs(&job.text, |paragraph| {
+            self.translate_paragraph(&TranslationJob {
+                text: paragraph.to_string(),
+                ..job.clone()
+            })
+        })
+    }
+
+    /// Translates one paragraph, a few sentences at a time if it is over
+    /// `max_input_tokens`, so long dictations aren't refused outright.
+    fn translate_paragraph(&self, job: &TranslationJob) -> Result<String, String> {
+        if self.fits_input(&job.text) {
+            return self.translate_chunk(job);
+        }
+        let mut translated = Vec::new();
+        for chunk in paragraphs::sentence_chunks(&job.text, |chunk| self.fits_input(chunk)) {
+            translated.push(self.translate_chunk(&TranslationJob {
+                text: chunk,
+                ..job.clone()
+            })?);
+        }
+        Ok(translated.join(" "))
+    }
+
+    /// Whether `text` is within `max_input_tokens`. Without a tokenizer there is
+    /// nothing to split by, so it counts as fitting and fails later as usual.
+    fn fits_input(&self, text: &str) -> bool {
+        match &self.tokenizer {
+            Some(tokenizer) => tokenizer
+                .encode(text.trim())
+                .is_ok_and(|tokens| tokens.len() <= self.max_input_tokens),
+            None => true,
+        }
+    }
+
+    fn translate_chunk(&self, job: &TranslationJob) -> Result<String, String> {
+        let translated = self
+            .translate_nbest(job, 1)?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        if translated.is_empty() {
+            Ok(job.text.trim().to_string())
+        } else {
+            Ok(translated)
+        }
+    }
+
+    /// Returns up to `n` candidate translations, best first. Candidates that decode
+    /// to the same text are only listed once.
+    fn translate_nbest(&self, job: &TranslationJob, n: usize) -> Result<Vec<String>, String> {
         if !self.model_loaded {
             return Err("Translation model not loaded".to_string());
         }
 
         let text = job.text.trim();
         if text.is_empty() {
-            return Ok(String::new());
+            return Ok(Vec::new());
         }
 
-        let target_nllb = nllb_lang_for_app_lang(&job.target_lang)
+        let target_nllb = nllb_target_lang(&job.target_lang, job.target_variant.as_deref())
             .ok_or_else(|| format!("Unsupported target language '{}'", job.target_lang))?;
         let source_nllb = resolve_source_nllb_lang(&job.source_lang, text)
             .ok_or_else(|| format!("Unsupported source language '{}'", job.source_lang))?;
 
         if source_nllb == target_nllb {
-            return Ok(text.to_string());
+            return Ok(vec![text.to_string()]);
         }
 
-        let translator = self
+        let (translator, tokenizer) = self
             .translator
             .as_ref()
+            .zip(self.tokenizer.as_ref())
             .ok_or_else(|| "Translation model not initialized".to_string())?;
 
         // For the ct2rs NLLB path, keep source as plain text and drive translation
         // direction via target prefix language token.
-        let sources = vec![text.to_string()];
+        let tokens = tokenizer
+            .encode(text)
+            .map_err(|e| format!("Failed to tokenize input: {}", e))?;
+        // Refuse rather than hand back a translation that silently stops partway
+        if tokens.len() > self.max_input_tokens {
+            return Err(format!(
+                "Text is too long to translate ({} tokens, limit {})",
+                tokens.len(),
+                self.max_input_tokens
+            ));
+        }
+        let sources = vec![tokens];
         let target_prefixes = vec![vec![target_nllb.to_string()]];
 
+        // Beam search has to keep at least as many beams as hypotheses requested
+        let n = n.clamp(1, MAX_ALTERNATIVES);
         let mut options = TranslationOptions::<String, String>::default();
-        options.beam_size = 1;
-        options.max_decoding_length = 256;
+        options.beam_size = n;
+        options.num_hypotheses = n;
+        options.max_decoding_length = MAX_DECODING_LENGTH;
 
         let output = translator
             .translate_batch_with_target_prefix(&sources, &target_prefixes, &options, None)
             .map_err(|e| format!("Translation inference failed: {}", e))?;
 
-        let translated = output
+        let mut candidates: Vec<String> = Vec::new();
+        for mut tokens in output
             .into_iter()
             .next()
-            .map(|(text, _)| text.trim().to_string())
-            .unwrap_or_default();
-
-        if translated.is_empty() {
-            Ok(text.to_string())
-        } else {
-            Ok(translated)
+            .map(|result| result.hypotheses)
+            .unwrap_or_default()
+        {
+            // Drop the target language token we forced as a prefix
+            tokens.drain(..target_prefixes[0].len().min(tokens.len()));
+            let candidate = tokenizer
+                .decode(tokens)
+                .map_err(|e| format!("Failed to decode translation: {}", e))?
+                .trim()
+                .to_string();
+            if !candidate.is_empty() && !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
         }
+        Ok(candidates)
     }
 }
 
@@ -127,8 +251,47 @@ fn nllb_lang_for_detected(lang: Lang) -> Option<&'static str> {
     }
 }
 
-fn nllb_lang_for_app_lang(lang: &str) -> Option<&'static str> {
-    match lang {
+/// NLLB codes for languages NLLB can write in more than one variant, default first.
+/// Languages with a single NLLB code (e.g. Portuguese) have no variants.
+pub fn target_variants(lang: &str) -> &'static [&'static str] {
+    match language::base(lang) {
+        "zh" => &["zho_Hans", "zho_Hant"],
+        "ar" => &["arb_Arab", "arz_Arab", "ary_Arab", "apc_Arab"],
+        _ => &[],
+    }
+}
+
+/// The NLLB variant implied by a region-qualified code's script or region,
+/// e.g. Traditional Chinese for "zh-TW" or Egyptian Arabic for "ar-EG".
+fn regional_variant(lang: &str) -> Option<&'static str> {
+    let has = |subtags: &[&str]| subtags.iter().any(|tag| language::has_subtag(lang, tag));
+    match language::base(lang) {
+        "zh" if has(&["Hant", "TW", "HK", "MO"]) => Some("zho_Hant"),
+        "ar" if has(&["EG"]) => Some("arz_Arab"),
+        "ar" if has(&["MA"]) => Some("ary_Arab"),
+        "ar" if has(&["SY", "LB", "JO", "PS"]) => Some("apc_Arab"),
+        _ => None,
+    }
+}
+
+/// Resolves the NLLB target code, honoring `variant` when it is one of the
+/// language's known variants, then the variant implied by the code's region
+/// (e.g. "zh-TW"), and falling back to the base language's default otherwise.
+fn nllb_target_lang(lang: &str, variant: Option<&str>) -> Option<&'static str> {
+    variant
+        .and_then(|variant| {
+            target_variants(lang)
+                .iter()
+                .copied()
+                .find(|&code| code == variant)
+        })
+        .or_else(|| regional_variant(lang))
+        .or_else(|| nllb_lang_for_app_lang(lang))
+}
+
+/// The NLLB code for an app language code; any script or region is ignored.
+pub fn nllb_lang_for_app_lang(lang: &str) -> Option<&'static str> {
+    match language::base(lang) {
         "en" => Some("eng_Latn"),
         "es" => Some("spa_Latn"),
         "fr" => Some("fra_Latn"),
@@ -156,26 +319,37 @@ pub enum TranslationRequest {
         source: Option<String>,
         target: String,
     },
-    Translate(TranslationJob),
+    SetMaxInputTokens(usize),
+    /// Answered on the given channel, so concurrent callers never see each
+    /// other's results and a reply that arrives after a caller gave up is dropped.
+    Translate(TranslationJob, mpsc::Sender<Result<String, String>>),
+    /// Up to `n` candidate translations, answered on the given channel like `Translate`.
+    TranslateNBest(
+        TranslationJob,
+        usize,
+        mpsc::Sender<Result<Vec<String>, String>>,
+    ),
     TranslatePartial(TranslationJob),
+    /// Answered on the given channel with whether a model is loaded.
+    Ping(mpsc::Sender<bool>),
     Shutdown,
 }
 
 pub enum TranslationResponse {
     ModelLoaded(Result<(), String>),
-    TranslationComplete(Result<String, String>),
 }
 
 pub fn spawn_translation_thread() -> (
     mpsc::Sender<TranslationRequest>,
     mpsc::Receiver<TranslationResponse>,
     mpsc::Receiver<String>,
+    std::thread::JoinHandle<()>,
 ) {
     let (req_tx, req_rx) = mpsc::channel::<TranslationRequest>();
     let (resp_tx, resp_rx) = mpsc::channel::<TranslationResponse>();
     let (partial_tx, partial_rx) = mpsc::channel::<String>();
 
-    std::thread::spawn(move || {
+    let handle = std::thread::spawn(move || {
         let mut service = TranslationService::new();
 
         while let Ok(request) = req_rx.recv() {
@@ -188,9 +362,14 @@ pub fn spawn_translation_thread() -> (
                     service.source_lang = source;
                     service.target_lang = target;
                 }
-                TranslationRequest::Translate(job) => {
-                    let result = service.translate(&job);
-                    let _ = resp_tx.send(TranslationResponse::TranslationComplete(result));
+                TranslationRequest::SetMaxInputTokens(max) => {
+                    service.max_input_tokens = max;
+                }
+                TranslationRequest::Translate(job, reply) => {
+                    let _ = reply.send(service.translate(&job));
+                }
+                TranslationRequest::TranslateNBest(job, n, reply) => {
+                    let _ = reply.send(service.translate_nbest(&job, n));
                 }
                 TranslationRequest::TranslatePartial(job) => {
                     // Drain stale partials — only process the newest one.
@@ -201,34 +380,51 @@ pub fn spawn_translation_thread() -> (
                             TranslationRequest::TranslatePartial(newer) => {
                                 latest_job = newer;
                             }
-                            TranslationRequest::Translate(final_job) => {
-                                got_final = Some(final_job);
+                            TranslationRequest::Translate(final_job, reply) => {
+                                got_final = Some((final_job, reply));
                                 break;
                             }
                             TranslationRequest::LoadModel(path) => {
                                 let result = service.load_model(path);
                                 let _ = resp_tx.send(TranslationResponse::ModelLoaded(result));
                             }
+                            TranslationRequest::TranslateNBest(job, n, reply) => {
+                                let _ = reply.send(service.translate_nbest(&job, n));
+                            }
                             TranslationRequest::SetLanguages { source, target } => {
                                 service.source_lang = source;
                                 service.target_lang = target;
                             }
+                            TranslationRequest::SetMaxInputTokens(max) => {
+                                service.max_input_tokens = max;
+                            }
+                            TranslationRequest::Ping(reply) => {
+                                let _ = reply.send(service.model_loaded);
+                            }
                             TranslationRequest::Shutdown => {
                                 return;
                             }
                         }
                     }
 
-                    if let Some(final_job) = got_final {
-                        let result = service.translate(&final_job);
-                        let _ = resp_tx.send(TranslationResponse::TranslationComplete(result));
+                    if let Some((final_job, reply)) = got_final {
+                        let _ = reply.send(service.translate(&final_job));
+                    } else if is_stale_partial(&latest_job) {
+                        // Recording stopped or a newer partial is on its way
+                        log::debug!("Skipping cancelled partial translation");
                     } else {
                         let result = service.translate(&latest_job);
-                        if let Ok(text) = result {
+                        // A newer partial arrived mid-translation; it will replace this one
+                        if is_stale_partial(&latest_job) {
+                            log::debug!("Discarding stale partial translation");
+                        } else if let Ok(text) = result {
                             let _ = partial_tx.send(text.trim().to_string());
                         }
                     }
                 }
+                TranslationRequest::Ping(reply) => {
+                    let _ = reply.send(service.model_loaded);
+                }
                 TranslationRequest::Shutdown => {
                     break;
                 }
@@ -236,5 +432,5 @@ pub fn spawn_translation_thread() -> (
         }
     });
 
-    (req_tx, resp_rx, partial_rx)
+    (req_tx, resp_rx, partial_rx, handle)
 }
@@ -1,2 +1,4 @@
 pub mod engine;
 pub mod model_manager;
+pub mod paragraphs;
+pub mod partial;
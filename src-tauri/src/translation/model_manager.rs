@@ -1,9 +1,8 @@
 use anyhow::{anyhow, Result};
-use futures_util::StreamExt;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 
 pub const DEFAULT_TRANSLATION_MODEL: &str = "nllb-200-distilled-600M-int8";
-const HF_BASE_URL: &str = "https://huggingface.co";
 
 pub struct TranslationModelInfo {
     pub name: &'static str,
@@ -43,14 +42,79 @@ pub fn model_exists(model_name: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Checks a model file's contents: JSON files must parse, and nothing may be empty.
+fn check_file_contents(filename: &str, contents: &[u8]) -> std::result::Result<(), String> {
+    if contents.is_empty() {
+        return Err(format!("{} is empty", filename));
+    }
+    if filename.ends_with(".json") {
+        serde_json::from_slice::<serde_json::Value>(contents)
+            .map_err(|e| format!("{} is not valid JSON: {}", filename, e))?;
+    }
+    Ok(())
+}
+
+/// Finds the first required file of `model_name` that is missing or corrupt, so
+/// a load failure can name it instead of reporting a generic error. Returns the
+/// file name and what is wrong with it. `model.bin` is only checked for size,
+/// since reading it would take as long as loading it.
+pub fn find_bad_file(model_name: &str) -> Option<(&'static str, String)> {
+    let model = find_model(model_name)?;
+    let path = model_path(model.name);
+    model.required_files.iter().find_map(|&filename| {
+        let file = path.join(filename);
+        let result = if filename.ends_with(".json") {
+            std::fs::read(&file)
+                .map_err(|_| format!("{} is missing", filename))
+                .and_then(|contents| check_file_contents(filename, &contents))
+        } else {
+            match std::fs::metadata(&file) {
+                Ok(metadata) if metadata.len() == 0 => Err(format!("{} is empty", filename)),
+                Ok(_) => Ok(()),
+                Err(_) => Err(format!("{} is missing", filename)),
+            }
+        };
+        result.err().map(|reason| (filename, reason))
+    })
+}
+
+/// Deletes one file of a downloaded model so the next `download_model` fetches
+/// just that file again.
+pub fn remove_model_file(model_name: &str, filename: &str) -> Result<()> {
+    let file = model_path(model_name).join(filename);
+    match std::fs::remove_file(&file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
 fn find_model(model_name: &str) -> Option<&'static TranslationModelInfo> {
     AVAILABLE_TRANSLATION_MODELS
         .iter()
         .find(|m| m.name == model_name)
 }
 
-fn resolve_url(repo: &str, filename: &str) -> String {
-    format!("{HF_BASE_URL}/{repo}/resolve/main/{filename}?download=true")
+fn resolve_url(base_url: &str, repo: &str, filename: &str) -> String {
+    format!("{base_url}/{repo}/resolve/main/{filename}?download=true")
+}
+
+/// Asks the server for a file's size without downloading it. Returns `None`
+/// when HEAD isn't supported or the response carries no length.
+async fn preflight_size(client: &reqwest::Client, url: &str) -> Option<u64> {
+    let response = client
+        .head(url)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
 }
 
 fn ensure_parent(path: &Path) -> Result<()> {
@@ -60,7 +124,15 @@ fn ensure_parent(path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn download_model<F>(model_name: &str, progress_callback: F) -> Result<PathBuf>
+/// Downloads any missing model files. Setting `cancel` or a stalled connection
+/// aborts the download and removes the partially written file. `base_url` is Hugging Face or a mirror
+/// with the same layout.
+pub async fn download_model<F>(
+    model_name: &str,
+    base_url: &str,
+    cancel: &AtomicBool,
+    progress_callback: F,
+) -> Result<PathBuf>
 where
     F: Fn(u64, u64),
 {
@@ -70,36 +142,68 @@ where
     let model_dir = model_path(model_name);
     std::fs::create_dir_all(&model_dir)?;
 
-    let client = reqwest::Client::new();
+    let client = crate::download::client()?;
+    let missing: Vec<&str> = model
+        .required_files
+        .iter()
+        .copied()
+        .filter(|filename| !model_dir.join(filename).exists())
+        .collect();
+
+    // Size every file up front so progress is accurate from the first byte.
+    // Files the server won't size are added to the total as their GET starts.
+    let mut preflight_sizes = Vec::with_capacity(missing.len());
+    for filename in &missing {
+        let size = preflight_size(&client, &resolve_url(base_url, model.repo, filename)).await;
+        preflight_sizes.push(size);
+    }
+    let mut expected_total: u64 = preflight_sizes.iter().flatten().sum();
     let mut downloaded_total: u64 = 0;
-    let mut expected_total: u64 = 0;
 
-    for filename in model.required_files {
+    for (filename, preflight) in missing.into_iter().zip(preflight_sizes) {
         let dest = model_dir.join(filename);
-        if dest.exists() {
-            continue;
-        }
-
-        let url = resolve_url(model.repo, filename);
+        let url = resolve_url(base_url, model.repo, filename);
         let response = client.get(&url).send().await?.error_for_status()?;
-        let content_len = response.content_length().unwrap_or(0);
-        expected_total = expected_total.saturating_add(content_len);
+        if preflight.is_none() {
+            let content_len = response.content_length().unwrap_or(0);
+            expected_total = expected_total.saturating_add(content_len);
+        }
 
         let tmp = dest.with_extension("part");
         ensure_parent(&tmp)?;
-        let mut file = std::fs::File::create(&tmp)?;
-        let mut stream = response.bytes_stream();
-
-        use std::io::Write;
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk)?;
-            downloaded_total = downloaded_total.saturating_add(chunk.len() as u64);
+        crate::download::write_body(response, &tmp, cancel, |len| {
+            downloaded_total = downloaded_total.saturating_add(len);
             progress_callback(downloaded_total, expected_total);
-        }
+        })
+        .await?;
 
         std::fs::rename(tmp, dest)?;
     }
 
     Ok(model_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_tokenizer_json_is_detected() {
+        let err = check_file_contents("tokenizer.json", b"{\"model\": {\"vocab\": [").unwrap_err();
+        assert!(
+            err.starts_with("tokenizer.json is not valid JSON"),
+            "{}",
+            err
+        );
+        assert!(check_file_contents("tokenizer.json", b"{\"model\": {}}").is_ok());
+    }
+
+    #[test]
+    fn test_empty_files_are_rejected() {
+        assert_eq!(
+            check_file_contents("shared_vocabulary.txt", b""),
+            Err("shared_vocabulary.txt is empty".to_string())
+        );
+        assert!(check_file_contents("shared_vocabulary.txt", b"<unk>\n").is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+/// Separator between paragraphs, as written by `paragraph_on_pause` or typed newlines.
+const PARAGRAPH_BREAK: &str = "\n\n";
+
+/// Translates each paragraph of `text` on its own with `translate` and joins the
+/// results with the original breaks, so a multi-paragraph note keeps its shape
+/// and no single call sees more than one paragraph. Blank paragraphs (from runs
+/// of extra newlines) are kept as-is.
+pub fn translate_paragraphs<F>(text: &str, mut translate: F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Result<String, String>,
+{
+    let mut translated = Vec::new();
+    for paragraph in text.split(PARAGRAPH_BREAK) {
+        if paragraph.trim().is_empty() {
+            translated.push(paragraph.to_string());
+        } else {
+            translated.push(translate(paragraph.trim())?);
+        }
+    }
+    Ok(translated.join(PARAGRAPH_BREAK))
+}
+
+/// Splits `paragraph` into runs of whole sentences that each satisfy `fits`, so
+/// a paragraph over the model's input limit can be translated in pieces rather
+/// than refused. A sentence that doesn't fit on its own is split between words.
+/// Pieces are rejoined with single spaces.
+pub fn sentence_chunks<F>(paragraph: &str, fits: F) -> Vec<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for sentence in split_sentences(paragraph) {
+        if fits(sentence) {
+            push_piece(&mut chunks, &mut current, sentence, &fits);
+        } else {
+            for word in sentence.split_whitespace() {
+                push_piece(&mut chunks, &mut current, word, &fits);
+            }
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Adds `piece` to the chunk being built, or starts a new chunk with it once
+/// the two together would no longer fit.
+fn push_piece<F>(chunks: &mut Vec<String>, current: &mut String, piece: &str, fits: &F)
+where
+    F: Fn(&str) -> bool,
+{
+    if current.is_empty() {
+        current.push_str(piece);
+        return;
+    }
+    let joined = format!("{} {}", current, piece);
+    if fits(&joined) {
+        *current = joined;
+    } else {
+        chunks.push(std::mem::replace(current, piece.to_string()));
+    }
+}
+
+/// Splits after sentence-ending punctuation: Latin marks only when whitespace
+/// follows (so "3.5" stays whole), full-width CJK marks anywhere.
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let ends_sentence = match c {
+            '.' | '!' | '?' => chars.peek().is_some_and(|(_, next)| next.is_whitespace()),
+            '。' | '！' | '？' => true,
+            _ => false,
+        };
+        if ends_sentence {
+            let end = i + c.len_utf8();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shout(text: &str) -> Result<String, String> {
+        Ok(text.to_uppercase())
+    }
+
+    #[test]
+    fn test_two_paragraphs_stay_two_paragraphs() {
+        let mut calls = Vec::new();
+        let result = translate_paragraphs("Hola amigo.\n\nAdiós.", |p| {
+            calls.push(p.to_string());
+            shout(p)
+        });
+        assert_eq!(result.unwrap(), "HOLA AMIGO.\n\nADIÓS.");
+        assert_eq!(calls, vec!["Hola amigo.", "Adiós."]);
+    }
+
+    #[test]
+    fn test_single_newlines_and_extra_breaks_are_kept() {
+        assert_eq!(translate_paragraphs("one\ntwo", shout).unwrap(), "ONE\nTWO");
+        assert_eq!(
+            translate_paragraphs("one\n\n\n\ntwo", shout).unwrap(),
+            "ONE\n\n\n\nTWO"
+        );
+    }
+
+    #[test]
+    fn test_error_in_any_paragraph_fails() {
+        let result = translate_paragraphs("ok\n\nbad", |p| {
+            if p == "bad" {
+                Err("too long".to_string())
+            } else {
+                shout(p)
+            }
+        });
+        assert_eq!(result, Err("too long".to_string()));
+    }
+
+    fn at_most_words(max: usize) -> impl Fn(&str) -> bool {
+        move |chunk| chunk.split_whitespace().count() <= max
+    }
+
+    #[test]
+    fn test_sentence_chunks_packs_whole_sentences() {
+        let chunks = sentence_chunks("Hola amigo. ¿Cómo estás? Bien, gracias.", at_most_words(4));
+        assert_eq!(chunks, vec!["Hola amigo. ¿Cómo estás?", "Bien, gracias."]);
+    }
+
+    #[test]
+    fn test_sentence_chunks_keeps_fitting_text_whole() {
+        let chunks = sentence_chunks("Pi es 3.14 más o menos.", at_most_words(10));
+        assert_eq!(chunks, vec!["Pi es 3.14 más o menos."]);
+    }
+
+    #[test]
+    fn test_sentence_chunks_splits_long_sentence_between_words() {
+        let chunks = sentence_chunks("Corto. uno dos tres cuatro cinco", at_most_words(2));
+        assert_eq!(chunks, vec!["Corto. uno", "dos tres", "cuatro cinco"]);
+    }
+
+    #[test]
+    fn test_sentence_chunks_splits_cjk_sentences() {
+        let chunks = sentence_chunks("你好。谢谢！", |chunk| chunk.chars().count() <= 3);
+        assert_eq!(chunks, vec!["你好。", "谢谢！"]);
+    }
+}
@@ -0,0 +1,167 @@
+/// Decides when a live partial transcript is worth re-translating, and which partial
+/// translation to show. NLLB re-decodes the whole growing sentence every time, so
+/// translating every tick makes the overlay jitter and sometimes shrink.
+#[derive(Debug, Default)]
+pub struct PartialTranslationDebouncer {
+    last_source: String,
+    shown: Option<String>,
+}
+
+/// How a new partial relates to the previous one, in words.
+#[derive(Debug, PartialEq)]
+struct Extension {
+    /// Words the sliding window dropped from the front of the previous partial.
+    dropped: usize,
+    /// Words appended after the part both partials share.
+    added: usize,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect()
+}
+
+/// Returns how `source` extends `prev`: either `prev` is a prefix of it, or the
+/// partial window slid forward so a tail of `prev` starts `source`. Whisper
+/// often revises the word it was still hearing, so when the overlap is at least
+/// two words its last word is allowed to differ. None means whisper revised the
+/// partial so heavily that it no longer extends the last one.
+fn extension(prev: &str, source: &str) -> Option<Extension> {
+    let prev = words(prev);
+    let source = words(source);
+    (1..=prev.len().min(source.len()))
+        .rev()
+        .find(|&k| {
+            let tail = &prev[prev.len() - k..];
+            let head = &source[..k];
+            tail == head || (k >= 2 && tail[..k - 1] == head[..k - 1])
+        })
+        .map(|k| Extension {
+            dropped: prev.len() - k,
+            added: source.len() - k,
+        })
+}
+
+impl PartialTranslationDebouncer {
+    /// Returns true if `source` gained at least one full word since the last
+    /// translated partial, or no longer extends it.
+    pub fn should_translate(&self, source: &str) -> bool {
+        if source.trim().is_empty() {
+            return false;
+        }
+        if self.last_source.is_empty() {
+            return true;
+        }
+        extension(&self.last_source, source).is_none_or(|ext| ext.added > 0)
+    }
+
+    /// Records the translation of `source`. When `source` extends the previous
+    /// partial, a translation shorter than the one shown (less whatever the
+    /// window dropped, counted as one word each) is a regression and is ignored.
+    /// A translation of revised source text always replaces it, so the overlay
+    /// never shows a translation of stale text.
+    pub fn accept(&mut self, source: &str, translation: String) {
+        let regressed = match (&self.shown, extension(&self.last_source, source)) {
+            (Some(prev), Some(ext)) => {
+                translation.split_whitespace().count()
+                    < prev.split_whitespace().count().saturating_sub(ext.dropped)
+            }
+            _ => false,
+        };
+        self.last_source = source.to_string();
+        if !regressed {
+            self.shown = Some(translation);
+        }
+    }
+
+    /// The partial translation the overlay should currently display.
+    pub fn shown(&self) -> Option<&str> {
+        self.shown.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_partial_is_translated() {
+        let debouncer = PartialTranslationDebouncer::default();
+        assert!(debouncer.should_translate("hola"));
+        assert!(!debouncer.should_translate("   "));
+    }
+
+    #[test]
+    fn test_waits_for_a_new_word() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        debouncer.accept("hola como", "hello how".to_string());
+        assert!(!debouncer.should_translate("hola cómo"));
+        assert!(debouncer.should_translate("hola como estas"));
+    }
+
+    #[test]
+    fn test_growing_source_keeps_longer_translation() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        let ticks = [
+            ("hola", "hello"),
+            ("hola como", "hello how"),
+            ("Hola, como estas", "hello"),
+            ("hola como estas hoy", "hello how are you today"),
+        ];
+        let mut shown = Vec::new();
+        for (source, translation) in ticks {
+            assert!(debouncer.should_translate(source), "{source}");
+            debouncer.accept(source, translation.to_string());
+            shown.push(debouncer.shown().unwrap().to_string());
+            assert!(!debouncer.should_translate(source));
+        }
+        assert_eq!(
+            shown,
+            ["hello", "hello how", "hello how", "hello how are you today"]
+        );
+    }
+
+    #[test]
+    fn test_sliding_window_is_not_a_restart() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        debouncer.accept("uno dos tres cuatro", "one two three four".to_string());
+        assert!(!debouncer.should_translate("tres cuatro"));
+        assert!(debouncer.should_translate("tres cuatro cinco"));
+        debouncer.accept("tres cuatro cinco", "three four five".to_string());
+        assert_eq!(debouncer.shown(), Some("three four five"));
+        debouncer.accept("cuatro cinco seis", "four".to_string());
+        assert_eq!(debouncer.shown(), Some("three four five"));
+    }
+
+    #[test]
+    fn test_shorter_translation_is_suppressed() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        debouncer.accept("hola como", "hello how are".to_string());
+        debouncer.accept("hola como", "hello how".to_string());
+        assert_eq!(debouncer.shown(), Some("hello how are"));
+        debouncer.accept("hola como estas hoy", "hello how are you today".to_string());
+        assert_eq!(debouncer.shown(), Some("hello how are you today"));
+    }
+
+    #[test]
+    fn test_revised_source_replaces_longer_translation() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        debouncer.accept("ola komo", "wave like a".to_string());
+        debouncer.accept("hola como estas", "how are".to_string());
+        assert_eq!(debouncer.shown(), Some("how are"));
+        assert!(!debouncer.should_translate("hola como estas"));
+    }
+
+    #[test]
+    fn test_restart_replaces_translation() {
+        let mut debouncer = PartialTranslationDebouncer::default();
+        debouncer.accept("hola como estas", "hello how are you".to_string());
+        assert!(debouncer.should_translate("buenos dias"));
+        debouncer.accept("buenos dias", "good morning".to_string());
+        assert_eq!(debouncer.shown(), Some("good morning"));
+    }
+}
@@ -1,9 +1,18 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     tray::TrayIconBuilder,
     Manager,
 };
 
+const TRAY_ID: &str = "wren";
+
+/// How long the menu bar shows that quiet hours ignored the hotkey.
+const QUIET_HOURS_NOTICE_MS: u64 = 2000;
+
+/// Bumped by each quiet-hours notice, so only the latest one clears the title.
+static QUIET_HOURS_NOTICES: AtomicU64 = AtomicU64::new(0);
+
 pub fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
     let settings = MenuItemBuilder::with_id("settings", "Settings...").build(app)?;
     let history = MenuItemBuilder::with_id("history", "History...").build(app)?;
@@ -15,7 +24,7 @@ pub fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
         .item(&quit)
         .build()?;
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .icon_as_template(true)
         .tooltip("Wren")
@@ -32,6 +41,22 @@ pub fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
     Ok(())
 }
 
+/// Briefly shows "Quiet hours" next to the menu bar icon, so a hotkey press
+/// ignored during quiet hours doesn't look like a broken hotkey.
+pub fn flash_quiet_hours(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let notice = QUIET_HOURS_NOTICES.fetch_add(1, Ordering::SeqCst) + 1;
+    let _ = tray.set_title(Some("Quiet hours"));
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(QUIET_HOURS_NOTICE_MS));
+        if QUIET_HOURS_NOTICES.load(Ordering::SeqCst) == notice {
+            let _ = tray.set_title(None::<&str>);
+        }
+    });
+}
+
 /// Opens the settings window if it doesn't exist, or focuses it if it does.
 pub fn show_settings_window(app: &tauri::AppHandle) {
     if let Some(window) = app.get_webview_window("settings") {
@@ -0,0 +1,64 @@
+/// Shortens `text` to at most `max` characters, cutting at the last word
+/// boundary that fits so no word is split. A single word longer than `max` is
+/// cut mid-word. `max` of 0 means no limit.
+pub fn truncate_at_word_boundary(text: &str, max: usize) -> String {
+    let Some((cut, next)) = text.char_indices().nth(max).filter(|_| max > 0) else {
+        return text.to_string();
+    };
+
+    let head = &text[..cut];
+    let head = if next.is_whitespace() {
+        head
+    } else {
+        match head.rfind(char::is_whitespace) {
+            Some(space) => &head[..space],
+            None => head,
+        }
+    };
+    head.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ':'))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_unchanged() {
+        assert_eq!(truncate_at_word_boundary("hello world", 11), "hello world");
+        assert_eq!(truncate_at_word_boundary("hello world", 0), "hello world");
+    }
+
+    #[test]
+    fn test_cuts_before_partial_word() {
+        assert_eq!(
+            truncate_at_word_boundary("hello brave new world", 13),
+            "hello brave"
+        );
+        assert_eq!(
+            truncate_at_word_boundary("hello brave new world", 11),
+            "hello brave"
+        );
+    }
+
+    #[test]
+    fn test_drops_trailing_separator() {
+        assert_eq!(truncate_at_word_boundary("first, second third", 9), "first");
+    }
+
+    #[test]
+    fn test_counts_characters_not_bytes() {
+        assert_eq!(
+            truncate_at_word_boundary("héllo wörld again", 11),
+            "héllo wörld"
+        );
+    }
+
+    #[test]
+    fn test_long_single_word_is_cut() {
+        assert_eq!(
+            truncate_at_word_boundary("supercalifragilistic", 5),
+            "super"
+        );
+    }
+}
@@ -1,8 +1,19 @@
 use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+/// How a vocabulary phrase is matched against the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// Only match the phrase as a whole word (bounded by `\b`).
+    #[default]
+    WholeWord,
+    /// Match the phrase anywhere, including inside larger words.
+    Anywhere,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VocabEntry {
     pub id: u64,
@@ -10,15 +21,49 @@ pub struct VocabEntry {
     pub replacement: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    #[serde(default)]
+    pub match_mode: MatchMode,
+    #[serde(default)]
+    pub priority: i32,
+    /// Also correct near misses: word runs within a small edit distance of the phrase.
+    #[serde(default)]
+    pub fuzzy: bool,
+    /// Treat `phrase` as a regular expression, whose groups `replacement` can
+    /// refer to as `$1` or `${name}`.
+    #[serde(default)]
+    pub is_regex: bool,
 }
 
 fn default_true() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Edit distance fuzzy entries tolerate unless configured otherwise.
+pub const DEFAULT_FUZZY_DISTANCE: usize = 1;
+/// Upper bound on the fuzzy edit distance; beyond this almost anything matches.
+pub const MAX_FUZZY_DISTANCE: usize = 3;
+/// Characters of phrase needed per allowed edit, so short phrases stay exact-only.
+const CHARS_PER_EDIT: usize = 4;
+
+fn default_fuzzy_distance() -> usize {
+    DEFAULT_FUZZY_DISTANCE
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vocabulary {
     pub entries: Vec<VocabEntry>,
+    /// Most edits (Levenshtein distance) a fuzzy entry accepts.
+    #[serde(default = "default_fuzzy_distance")]
+    pub fuzzy_max_distance: usize,
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            fuzzy_max_distance: DEFAULT_FUZZY_DISTANCE,
+        }
+    }
 }
 
 fn vocabulary_path() -> PathBuf {
@@ -31,7 +76,7 @@ pub fn load_vocabulary() -> Vocabulary {
     if !path.exists() {
         return Vocabulary::default();
     }
-    match std::fs::read_to_string(&path) {
+    match crate::crypto::read_file(&path) {
         Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
         Err(_) => Vocabulary::default(),
     }
@@ -43,7 +88,7 @@ pub fn save_vocabulary(vocabulary: &Vocabulary) -> Result<()> {
         std::fs::create_dir_all(parent)?;
     }
     let contents = serde_json::to_string_pretty(vocabulary)?;
-    std::fs::write(&path, contents)?;
+    crate::crypto::write_file(&path, &contents)?;
     Ok(())
 }
 
@@ -53,12 +98,11 @@ pub fn add_entry(entry: VocabEntry) -> Result<()> {
     save_vocabulary(&vocabulary)
 }
 
-pub fn update_entry(id: u64, phrase: String, replacement: String, enabled: bool) -> Result<()> {
+/// Replaces the entry with the same id as `updated`.
+pub fn update_entry(updated: VocabEntry) -> Result<()> {
     let mut vocabulary = load_vocabulary();
-    if let Some(entry) = vocabulary.entries.iter_mut().find(|e| e.id == id) {
-        entry.phrase = phrase;
-        entry.replacement = replacement;
-        entry.enabled = enabled;
+    if let Some(entry) = vocabulary.entries.iter_mut().find(|e| e.id == updated.id) {
+        *entry = updated;
     }
     save_vocabulary(&vocabulary)
 }
@@ -69,11 +113,28 @@ pub fn delete_entry(id: u64) -> Result<()> {
     save_vocabulary(&vocabulary)
 }
 
+/// One vocabulary entry's replacement for a corrected span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrectionCandidate {
+    pub entry_id: u64,
+    pub replacement: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrectionApplied {
     pub original: String,
     pub replacement: String,
     pub position: usize,
+    /// Every entry matching exactly this span, the applied one first. An entry
+    /// whose match only overlaps the span (starting or ending elsewhere) is
+    /// dropped rather than offered here, since choosing it would change which
+    /// text gets replaced. Only serialized when there is an actual choice to make.
+    #[serde(default, skip_serializing_if = "has_no_alternatives")]
+    pub candidates: Vec<CorrectionCandidate>,
+}
+
+fn has_no_alternatives(candidates: &[CorrectionCandidate]) -> bool {
+    candidates.len() < 2
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,37 +162,93 @@ fn apply_case(matched: &str, replacement: &str) -> String {
 }
 
 pub fn apply_corrections(text: &str, vocabulary: &Vocabulary) -> CorrectionResult {
-    let mut result = text.to_string();
-    let mut corrections = Vec::new();
+    // Higher priority first; among equal priorities, longer phrases first so
+    // multi-word phrases are corrected before their constituent words
+    let mut entries: Vec<&VocabEntry> = vocabulary.entries.iter().filter(|e| e.enabled).collect();
+    entries.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then_with(|| b.phrase.chars().count().cmp(&a.phrase.chars().count()))
+    });
 
-    for entry in &vocabulary.entries {
-        if !entry.enabled {
-            continue;
-        }
+    // Match every entry against the original text and claim spans in
+    // priority order, so each original character is corrected at most once
+    // and replacements are never re-matched by later entries
+    let mut corrections: Vec<CorrectionApplied> = Vec::new();
+    let mut claimed: Vec<(usize, usize)> = Vec::new();
 
-        let pattern = format!(r"(?i)\b{}\b", regex::escape(&entry.phrase));
+    for entry in entries {
+        let phrase = if entry.is_regex {
+            // Grouped so an alternation stays inside the word boundaries
+            format!("(?:{})", entry.phrase)
+        } else {
+            regex::escape(&entry.phrase)
+        };
+        let pattern = match entry.match_mode {
+            MatchMode::WholeWord => format!(r"(?i)\b{}\b", phrase),
+            MatchMode::Anywhere => format!(r"(?i){}", phrase),
+        };
         let re = match Regex::new(&pattern) {
             Ok(re) => re,
             Err(_) => continue,
         };
 
-        // Collect matches first, then apply replacements from end to start
-        // to preserve positions
-        let mut matches: Vec<(usize, usize, String)> = Vec::new();
-        for m in re.find_iter(&result) {
-            let matched_text = m.as_str();
-            let replacement = apply_case(matched_text, &entry.replacement);
-            matches.push((m.start(), m.end(), replacement));
+        // Exact matches first, so a near miss never claims text an exact match
+        // wanted. Regex entries fill in their replacement's group references.
+        let mut spans: Vec<(usize, usize, String)> = re
+            .captures_iter(text)
+            .filter_map(|caps| {
+                let m = caps.get(0)?;
+                // A pattern like `a*` would otherwise claim empty spans everywhere
+                if m.is_empty() {
+                    return None;
+                }
+                let mut replacement = String::new();
+                if entry.is_regex {
+                    caps.expand(&entry.replacement, &mut replacement);
+                } else {
+                    replacement.push_str(&entry.replacement);
+                }
+                Some((m.start(), m.end(), replacement))
+            })
+            .collect();
+        // Near misses are measured against literal text, so regex entries skip them
+        if entry.fuzzy && !entry.is_regex {
+            spans.extend(
+                fuzzy_matches(text, &entry.phrase, vocabulary.fuzzy_max_distance)
+                    .into_iter()
+                    .map(|(start, end)| (start, end, entry.replacement.clone())),
+            );
         }
 
-        // Apply from end to start so positions remain valid
-        for (start, end, replacement) in matches.iter().rev() {
+        for (start, end, replacement) in spans {
+            let matched = &text[start..end];
+            let candidate = CorrectionCandidate {
+                entry_id: entry.id,
+                replacement: apply_case(matched, &replacement),
+            };
+
+            // A lower-ranked entry for exactly the same span becomes an alternative
+            if let Some(index) = claimed.iter().position(|&span| span == (start, end)) {
+                let candidates = &mut corrections[index].candidates;
+                if !candidates.iter().any(|c| c.entry_id == entry.id) {
+                    candidates.push(candidate);
+                }
+                continue;
+            }
+            let overlaps = claimed
+                .iter()
+                .any(|&(claimed_start, claimed_end)| start < claimed_end && claimed_start < end);
+            if overlaps {
+                continue;
+            }
+            claimed.push((start, end));
             corrections.push(CorrectionApplied {
-                original: result[*start..*end].to_string(),
-                replacement: replacement.clone(),
-                position: *start,
+                original: matched.to_string(),
+                replacement: candidate.replacement.clone(),
+                position: start,
+                candidates: vec![candidate],
             });
-            result.replace_range(*start..*end, replacement);
         }
     }
 
@@ -139,11 +256,340 @@ pub fn apply_corrections(text: &str, vocabulary: &Vocabulary) -> CorrectionResul
     corrections.sort_by_key(|c| c.position);
 
     CorrectionResult {
-        text: result,
+        text: splice_corrections(text, &corrections),
         corrections,
     }
 }
 
+/// Rebuilds the text from the original, splicing in each correction's
+/// replacement. `corrections` must be sorted by position.
+pub fn splice_corrections(text: &str, corrections: &[CorrectionApplied]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for correction in corrections {
+        result.push_str(&text[cursor..correction.position]);
+        result.push_str(&correction.replacement);
+        cursor = correction.position + correction.original.len();
+    }
+    result.push_str(&text[cursor..]);
+    result
+}
+
+/// Switches the correction at `position` to the candidate from `entry_id`.
+pub fn choose_candidate(
+    corrections: &mut [CorrectionApplied],
+    position: usize,
+    entry_id: u64,
+) -> Result<(), String> {
+    let correction = corrections
+        .iter_mut()
+        .find(|c| c.position == position)
+        .ok_or_else(|| format!("No correction at position {}", position))?;
+    let candidate = correction
+        .candidates
+        .iter()
+        .find(|c| c.entry_id == entry_id)
+        .ok_or_else(|| format!("Entry {} is not a candidate here", entry_id))?;
+    correction.replacement = candidate.replacement.clone();
+    Ok(())
+}
+
+/// Finds runs of words in `text` that are a near miss for `phrase`: the same
+/// number of words, within `max_distance` edits of it ignoring case. The allowed
+/// distance shrinks for short phrases (one edit per `CHARS_PER_EDIT` characters)
+/// so common short words aren't rewritten.
+fn fuzzy_matches(text: &str, phrase: &str, max_distance: usize) -> Vec<(usize, usize)> {
+    let phrase_words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    let target = phrase_words.join(" ");
+    let max_distance = max_distance
+        .min(MAX_FUZZY_DISTANCE)
+        .min(target.chars().count() / CHARS_PER_EDIT);
+    if phrase_words.is_empty() || max_distance == 0 {
+        return Vec::new();
+    }
+
+    let words: Vec<(usize, usize)> = Regex::new(r"[\w'’-]+")
+        .map(|re| re.find_iter(text).map(|m| (m.start(), m.end())).collect())
+        .unwrap_or_default();
+
+    words
+        .windows(phrase_words.len())
+        .filter_map(|window| {
+            let (start, end) = (window[0].0, window[window.len() - 1].1);
+            let candidate = window
+                .iter()
+                .map(|&(s, e)| text[s..e].to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let distance = levenshtein(&candidate, &target);
+            (distance > 0 && distance <= max_distance).then_some((start, end))
+        })
+        .collect()
+}
+
+/// Character-level edit distance between `a` and `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Checks that a vocabulary phrase compiles the way `apply_corrections` would
+/// compile it. For regex phrases, also checks that every `$1`/`${name}`
+/// reference in `replacement` names a group the pattern actually has.
+pub fn validate_pattern(phrase: &str, is_regex: bool, replacement: &str) -> Result<(), String> {
+    if phrase.trim().is_empty() {
+        return Err("Phrase cannot be empty".to_string());
+    }
+    if !is_regex {
+        // Literal phrases are escaped, so they always compile
+        return Ok(());
+    }
+
+    let re = Regex::new(&format!("(?i){}", phrase)).map_err(|e| format!("Invalid regex: {}", e))?;
+    for reference in capture_references(replacement) {
+        let exists = match reference.parse::<usize>() {
+            Ok(index) => index < re.captures_len(),
+            Err(_) => re.capture_names().flatten().any(|name| name == reference),
+        };
+        if !exists {
+            return Err(format!(
+                "Replacement refers to ${{{}}}, but the pattern has no such group",
+                reference
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Extracts the group names/indices a replacement string refers to, following
+/// the `regex` crate's syntax: `$name`, `${name}`, and `$$` for a literal `$`.
+fn capture_references(replacement: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    let mut rest = replacement;
+    while let Some(dollar) = rest.find('$') {
+        rest = &rest[dollar + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            if let Some(end) = braced.find('}') {
+                references.push(braced[..end].to_string());
+                rest = &braced[end + 1..];
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if end > 0 {
+                references.push(rest[..end].to_string());
+            }
+            rest = &rest[end..];
+        }
+    }
+    references
+}
+
+/// Most suggestions `suggest_entries` returns.
+const MAX_SUGGESTIONS: usize = 20;
+/// A token must appear at least this often to be suggested.
+const MIN_SUGGESTION_COUNT: usize = 2;
+
+/// Common English words that are never worth a vocabulary entry.
+const STOPWORDS: &str = "the and for are but not you all any can had her was one our out day get \
+    has him his how man new now old see two way who did its let put say she too use that with \
+    have this will your from they know want been good much some time very when come here just \
+    like long make many more only over such take than them well were what about there their \
+    would could should which these those think going really because then also into after other \
+    where yeah okay right thing things need said does doing done being still even back first \
+    last next maybe something anything people little great today tomorrow yesterday work gonna \
+    we're i'm it's don't that's can't didn't you're they're i'll";
+
+/// Suggests vocabulary phrases from past transcripts: frequent tokens that aren't
+/// common words and aren't already a phrase or replacement in `vocabulary`.
+/// Sorted by descending frequency.
+pub fn suggest_entries<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    vocabulary: &Vocabulary,
+) -> Vec<String> {
+    let covered: HashSet<String> = vocabulary
+        .entries
+        .iter()
+        .flat_map(|e| [e.phrase.as_str(), e.replacement.as_str()])
+        .flat_map(|p| p.split_whitespace())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    // Count case-insensitively, but suggest the spelling seen first
+    let mut counts: HashMap<String, (usize, String)> = HashMap::new();
+    for text in texts {
+        let tokens = text
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .map(|t| t.trim_matches('\''));
+        for token in tokens {
+            if token.chars().count() < 3 || token.chars().all(|c| c.is_numeric()) {
+                continue;
+            }
+            let key = token.to_lowercase();
+            if STOPWORDS.split_whitespace().any(|w| w == key) || covered.contains(&key) {
+                continue;
+            }
+            counts
+                .entry(key)
+                .or_insert_with(|| (0, token.to_string()))
+                .0 += 1;
+        }
+    }
+
+    let mut suggestions: Vec<(usize, String)> = counts
+        .into_values()
+        .filter(|(count, _)| *count >= MIN_SUGGESTION_COUNT)
+        .collect();
+    suggestions.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, token)| token)
+        .collect()
+}
+
+/// Outcome of `import_vocabulary_csv`: how many entries were added or updated,
+/// and the 1-based line numbers that couldn't be read as a phrase/replacement
+/// pair.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CsvImportResult {
+    pub imported: usize,
+    pub skipped_lines: Vec<usize>,
+}
+
+/// Column names that mark the first row of a CSV as a header rather than an entry.
+const CSV_HEADER_NAMES: [&str; 8] = [
+    "phrase",
+    "replacement",
+    "typo",
+    "correct",
+    "correction",
+    "from",
+    "to",
+    "word",
+];
+
+/// Splits one CSV line into fields. Fields may be quoted, with `""` for a
+/// literal quote; an unterminated quote makes the line unreadable.
+fn parse_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut in_quotes = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push(field);
+    Some(fields.into_iter().map(|f| f.trim().to_string()).collect())
+}
+
+/// Reads a two-column "phrase,replacement" CSV, such as an autocorrect list.
+/// Blank lines and a header row are ignored; lines without exactly two
+/// non-empty fields are reported by line number.
+pub fn parse_csv(contents: &str) -> (Vec<(String, String)>, Vec<usize>) {
+    let mut pairs = Vec::new();
+    let mut skipped = Vec::new();
+    let mut first_row = true;
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = parse_csv_line(line);
+        let is_header = std::mem::take(&mut first_row)
+            && fields.as_ref().is_some_and(|fields| {
+                fields
+                    .iter()
+                    .all(|f| CSV_HEADER_NAMES.contains(&f.to_lowercase().as_str()))
+            });
+        if is_header {
+            continue;
+        }
+        match fields.as_deref() {
+            Some([phrase, replacement]) if !phrase.is_empty() && !replacement.is_empty() => {
+                pairs.push((phrase.clone(), replacement.clone()));
+            }
+            _ => skipped.push(index + 1),
+        }
+    }
+    (pairs, skipped)
+}
+
+/// Adds `pairs` to `vocabulary` as enabled whole-word entries with ids from
+/// `next_id` up. With `merge`, an existing entry for the same phrase (ignoring
+/// case) gets the new replacement and other entries are kept; otherwise the
+/// imported entries replace all existing ones. Returns how many were imported.
+pub fn import_pairs(
+    vocabulary: &mut Vocabulary,
+    pairs: Vec<(String, String)>,
+    merge: bool,
+    next_id: u64,
+) -> usize {
+    if !merge {
+        vocabulary.entries.clear();
+    }
+    // Generated ids must not collide with the entries being kept
+    let mut next_id = vocabulary
+        .entries
+        .iter()
+        .map(|e| e.id + 1)
+        .fold(next_id, u64::max);
+
+    let imported = pairs.len();
+    for (phrase, replacement) in pairs {
+        let existing = vocabulary
+            .entries
+            .iter_mut()
+            .find(|e| e.phrase.to_lowercase() == phrase.to_lowercase());
+        match existing {
+            Some(entry) => entry.replacement = replacement,
+            None => {
+                vocabulary.entries.push(VocabEntry {
+                    id: next_id,
+                    phrase,
+                    replacement,
+                    enabled: true,
+                    match_mode: MatchMode::default(),
+                    priority: 0,
+                    fuzzy: false,
+                    is_regex: false,
+                });
+                next_id += 1;
+            }
+        }
+    }
+    imported
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,11 +600,18 @@ mod tests {
             phrase: phrase.to_string(),
             replacement: replacement.to_string(),
             enabled: true,
+            match_mode: MatchMode::WholeWord,
+            priority: 0,
+            fuzzy: false,
+            is_regex: false,
         }
     }
 
     fn make_vocab(entries: Vec<VocabEntry>) -> Vocabulary {
-        Vocabulary { entries }
+        Vocabulary {
+            entries,
+            ..Vocabulary::default()
+        }
     }
 
     #[test]
@@ -210,6 +663,10 @@ mod tests {
                 phrase: "recieve".to_string(),
                 replacement: "receive".to_string(),
                 enabled: true,
+                match_mode: MatchMode::WholeWord,
+                priority: 0,
+                fuzzy: false,
+                is_regex: false,
             },
         ]);
         let result = apply_corrections("I recieve teh package", &vocab);
@@ -224,12 +681,176 @@ mod tests {
             phrase: "teh".to_string(),
             replacement: "the".to_string(),
             enabled: false,
+            match_mode: MatchMode::WholeWord,
+            priority: 0,
+            fuzzy: false,
+            is_regex: false,
         }]);
         let result = apply_corrections("I went to teh store", &vocab);
         assert_eq!(result.text, "I went to teh store");
         assert_eq!(result.corrections.len(), 0);
     }
 
+    #[test]
+    fn test_anywhere_matches_inside_word() {
+        let vocab = make_vocab(vec![VocabEntry {
+            match_mode: MatchMode::Anywhere,
+            ..make_entry("msft", "microsoft")
+        }]);
+        let result = apply_corrections("Msftware ships msft builds", &vocab);
+        assert_eq!(result.text, "Microsoftware ships microsoft builds");
+        assert_eq!(result.corrections.len(), 2);
+    }
+
+    #[test]
+    fn test_whole_word_refuses_inside_word() {
+        let vocab = make_vocab(vec![make_entry("msft", "microsoft")]);
+        let result = apply_corrections("msftware", &vocab);
+        assert_eq!(result.text, "msftware");
+        assert_eq!(result.corrections.len(), 0);
+    }
+
+    #[test]
+    fn test_longer_phrase_wins() {
+        let vocab = make_vocab(vec![
+            make_entry("York", "Yorkshire"),
+            make_entry("New York", "NYC"),
+        ]);
+        let result = apply_corrections("I love New York", &vocab);
+        assert_eq!(result.text, "I love NYC");
+        assert_eq!(result.corrections.len(), 1);
+    }
+
+    #[test]
+    fn test_priority_overrides_length() {
+        let vocab = make_vocab(vec![
+            make_entry("New York", "NYC"),
+            VocabEntry {
+                priority: 1,
+                ..make_entry("York", "Yorkshire")
+            },
+        ]);
+        let result = apply_corrections("I love New York", &vocab);
+        assert_eq!(result.text, "I love New Yorkshire");
+    }
+
+    #[test]
+    fn test_no_cascading_replacements() {
+        let vocab = make_vocab(vec![make_entry("the", "cat"), make_entry("cat", "dog")]);
+        let result = apply_corrections("the cat sat", &vocab);
+        assert_eq!(result.text, "cat dog sat");
+        assert_eq!(result.corrections.len(), 2);
+        assert_eq!(result.corrections[0].position, 0);
+        assert_eq!(result.corrections[1].position, 4);
+    }
+
+    #[test]
+    fn test_same_span_entries_become_candidates() {
+        let vocab = make_vocab(vec![
+            make_entry("jason", "JSON"),
+            VocabEntry {
+                id: 2,
+                priority: -1,
+                ..make_entry("jason", "Jason")
+            },
+        ]);
+        let mut result = apply_corrections("parse the jason file", &vocab);
+        assert_eq!(result.text, "parse the JSON file");
+        let ids: Vec<u64> = result.corrections[0]
+            .candidates
+            .iter()
+            .map(|c| c.entry_id)
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        choose_candidate(&mut result.corrections, 10, 2).unwrap();
+        assert_eq!(
+            splice_corrections("parse the jason file", &result.corrections),
+            "parse the Jason file"
+        );
+        assert!(choose_candidate(&mut result.corrections, 10, 3).is_err());
+    }
+
+    #[test]
+    fn test_overlapping_span_is_not_a_candidate() {
+        let vocab = make_vocab(vec![
+            make_entry("new york", "New York"),
+            VocabEntry {
+                id: 2,
+                ..make_entry("york", "Yorkshire")
+            },
+        ]);
+        let result = apply_corrections("visit new york", &vocab);
+        assert_eq!(result.text, "visit New York");
+        assert_eq!(result.corrections.len(), 1);
+        assert_eq!(result.corrections[0].candidates.len(), 1);
+    }
+
+    #[test]
+    fn test_single_candidate_not_serialized() {
+        let vocab = make_vocab(vec![make_entry("teh", "the")]);
+        let result = apply_corrections("teh", &vocab);
+        let json = serde_json::to_value(&result.corrections[0]).unwrap();
+        assert!(json.get("candidates").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_corrects_one_character_off() {
+        let vocab = make_vocab(vec![VocabEntry {
+            fuzzy: true,
+            ..make_entry("kubernetes", "Kubernetes")
+        }]);
+        let result = apply_corrections("deploy to kubernetis now", &vocab);
+        assert_eq!(result.text, "deploy to Kubernetes now");
+        assert_eq!(result.corrections.len(), 1);
+        assert_eq!(result.corrections[0].original, "kubernetis");
+    }
+
+    #[test]
+    fn test_fuzzy_multi_word_phrase() {
+        let vocab = make_vocab(vec![VocabEntry {
+            fuzzy: true,
+            ..make_entry("Tauri app", "Tauri app")
+        }]);
+        let result = apply_corrections("my Tory app crashed", &vocab);
+        // Two edits exceed the default distance of one
+        assert_eq!(result.text, "my Tory app crashed");
+
+        let result = apply_corrections("my Taury app crashed", &vocab);
+        assert_eq!(result.text, "my Tauri app crashed");
+    }
+
+    #[test]
+    fn test_fuzzy_skips_short_phrases_and_non_fuzzy_entries() {
+        let vocab = make_vocab(vec![
+            VocabEntry {
+                fuzzy: true,
+                ..make_entry("git", "Git")
+            },
+            make_entry("Postgres", "PostgreSQL"),
+        ]);
+        let result = apply_corrections("get the postgress logs", &vocab);
+        assert_eq!(result.text, "get the postgress logs");
+    }
+
+    #[test]
+    fn test_suggest_entries_by_frequency() {
+        let texts = [
+            "Deploy the Kubernetes cluster with Terraform",
+            "Kubernetes and Terraform again, then kubernetes",
+            "Just a one-off Ansible mention",
+        ];
+        let suggestions = suggest_entries(texts, &make_vocab(vec![]));
+        assert_eq!(suggestions, vec!["Kubernetes", "Terraform"]);
+    }
+
+    #[test]
+    fn test_suggest_entries_skips_covered_tokens() {
+        let texts = ["Kubernetes Kubernetes kubes kubes"];
+        let vocab = make_vocab(vec![make_entry("kubes", "Kubernetes")]);
+        assert!(suggest_entries(texts, &vocab).is_empty());
+    }
+
     #[test]
     fn test_empty_vocabulary() {
         let vocab = make_vocab(vec![]);
@@ -237,4 +858,95 @@ mod tests {
         assert_eq!(result.text, "Hello world");
         assert_eq!(result.corrections.len(), 0);
     }
+
+    #[test]
+    fn test_regex_entry_expands_capture_groups() {
+        let vocab = make_vocab(vec![VocabEntry {
+            is_regex: true,
+            ..make_entry(r"(\w+) dot com", "$1.com")
+        }]);
+        let result = apply_corrections("visit example dot com today", &vocab);
+        assert_eq!(result.text, "visit example.com today");
+        assert_eq!(result.corrections[0].original, "example dot com");
+    }
+
+    #[test]
+    fn test_regex_entry_is_case_insensitive_and_whole_word() {
+        let vocab = make_vocab(vec![VocabEntry {
+            is_regex: true,
+            ..make_entry("colou?r|hue", "tint")
+        }]);
+        let result = apply_corrections("Color, hues and colour", &vocab);
+        assert_eq!(result.text, "Tint, hues and tint");
+    }
+
+    #[test]
+    fn test_literal_entry_is_not_a_regex() {
+        let vocab = make_vocab(vec![make_entry("a.m", "AM")]);
+        let result = apply_corrections("at 9 a.m or 9 axm", &vocab);
+        assert_eq!(result.text, "at 9 AM or 9 axm");
+    }
+
+    #[test]
+    fn test_validate_pattern_rejects_invalid_regex() {
+        assert!(validate_pattern("foo(bar", true, "x").is_err());
+        // The same text is fine as a literal phrase
+        assert!(validate_pattern("foo(bar", false, "x").is_ok());
+        assert!(validate_pattern(r"colou?r", true, "color").is_ok());
+    }
+
+    #[test]
+    fn test_validate_pattern_checks_capture_references() {
+        assert!(validate_pattern(r"(\w+)\.com", true, "$1 dot com").is_ok());
+        assert!(validate_pattern(r"(?P<site>\w+)\.com", true, "${site} dot com").is_ok());
+        assert!(validate_pattern(r"(\w+)\.com", true, "$2 dot com").is_err());
+        assert!(validate_pattern(r"(\w+)\.com", true, "$site").is_err());
+        // `$$` is an escaped dollar sign, not a reference
+        assert!(validate_pattern(r"\w+", true, "$$5").is_ok());
+    }
+
+    #[test]
+    fn test_parse_csv_with_header_and_quotes() {
+        let csv =
+            "typo,correct\nteh,the\n\n\"recieve\", \"receive\"\n\"a, b\",\"say \"\"hi\"\"\"\n";
+        let (pairs, skipped) = parse_csv(csv);
+        assert_eq!(
+            pairs,
+            vec![
+                ("teh".to_string(), "the".to_string()),
+                ("recieve".to_string(), "receive".to_string()),
+                ("a, b".to_string(), "say \"hi\"".to_string()),
+            ]
+        );
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv_skips_malformed_lines() {
+        let csv = "teh,the\nonlyone\na,b,c\n\"open,x\n,empty\nadn,and";
+        let (pairs, skipped) = parse_csv(csv);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(skipped, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_import_pairs_merge_and_replace() {
+        let mut vocab = make_vocab(vec![
+            make_entry("Teh", "teh"),
+            make_entry("gonna", "going to"),
+        ]);
+        let pairs = vec![
+            ("teh".to_string(), "the".to_string()),
+            ("adn".to_string(), "and".to_string()),
+        ];
+        assert_eq!(import_pairs(&mut vocab, pairs.clone(), true, 1), 2);
+        assert_eq!(vocab.entries.len(), 3);
+        assert_eq!(vocab.entries[0].replacement, "the");
+        assert_eq!(vocab.entries[2].id, 2);
+
+        assert_eq!(import_pairs(&mut vocab, pairs, false, 100), 2);
+        let phrases: Vec<&str> = vocab.entries.iter().map(|e| e.phrase.as_str()).collect();
+        assert_eq!(phrases, vec!["teh", "adn"]);
+        assert_eq!(vocab.entries[0].id, 100);
+    }
 }
@@ -31,9 +31,12 @@ export function HistoryView() {
   const [expandedId, setExpandedId] = useState<number | null>(null);
   const [copiedId, setCopiedId] = useState<number | null>(null);
   const [search, setSearch] = useState("");
+  const [historyEnabled, setHistoryEnabled] = useState(true);
 
   const fetchHistory = async () => {
     try {
+      // Turning history on or off also emits history-updated
+      setHistoryEnabled(await invoke<boolean>("get_history_enabled"));
       const result = await invoke<HistoryEntry[]>("get_history");
       setEntries(result);
     } catch (e) {
@@ -93,7 +96,9 @@ export function HistoryView() {
         <div>
           <h1 className="text-lg font-semibold">Transcription History</h1>
           <p className="text-sm text-white/50">
-            {entries.length} transcription{entries.length !== 1 ? "s" : ""}
+            {historyEnabled
+              ? `${entries.length} transcription${entries.length !== 1 ? "s" : ""}`
+              : "Off"}
           </p>
         </div>
         {entries.length > 0 && (
@@ -117,7 +122,16 @@ export function HistoryView() {
       )}
 
       <div className="flex flex-col gap-2 flex-1 overflow-y-auto">
-        {filtered.length === 0 && (
+        {!historyEnabled && (
+          <div className="flex-1 flex flex-col items-center justify-center gap-1 text-center">
+            <span className="text-white/60 text-sm">History is turned off</span>
+            <span className="text-white/30 text-xs">
+              New transcriptions aren't saved. Turn on Save History in Settings to keep them here.
+            </span>
+          </div>
+        )}
+
+        {historyEnabled && filtered.length === 0 && (
           <div className="flex-1 flex items-center justify-center text-white/30 text-sm">
             {search ? "No matching transcriptions" : "No transcriptions yet"}
           </div>
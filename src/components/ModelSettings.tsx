@@ -3,6 +3,17 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { enable, disable, isEnabled } from "@tauri-apps/plugin-autostart";
 import { VocabularyModal } from "./VocabularyModal";
+import { PassphraseModal, PassphraseMode } from "./PassphraseModal";
+
+interface EncryptionStatus {
+  enabled: boolean;
+  unlocked: boolean;
+}
+
+interface WhisperThreads {
+  threads: number;
+  available: number;
+}
 
 interface ModelInfo {
   name: string;
@@ -45,7 +56,23 @@ export function ModelSettings() {
   const [translationEnabled, setTranslationEnabled] = useState(false);
   const [translationTargetLang, setTranslationTargetLang] = useState("en");
   const [vocabEnabled, setVocabEnabled] = useState<boolean>(true);
+  const [historyEnabled, setHistoryEnabled] = useState<boolean>(true);
+  const [whisperThreads, setWhisperThreads] = useState<WhisperThreads>({
+    threads: 0,
+    available: 4,
+  });
   const [showVocabModal, setShowVocabModal] = useState(false);
+  const [encryption, setEncryption] = useState<EncryptionStatus>({
+    enabled: false,
+    unlocked: true,
+  });
+  const [passphraseMode, setPassphraseMode] = useState<PassphraseMode | null>(null);
+
+  const fetchEncryptionStatus = async () => {
+    const status = await invoke<EncryptionStatus>("get_encryption_status");
+    setEncryption(status);
+    return status;
+  };
 
   const fetchModels = async () => {
     try {
@@ -63,7 +90,15 @@ export function ModelSettings() {
     invoke<boolean>("get_translation_enabled").then(setTranslationEnabled);
     invoke<string>("get_translation_target_lang").then(setTranslationTargetLang);
     invoke<boolean>("get_vocab_enabled").then(setVocabEnabled);
+    invoke<boolean>("get_history_enabled").then(setHistoryEnabled);
+    invoke<WhisperThreads>("get_whisper_threads").then(setWhisperThreads);
     isEnabled().then(setAutostart).catch(() => {});
+    // Encrypted data stays unreadable until the passphrase is entered
+    fetchEncryptionStatus().then((status) => {
+      if (status.enabled && !status.unlocked) {
+        setPassphraseMode("unlock");
+      }
+    });
 
     const unlistenModel = listen("model-changed", () => {
       fetchModels();
@@ -88,6 +123,17 @@ export function ModelSettings() {
     }
   };
 
+  const handleWhisperThreadsChange = async (threads: number) => {
+    const oldThreads = whisperThreads.threads;
+    setWhisperThreads({ ...whisperThreads, threads });
+    try {
+      await invoke("set_whisper_threads", { threads });
+    } catch (e) {
+      setWhisperThreads({ ...whisperThreads, threads: oldThreads });
+      setError(String(e));
+    }
+  };
+
   const handleToggleAutostart = async () => {
     const newValue = !autostart;
     setAutostart(newValue);
@@ -125,6 +171,17 @@ export function ModelSettings() {
     }
   };
 
+  const handleToggleHistory = async () => {
+    const newValue = !historyEnabled;
+    setHistoryEnabled(newValue);
+    try {
+      await invoke("set_history_enabled", { enabled: newValue });
+    } catch (e) {
+      setHistoryEnabled(!newValue);
+      setError(String(e));
+    }
+  };
+
   const handleToggleTranslation = async () => {
     const newValue = !translationEnabled;
     setTranslationEnabled(newValue);
@@ -136,6 +193,25 @@ export function ModelSettings() {
     }
   };
 
+  const handleToggleEncryption = async () => {
+    setError(null);
+    if (!encryption.enabled) {
+      setPassphraseMode("enable");
+      return;
+    }
+    try {
+      await invoke("disable_encryption");
+    } catch (e) {
+      setError(String(e));
+    }
+    await fetchEncryptionStatus();
+  };
+
+  const handlePassphraseDone = async () => {
+    setPassphraseMode(null);
+    await fetchEncryptionStatus();
+  };
+
   const handleTranslationTargetChange = async (newTarget: string) => {
     const oldTarget = translationTargetLang;
     setTranslationTargetLang(newTarget);
@@ -188,6 +264,28 @@ export function ModelSettings() {
         </select>
       </div>
 
+      <div className="mb-4">
+        <label className="text-sm font-medium mb-1.5 flex items-center justify-between">
+          <span>Transcription Threads</span>
+          <span className="text-xs text-white/40">
+            {whisperThreads.threads === 0
+              ? `Auto (${whisperThreads.available})`
+              : whisperThreads.threads}
+          </span>
+        </label>
+        <input
+          type="range"
+          min={0}
+          max={whisperThreads.available}
+          value={whisperThreads.threads}
+          onChange={(e) => handleWhisperThreadsChange(Number(e.target.value))}
+          className="w-full accent-blue-500 cursor-pointer"
+        />
+        <span className="text-xs text-white/40">
+          More threads transcribe faster but use more CPU
+        </span>
+      </div>
+
       <div className="flex flex-col gap-2 flex-1 min-h-0 overflow-y-auto pr-1">
         {filteredModels.map((model) => (
           <ModelCard
@@ -309,6 +407,59 @@ export function ModelSettings() {
             Manage Vocabulary...
           </button>
         )}
+
+        <button
+          onClick={handleToggleHistory}
+          className="flex items-center justify-between w-full"
+        >
+          <div className="flex flex-col items-start">
+            <span className="text-sm font-medium">Save History</span>
+            <span className="text-xs text-white/40">
+              {historyEnabled
+                ? "Keeps your transcriptions in the History window"
+                : "Transcriptions are never written to disk"}
+            </span>
+          </div>
+          <div
+            className={`w-9 h-5 rounded-full transition-colors flex items-center ${
+              historyEnabled ? "bg-blue-500 justify-end" : "bg-white/20 justify-start"
+            }`}
+          >
+            <div className="w-4 h-4 bg-white rounded-full mx-0.5" />
+          </div>
+        </button>
+
+        <button
+          onClick={handleToggleEncryption}
+          className="flex items-center justify-between w-full"
+        >
+          <div className="flex flex-col items-start">
+            <span className="text-sm font-medium">Encrypt History</span>
+            <span className="text-xs text-white/40">
+              {!encryption.enabled
+                ? "History and vocabulary are stored as plain files"
+                : encryption.unlocked
+                  ? "History and vocabulary are encrypted with your passphrase"
+                  : "Locked — enter your passphrase to use history and vocabulary"}
+            </span>
+          </div>
+          <div
+            className={`w-9 h-5 rounded-full transition-colors flex items-center ${
+              encryption.enabled ? "bg-blue-500 justify-end" : "bg-white/20 justify-start"
+            }`}
+          >
+            <div className="w-4 h-4 bg-white rounded-full mx-0.5" />
+          </div>
+        </button>
+
+        {encryption.enabled && !encryption.unlocked && (
+          <button
+            onClick={() => setPassphraseMode("unlock")}
+            className="text-xs text-blue-400 hover:text-blue-300 transition-colors text-left pl-0.5"
+          >
+            Unlock...
+          </button>
+        )}
       </div>
 
       {error && <div className="mt-3 text-red-400 text-sm">{error}</div>}
@@ -317,6 +468,12 @@ export function ModelSettings() {
         visible={showVocabModal}
         onClose={() => setShowVocabModal(false)}
       />
+
+      <PassphraseModal
+        mode={passphraseMode}
+        onDone={handlePassphraseDone}
+        onClose={() => setPassphraseMode(null)}
+      />
     </div>
   );
 }
@@ -9,6 +9,9 @@ import { AudioWaveform } from "./AudioWaveform";
 import { Settings } from "./Settings";
 
 const CORRECTION_AUTO_DISMISS_MS = 3000;
+// Number keys reach this many candidates (matches the native key tap)
+const CANDIDATE_KEYS = 4;
+const DEVICE_NOTICE_MS = 4000;
 
 function formatDuration(ms: number): string {
   const totalSeconds = Math.floor(ms / 1000);
@@ -45,7 +48,36 @@ export function Overlay({ state }: OverlayProps) {
     }
   }, [partialText, partialTranslation]);
 
-  // CorrectionPreview: auto-dismiss timer with progress tracking
+  // Mic fallbacks and switches, shown as a warning line while recording
+  const [deviceNotice, setDeviceNotice] = useState<string | null>(null);
+
+  useEffect(() => {
+    let timer: ReturnType<typeof setTimeout> | null = null;
+    const unlisten = listen<string>("input-device-changed", (event) => {
+      setDeviceNotice(event.payload);
+      if (timer) clearTimeout(timer);
+      timer = setTimeout(() => setDeviceNotice(null), DEVICE_NOTICE_MS);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+      if (timer) clearTimeout(timer);
+    };
+  }, []);
+
+  // The first correction with alternatives is the one the number keys choose for
+  const choosable =
+    state.type === "CorrectionPreview"
+      ? state.corrections.find((c) => (c.candidates?.length ?? 0) > 1)
+      : undefined;
+  const previewText = state.type === "CorrectionPreview" ? state.text : undefined;
+
+  const chooseCorrection = (position: number, entryId: number) => {
+    invoke("choose_correction", { position, entryId });
+  };
+
+  // CorrectionPreview: auto-dismiss timer with progress tracking; choosing a
+  // candidate changes the text and restarts it
   const [previewProgress, setPreviewProgress] = useState(0);
   const previewTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const previewAnimRef = useRef<number | null>(null);
@@ -91,7 +123,7 @@ export function Overlay({ state }: OverlayProps) {
         previewAnimRef.current = null;
       }
     };
-  }, [state.type]);
+  }, [state.type, previewText]);
 
   // Preview keyboard event listeners (via global NSEvent monitor → Tauri event bridge)
   useEffect(() => {
@@ -103,6 +135,11 @@ export function Overlay({ state }: OverlayProps) {
           invoke("undo_corrections");
         } else if (key === "enter") {
           invoke("accept_corrections");
+        } else if (choosable) {
+          const candidate = choosable.candidates?.[Number(key) - 1];
+          if (candidate) {
+            chooseCorrection(choosable.position, candidate.entry_id);
+          }
         }
       } else if (state.type === "TranslationPreview") {
         if (key === "escape") {
@@ -122,6 +159,7 @@ export function Overlay({ state }: OverlayProps) {
     const handleKeyDown = (e: KeyboardEvent) => {
       if (e.key === "Escape") handleKey("escape");
       else if (e.key === "Enter") handleKey("enter");
+      else if (Number(e.key) >= 1 && Number(e.key) <= CANDIDATE_KEYS) handleKey(e.key);
     };
     document.addEventListener("keydown", handleKeyDown);
 
@@ -129,7 +167,7 @@ export function Overlay({ state }: OverlayProps) {
       unlisten.then((fn) => fn());
       document.removeEventListener("keydown", handleKeyDown);
     };
-  }, [state.type]);
+  }, [state.type, choosable]);
 
   const handleMouseDown = useCallback(async (e: React.MouseEvent) => {
     if (e.button !== 0) return;
@@ -195,6 +233,7 @@ export function Overlay({ state }: OverlayProps) {
   const remainingCount = isCorrectionPreview
     ? Math.max(0, state.corrections.length - 5)
     : 0;
+  const choosableCount = Math.min(choosable?.candidates?.length ?? 0, CANDIDATE_KEYS);
 
   return (
     <div
@@ -288,7 +327,27 @@ export function Overlay({ state }: OverlayProps) {
                   <div key={i} className="flex items-center gap-1.5 text-xs">
                     <span className="text-white/40 line-through">{c.original}</span>
                     <span className="text-white/30">→</span>
-                    <span className="text-green-400">{c.replacement}</span>
+                    {c.candidates && c.candidates.length > 1 ? (
+                      c.candidates.map((candidate, n) => (
+                        <button
+                          key={candidate.entry_id}
+                          onMouseDown={(e) => e.stopPropagation()}
+                          onClick={() => chooseCorrection(c.position, candidate.entry_id)}
+                          className={`px-1 rounded transition-colors ${
+                            candidate.replacement === c.replacement
+                              ? "bg-green-400/20 text-green-400"
+                              : "text-white/50 hover:text-white/80"
+                          }`}
+                        >
+                          {c === choosable && n < CANDIDATE_KEYS && (
+                            <span className="text-white/30 mr-0.5">{n + 1}</span>
+                          )}
+                          {candidate.replacement}
+                        </button>
+                      ))
+                    ) : (
+                      <span className="text-green-400">{c.replacement}</span>
+                    )}
                   </div>
                 ))}
                 {remainingCount > 0 && (
@@ -302,6 +361,7 @@ export function Overlay({ state }: OverlayProps) {
                 />
               </div>
               <span className="text-white/25 text-[10px] text-center">
+                {choosableCount > 0 && `1–${choosableCount} to choose · `}
                 Enter to accept · Esc to undo
               </span>
             </div>
@@ -325,6 +385,13 @@ export function Overlay({ state }: OverlayProps) {
             </div>
           )}
 
+          {state.type === "Recording" && deviceNotice && (
+            <div className="flex items-center gap-1.5">
+              <div className="w-1.5 h-1.5 rounded-full bg-yellow-400 flex-shrink-0" />
+              <span className="text-yellow-400 text-xs">{deviceNotice}</span>
+            </div>
+          )}
+
           {state.type === "Recording" && (
             <AudioWaveform levels={audioLevels} />
           )}
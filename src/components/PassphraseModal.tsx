@@ -0,0 +1,98 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+export type PassphraseMode = "unlock" | "enable";
+
+interface PassphraseModalProps {
+  mode: PassphraseMode | null;
+  onDone: () => void;
+  onClose: () => void;
+}
+
+export function PassphraseModal({ mode, onDone, onClose }: PassphraseModalProps) {
+  const [passphrase, setPassphrase] = useState("");
+  const [confirmation, setConfirmation] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setPassphrase("");
+    setConfirmation("");
+    setError(null);
+  }, [mode]);
+
+  if (mode === null) return null;
+
+  const mismatch = mode === "enable" && passphrase !== confirmation;
+  const canSubmit = passphrase.length > 0 && !mismatch && !busy;
+
+  const handleSubmit = async () => {
+    if (!canSubmit) return;
+    setBusy(true);
+    setError(null);
+    try {
+      await invoke(mode === "unlock" ? "unlock_data" : "enable_encryption", {
+        passphrase,
+      });
+      onDone();
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="absolute inset-0 flex items-center justify-center z-50 bg-black/50">
+      <div className="bg-black/90 backdrop-blur-xl rounded-2xl border border-white/10 w-80 p-4 flex flex-col gap-3">
+        <div className="flex items-center justify-between">
+          <span className="text-sm font-medium text-white">
+            {mode === "unlock" ? "Unlock History" : "Encrypt History"}
+          </span>
+          <button
+            onClick={onClose}
+            className="text-white/40 hover:text-white/80 text-xs transition-colors"
+          >
+            ✕
+          </button>
+        </div>
+
+        <span className="text-xs text-white/50">
+          {mode === "unlock"
+            ? "Enter your passphrase to read and save history and vocabulary this session."
+            : "History and vocabulary will be encrypted with this passphrase. It can't be recovered if you forget it."}
+        </span>
+
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          onKeyDown={(e) => e.key === "Enter" && handleSubmit()}
+          placeholder="Passphrase"
+          className="bg-white/10 text-white text-xs px-2 py-1.5 rounded-lg border border-white/10 outline-none focus:border-white/30 placeholder:text-white/30"
+        />
+        {mode === "enable" && (
+          <input
+            type="password"
+            value={confirmation}
+            onChange={(e) => setConfirmation(e.target.value)}
+            onKeyDown={(e) => e.key === "Enter" && handleSubmit()}
+            placeholder="Confirm passphrase"
+            className="bg-white/10 text-white text-xs px-2 py-1.5 rounded-lg border border-white/10 outline-none focus:border-white/30 placeholder:text-white/30"
+          />
+        )}
+
+        {error && <span className="text-xs text-red-400">{error}</span>}
+
+        <button
+          onClick={handleSubmit}
+          disabled={!canSubmit}
+          className="text-xs px-2.5 py-1.5 rounded-lg bg-blue-500 text-white hover:bg-blue-600 disabled:opacity-30 disabled:cursor-default transition-colors"
+        >
+          {busy ? "Working..." : mode === "unlock" ? "Unlock" : "Encrypt"}
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -6,6 +6,7 @@ interface VocabEntry {
   phrase: string;
   replacement: string;
   enabled: boolean;
+  is_regex: boolean;
 }
 
 interface VocabularyModalProps {
@@ -17,6 +18,8 @@ export function VocabularyModal({ visible, onClose }: VocabularyModalProps) {
   const [entries, setEntries] = useState<VocabEntry[]>([]);
   const [phrase, setPhrase] = useState("");
   const [replacement, setReplacement] = useState("");
+  const [isRegex, setIsRegex] = useState(false);
+  const [patternError, setPatternError] = useState<string | null>(null);
 
   const fetchEntries = async () => {
     try {
@@ -33,20 +36,36 @@ export function VocabularyModal({ visible, onClose }: VocabularyModalProps) {
     }
   }, [visible]);
 
+  useEffect(() => {
+    if (!isRegex || !phrase.trim()) {
+      setPatternError(null);
+      return;
+    }
+    invoke("validate_vocab_pattern", {
+      phrase: phrase.trim(),
+      isRegex: true,
+      replacement: replacement.trim(),
+    })
+      .then(() => setPatternError(null))
+      .catch((e) => setPatternError(String(e)));
+  }, [phrase, replacement, isRegex]);
+
   if (!visible) return null;
 
   const handleAdd = async () => {
     const trimmedPhrase = phrase.trim();
     const trimmedReplacement = replacement.trim();
-    if (!trimmedPhrase || !trimmedReplacement) return;
+    if (!trimmedPhrase || !trimmedReplacement || patternError) return;
 
     try {
       await invoke("add_vocab_entry", {
         phrase: trimmedPhrase,
         replacement: trimmedReplacement,
+        isRegex,
       });
       setPhrase("");
       setReplacement("");
+      setIsRegex(false);
       await fetchEntries();
     } catch (e) {
       console.error("Failed to add entry:", e);
@@ -65,10 +84,7 @@ export function VocabularyModal({ visible, onClose }: VocabularyModalProps) {
   const handleToggleEnabled = async (entry: VocabEntry) => {
     try {
       await invoke("update_vocab_entry", {
-        id: entry.id,
-        phrase: entry.phrase,
-        replacement: entry.replacement,
-        enabled: !entry.enabled,
+        entry: { ...entry, enabled: !entry.enabled },
       });
       await fetchEntries();
     } catch (e) {
@@ -106,14 +122,28 @@ export function VocabularyModal({ visible, onClose }: VocabularyModalProps) {
             placeholder="Replacement"
             className="flex-1 bg-white/10 text-white text-xs px-2 py-1.5 rounded-lg border border-white/10 outline-none focus:border-white/30 placeholder:text-white/30"
           />
+          <button
+            onClick={() => setIsRegex(!isRegex)}
+            title="Treat the phrase as a regular expression ($1 in the replacement inserts a group)"
+            className={`text-xs px-1.5 py-1.5 rounded-lg font-mono transition-colors ${
+              isRegex
+                ? "bg-blue-500/30 text-blue-300"
+                : "bg-white/10 text-white/40 hover:text-white/70"
+            }`}
+          >
+            .*
+          </button>
           <button
             onClick={handleAdd}
-            disabled={!phrase.trim() || !replacement.trim()}
+            disabled={!phrase.trim() || !replacement.trim() || !!patternError}
             className="text-xs px-2.5 py-1.5 rounded-lg bg-blue-500 text-white hover:bg-blue-600 disabled:opacity-30 disabled:cursor-default transition-colors"
           >
             Add
           </button>
         </div>
+        {patternError && (
+          <span className="text-xs text-red-400">{patternError}</span>
+        )}
 
         <div className="max-h-[400px] overflow-y-auto flex flex-col gap-1.5">
           {entries.length === 0 && (
@@ -130,7 +160,9 @@ export function VocabularyModal({ visible, onClose }: VocabularyModalProps) {
               <div className="flex-1 min-w-0">
                 <div className="flex items-center gap-1.5 text-xs">
                   <span
-                    className={`truncate ${entry.enabled ? "text-white/80" : "text-white/30"}`}
+                    className={`truncate ${entry.enabled ? "text-white/80" : "text-white/30"} ${
+                      entry.is_regex ? "font-mono" : ""
+                    }`}
                   >
                     {entry.phrase}
                   </span>
@@ -3,6 +3,13 @@ import { listen } from "@tauri-apps/api/event";
 
 const NUM_BARS = 48;
 
+interface AudioLevelsPayload {
+  bars: number[];
+  peak: number;
+  average: number;
+  too_quiet: boolean;
+}
+
 export function useAudioLevels(active: boolean): number[] {
   const [levels, setLevels] = useState<number[]>(() => new Array(NUM_BARS).fill(0));
   const smoothedRef = useRef<number[]>(new Array(NUM_BARS).fill(0));
@@ -15,8 +22,8 @@ export function useAudioLevels(active: boolean): number[] {
       return;
     }
 
-    const unlisten = listen<number[]>("audio-levels", (event) => {
-      latestRawRef.current = event.payload;
+    const unlisten = listen<AudioLevelsPayload>("audio-levels", (event) => {
+      latestRawRef.current = event.payload.bars;
     });
 
     let running = true;
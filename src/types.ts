@@ -20,6 +20,11 @@ export type DictationState =
         original: string;
         replacement: string;
         position: number;
+        // Present only when more than one entry matched this span
+        candidates?: Array<{
+          entry_id: number;
+          replacement: string;
+        }>;
       }>;
     }
   | {